@@ -1,4 +1,9 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
+
+use aoc_common::{
+    ocr::OcrError,
+    vm::{self, assemble, Flow, Machine, RegisterFile, State},
+};
 
 impl FromStr for Instruction {
     type Err = AocError;
@@ -18,6 +23,15 @@ impl FromStr for Instruction {
     }
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::AddX(x) => write!(f, "addx {}", x),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel  {
     pub instructions: Vec<Instruction>,
@@ -29,16 +43,17 @@ pub enum AocError {
     ParseError,
     #[error("No solution found")]
     NoSolutionFound,
+    #[error("Error reading the CRT: {0}")]
+    OcrError(#[from] OcrError),
 }
         
 impl FromStr for InputModel {
     type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let result: Result<Vec<Instruction>, Self::Err> = s.lines()
-            .map(|l| l.parse::<Instruction>())
-            .collect();
-        result.map(|instructions| InputModel { instructions })
+        assemble::<Instruction>(s)
+            .map(|instructions| InputModel { instructions })
+            .map_err(|_| AocError::ParseError)
     }
 }
 
@@ -50,12 +65,12 @@ pub struct Registers {
     x: i32,
 }
 
-impl Registers {
-    fn new() -> Registers {
+impl From<&State<RegisterFile>> for Registers {
+    fn from(state: &State<RegisterFile>) -> Self {
         Registers {
-            cycle: 1,
-            pc: 0,
-            x: 1,
+            cycle: state.cycle,
+            pc: state.pc,
+            x: state.registers["x"] as i32,
         }
     }
 }
@@ -66,69 +81,41 @@ pub enum Instruction {
     AddX(i32),
 }
 
-impl Instruction {
-    fn execute(&self, registers: &Registers) -> Registers {
+impl vm::Instruction for Instruction {
+    type Registers = RegisterFile;
+
+    fn cycles(&self) -> usize {
         match self {
-            Instruction::Noop => Registers {
-                pc: registers.pc + 1,
-                cycle: registers.cycle + 1,
-                ..*registers
-            },
-            Instruction::AddX(x) => Registers{
-                pc: registers.pc + 1,
-                x: registers.x + x,
-                cycle: registers.cycle + 2,
-            }
+            Instruction::Noop => 1,
+            Instruction::AddX(_) => 2,
         }
     }
+
+    fn execute(&self, registers: &mut RegisterFile) -> Flow {
+        if let Instruction::AddX(x) = self {
+            registers["x"] += *x as i64;
+        }
+        Flow::Next
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone)]
 pub struct Computer<'a> {
-    code: &'a Vec<Instruction>,
-    registers: Registers,
+    machine: Machine<'a, Instruction>,
 }
 
 impl <'a> Computer<'a> {
-    pub fn new(code: &'a Vec<Instruction>) -> Self {
-        Computer { code, registers: Registers::new() }
+    pub fn new(code: &'a [Instruction]) -> Self {
+        let registers = RegisterFile::new(&["x"]).with("x", 1);
+        Computer { machine: Machine::new(code, registers) }
     }
 
-    fn step(&mut self) {
-        if self.is_halted() {
-            return;
-        }
-        let instruction = &self.code[self.registers.pc];
-        self.registers = instruction.execute(&self.registers);
-    }
-
-    fn is_halted(&self) -> bool {
-        self.registers.pc >= self.code.len()
-    }
-    
-    fn log_execution(&'a mut self) -> impl Iterator<Item=Registers> + 'a {
-        vec![self.registers.clone()].into_iter()
-            .chain(std::iter::from_fn(move || {
-                if self.is_halted() {
-                    None
-                } else {
-                    self.step();
-                    Some(self.registers.clone())
-                }
-            }))
+    pub fn log_execution(&mut self) -> impl Iterator<Item=Registers> + use<'_, 'a> {
+        self.machine.trace().map(|state| Registers::from(&state))
     }
 
     fn run_till_cycle(&mut self, cycle: usize) -> Option<Registers>{
-        std::iter::from_fn(move || {
-            if self.is_halted() {
-                None
-            } else {
-                self.step();
-                Some(self.registers.clone())
-            }
-        })
-        .take_while(|r| r.cycle <= cycle)
-        .last()
+        self.machine.run_to_cycle(cycle).map(Registers::from)
     }
 
     pub fn signal_strength_at_cycle(&mut self, cycle: usize) -> Option<i32> {
@@ -144,18 +131,12 @@ fn pixel_on(cycle: usize, x: i32) -> bool {
     (col - x).abs() < 2
 }
 
-pub fn crt_output(code: &Vec<Instruction>) -> Result<String, AocError> {    
+pub fn crt_output(code: &[Instruction]) -> Result<String, AocError> {    
     let mut computer = Computer::new(code);
-    let mut crt = vec!['.';240];
-    computer.log_execution()
-        .reduce(|last_r, r| {
-            for p in last_r.cycle..r.cycle {
-                if pixel_on(p, last_r.x) {
-                    crt[p-1] = '#';
-                }
-            };
-            r
-        });
+    let crt = computer.machine.cycles()
+        .take(240)
+        .map(|(cycle, registers)| if pixel_on(cycle, registers["x"] as i32) { '#' } else { '.' })
+        .collect::<Vec<_>>();
 
     Ok(crt
        .chunks(40)
@@ -339,6 +320,14 @@ noop";
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_disassemble() {
+        let actual = vm::disassemble(&test_input().instructions);
+        let expected = format!("{}\n", TEST_INPUT);
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_state_log() {
         let code = test_input().instructions;
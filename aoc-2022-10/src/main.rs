@@ -1,5 +1,6 @@
 #![feature(test)]
 use aoc_2022_10::{AocError, InputModel, Computer, crt_output};
+use aoc_common::ocr::read_letters;
 
 const INPUT: &str = include_str!("../data/input.txt");
 
@@ -21,7 +22,8 @@ fn part1(input: &InputModel) -> Result<String,AocError> {
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
     let code = input.instructions.clone();
-    crt_output(&code)
+    let crt = crt_output(&code)?;
+    Ok(read_letters(&crt)?)
 }
 
 fn main() -> Result<(), AocError> {
@@ -216,12 +218,20 @@ noop";
 
     
     #[test]
-    fn test_part2() {
-        let actual = part2(&input_data()).unwrap();
+    fn test_crt_output() {
+        let actual = crt_output(&input_data().instructions).unwrap();
         let expected = TEST_CRT_IMAGE;
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_part2() {
+        // the test program draws stripes rather than letters, the letters are read in aoc-common
+        let actual = part2(&input_data());
+
+        assert!(matches!(actual, Err(AocError::OcrError(_))));
+    }
     
     #[bench]
     fn bench_parse(b: &mut Bencher) {
@@ -1,13 +1,18 @@
-use std::str::FromStr;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 use nom::{
     IResult, Parser, 
     character::complete::*, 
-    branch::*,
     bytes::complete::*, 
     combinator::*, 
     multi::*, 
     sequence::*,
 };
+use operation::Operation;
+
+pub mod operation;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel  {
@@ -18,6 +23,14 @@ pub struct InputModel  {
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("Monkey {monkey} cannot work out the worry level of an item at {worry}")]
+    InvalidWorry { monkey: usize, worry: i64 },
+    #[error("Monkey {0} throws to a monkey which is not there")]
+    UnknownMonkey(usize),
+    #[error("Relief has to divide or reduce by a positive number")]
+    InvalidRelief,
+    #[error("Monkey {0} divides its worry level, which a modulus cannot keep track of")]
+    ModuloRelief(usize),
 }
         
 impl FromStr for InputModel {
@@ -40,60 +53,44 @@ impl Item {
         map(digit1, |s: &str| Item(s.parse().unwrap()))(input)
     }
 
-    fn inspect(&self, operation: &Operation, modulo: i64) -> Item {
-        if modulo == i64::MAX {
-            Item(operation.evaluate(self.0) / 3)
-        } else {
-            Item(operation.evaluate(self.0) % modulo)
-        }
+    fn inspect(&self, operation: &Operation, relief: &Relief) -> Option<Item> {
+        relief.apply(operation.evaluate(self.0)?).map(Item)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub enum Operand {
-    Old,
-    Literal(i64),
+/// How worry levels are kept in check after a monkey inspects an item.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Relief {
+    // the item is undamaged, so worry drops to a fraction
+    Divide(i64),
+    // only the remainder matters to divisibility tests which divide the modulus
+    Modulo(i64),
+    None,
 }
 
-impl Operand {
-    fn parser(input: &str) -> IResult<&str, Self> {
-        alt((
-            map(tag("old"), |_| Operand::Old),
-            map(digit1, |s: &str| Operand::Literal(s.parse().unwrap())),
-        ))(input)
-    }
-
-    fn evaluate(&self, old: i64) -> i64 {
-        match self {
-            Operand::Old => old,
-            Operand::Literal(literal) => *literal,
+impl Relief {
+    /// Keep the worry levels below the least common multiple of the tests, which leaves all
+    /// test results unchanged as long as the operations do not divide; `MonkeyTroop::new`
+    /// refuses troops where they do.
+    pub fn tests(monkeys: &[Monkey]) -> Relief {
+        fn gcd(a: i64, b: i64) -> i64 {
+            if b == 0 { a } else { gcd(b, a % b) }
         }
+        Relief::Modulo(
+            monkeys
+                .iter()
+                .map(|monkey| monkey.test.divisor)
+                .fold(1, |acc, d| acc / gcd(acc, d) * d),
+        )
     }
-}
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub enum  Operation {
-    Add(Operand, Operand),
-    Multiply(Operand, Operand),
-}
-
-impl Operation {
-    fn parser(input: &str) -> IResult<&str, Self> {
-        preceded(
-            tag("new = "),
-            alt((
-                tuple((Operand::parser, tag(" + "), Operand::parser)).map(|(a, _, b)| Operation::Add(a, b)),
-                tuple((Operand::parser, tag(" * "), Operand::parser)).map(|(a, _, b)| Operation::Multiply(a, b)),
-            ))
-        ).parse(input)
-    }
-
-    fn evaluate(&self, old: i64) -> i64 {
+    fn apply(&self, worry: i64) -> Option<i64> {
         match self {
-            Operation::Add(a, b) => a.evaluate(old) + b.evaluate(old),
-            Operation::Multiply(a, b) => a.evaluate(old) * b.evaluate(old),
+            Relief::Divide(k) => worry.checked_div(*k),
+            Relief::Modulo(m) => worry.checked_rem_euclid(*m),
+            Relief::None => Some(worry),
         }
-    }   
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -155,7 +152,7 @@ impl Monkey {
         tuple((
             delimited(tag("Monkey "), MonkeyId::parser, tag(":\n")),
             delimited(tag("  Starting items: "), separated_list0(tag(", "), Item::parser), tag("\n")),
-            delimited(tag("  Operation: "), Operation::parser, tag("\n")),
+            delimited(tag("  Operation: new = "), Operation::expression, tag("\n")),
             Test::parser,
         ))
         .map(|(id, starting_items, operation, test)| 
@@ -163,60 +160,110 @@ impl Monkey {
         .parse(input)
     }
 
-    pub fn inspect_inventory(&mut self, modulo: i64) -> Vec<(Item, MonkeyId)> {
+    pub fn inspect_inventory(&mut self, relief: &Relief) -> Result<Vec<(Item, MonkeyId)>, AocError> {
         let thrown_items = self.items.iter()
-            .map(|item| item.inspect(&self.operation, modulo))
-            .map(|item| (item, self.test.evaluate(&item)))
-            .collect();
+            .map(|item| {
+                item.inspect(&self.operation, relief)
+                    .map(|item| (item, self.test.evaluate(&item)))
+                    .ok_or(AocError::InvalidWorry { monkey: self.id.0, worry: item.0 })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
         self.inspections += self.items.len() as i64;
         self.items.clear();
-        thrown_items
+        Ok(thrown_items)
     }
 }
 
+/// The number of items each monkey inspected in each round.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Inspections {
+    rounds: Vec<Vec<i64>>,
+}
+
+impl Inspections {
+    pub fn rounds(&self) -> &[Vec<i64>] {
+        &self.rounds
+    }
+
+    // the inspections of each monkey over all rounds
+    pub fn totals(&self) -> Vec<i64> {
+        self.rounds.iter().fold(vec![], |mut totals, round| {
+            totals.resize(round.len(), 0);
+            totals.iter_mut().zip(round).for_each(|(t, n)| *t += n);
+            totals
+        })
+    }
+
+    pub fn monkey_business(&self) -> i64 {
+        let mut totals = self.totals();
+        totals.sort();
+        totals.iter().rev().take(2).product()
+    }
+}
+
+impl Display for Inspections {
+    // the way the puzzle reports the inspections
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "== After round {} ==", self.rounds.len())?;
+        for (id, count) in self.totals().iter().enumerate() {
+            writeln!(f, "Monkey {} inspected items {} times.", id, count)?;
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct MonkeyTroop {
     monkeys: Vec<Monkey>,
-    modulo: i64,
+    relief: Relief,
 }
 
 impl MonkeyTroop {
-    pub fn new(monkeys: Vec<Monkey>, part2: bool) -> Self {
-        if part2 {
-            let modulo = monkeys.iter().map(|monkey| monkey.test.divisor).product::<i64>();
-            MonkeyTroop { monkeys, modulo }
-        } else {
-            MonkeyTroop { monkeys, modulo: i64::MAX }
+    pub fn new(monkeys: Vec<Monkey>, relief: Relief) -> Result<Self, AocError> {
+        if matches!(relief, Relief::Divide(k) | Relief::Modulo(k) if k <= 0) {
+            return Err(AocError::InvalidRelief);
         }
+        if let Relief::Modulo(m) = relief {
+            if let Some(monkey) = monkeys.iter().find(|monkey| !monkey.operation.keeps_modulus(m)) {
+                return Err(AocError::ModuloRelief(monkey.id.0));
+            }
+        }
+        let count = monkeys.len();
+        if let Some((id, _)) = monkeys.iter().enumerate().find(|(i, m)| {
+            m.id.0 != *i || m.test.if_true.0 >= count || m.test.if_false.0 >= count
+        }) {
+            return Err(AocError::UnknownMonkey(id));
+        }
+        Ok(MonkeyTroop { monkeys, relief })
     }
 
-    pub(crate) fn inspection_round(&mut self) {
-        let ids = self.monkeys.iter().map(|monkey| monkey.id.clone()).collect::<Vec<_>>();
-        
-        for id in ids {
-            let monkey = &mut self.monkeys[id.0];
-            let thrown_items = monkey.inspect_inventory(self.modulo);
+    pub fn monkeys(&self) -> &[Monkey] {
+        &self.monkeys
+    }
+
+    // every monkey takes its turn, returning how many items each inspected
+    pub fn inspection_round(&mut self) -> Result<Vec<i64>, AocError> {
+        let mut inspected = vec![0; self.monkeys.len()];
+        for (id, count) in inspected.iter_mut().enumerate() {
+            let monkey = &mut self.monkeys[id];
+            *count = monkey.items.len() as i64;
+            let thrown_items = monkey.inspect_inventory(&self.relief)?;
             for (item, target) in thrown_items {
                 self.monkeys[target.0].items.push(item);
             }
         }
+        Ok(inspected)
     }
 
-    pub fn monkey_business(&mut self, rounds: usize) -> i64 {
-        for _ in 0..rounds {
-            self.inspection_round();
-        }
-        
-        let mut inspections = self.monkeys.iter().map(|monkey| monkey.inspections)
-            .collect::<Vec<_>>();
+    pub fn play(&mut self, rounds: usize) -> Result<Inspections, AocError> {
+        let rounds = (0..rounds)
+            .map(|_| self.inspection_round())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Inspections { rounds })
+    }
 
-        inspections.sort();
-        inspections.iter()
-            .rev()
-            .take(2)
-            .copied()
-            .product()
+    pub fn monkey_business(&mut self, rounds: usize) -> Result<i64, AocError> {
+        Ok(self.play(rounds)?.monkey_business())
     }
 }
 
@@ -227,28 +274,28 @@ pub fn input_data() -> InputModel {
             Monkey {
                 id: MonkeyId(0),
                 items: vec![Item(79), Item(98)],
-                operation: Operation::Multiply(Operand::Old, Operand::Literal(19)),
+                operation: Operation::Old * Operation::Literal(19),
                 test: Test{divisor: 23, if_true: MonkeyId(2), if_false: MonkeyId(3),},
                 inspections: 0,
             },
             Monkey {
                 id: MonkeyId(1),
                 items: vec![Item(54), Item(65), Item(75), Item(74)],
-                operation: Operation::Add(Operand::Old, Operand::Literal(6)),
+                operation: Operation::Old + Operation::Literal(6),
                 test: Test{divisor: 19, if_true: MonkeyId(2), if_false: MonkeyId(0),},
                 inspections: 0,
             },
             Monkey {
                 id: MonkeyId(2),
                 items: vec![Item(79), Item(60), Item(97)],
-                operation: Operation::Multiply(Operand::Old, Operand::Old),
+                operation: Operation::Old * Operation::Old,
                 test: Test{divisor: 13, if_true: MonkeyId(1), if_false: MonkeyId(3),},
                 inspections: 0,
             },
             Monkey {
                 id: MonkeyId(3),
                 items: vec![Item(74)],
-                operation: Operation::Add(Operand::Old, Operand::Literal(3)),
+                operation: Operation::Old + Operation::Literal(3),
                 test: Test{divisor: 17, if_true: MonkeyId(0), if_false: MonkeyId(1),},
                 inspections: 0,
             },
@@ -271,7 +318,7 @@ mod tests {
         let expected = Monkey {
             id: MonkeyId(0),
             items: vec![Item(79), Item(98)],
-            operation: Operation::Multiply(Operand::Old, Operand::Literal(19)),
+            operation: Operation::Old * Operation::Literal(19),
             test: Test{divisor: 23, if_true: MonkeyId(2), if_false: MonkeyId(3),},
             inspections: 0,
         };
@@ -284,13 +331,13 @@ mod tests {
     fn test_inspection() {
         // test data with start item, operation and expected Result
         let test_data = vec![
-            (Item(79), Operation::Multiply(Operand::Old, Operand::Literal(19)), Item(500)),
-            (Item(54), Operation::Add(Operand::Old, Operand::Literal(6)), Item(20)),
-            (Item(79), Operation::Multiply(Operand::Old, Operand::Old), Item(2080)),
+            (Item(79), Operation::Old * Operation::Literal(19), Item(500)),
+            (Item(54), Operation::Old + Operation::Literal(6), Item(20)),
+            (Item(79), Operation::Old * Operation::Old, Item(2080)),
         ];
 
         for (start_item, operation, expected) in test_data {
-            let actual = start_item.inspect(&operation, i64::MAX);
+            let actual = start_item.inspect(&operation, &Relief::Divide(3)).unwrap();
             assert_eq!(actual, expected);
         }
     }
@@ -304,9 +351,7 @@ mod tests {
         ];
 
         for (test, item, expected) in test_data {
-            println!("test: {:?}, item: {:?}, expected: {:?}", test, item, expected);
             let actual = test.evaluate(&item);
-            println!("actual: {:?}, expected: {:?}", actual, expected);
             assert_eq!(actual, expected);
         }
     }
@@ -322,8 +367,8 @@ mod tests {
             .map(|items| items.into_iter().map(Item).collect())
             .collect();
 
-        let mut troop = MonkeyTroop::new(input_data().monkeys, false);
-        troop.inspection_round();
+        let mut troop = MonkeyTroop::new(input_data().monkeys, Relief::Divide(3)).unwrap();
+        troop.inspection_round().unwrap();
 
         let actual = troop.monkeys.iter().map(|m| m.items.clone()).collect::<Vec<_>>();
         assert_eq!(actual, expected);
@@ -340,9 +385,9 @@ mod tests {
             .map(|items| items.into_iter().map(Item).collect())
             .collect();
 
-        let mut troop = MonkeyTroop::new(input_data().monkeys, false);
+        let mut troop = MonkeyTroop::new(input_data().monkeys, Relief::Divide(3)).unwrap();
         for _ in 0..20 {
-            troop.inspection_round();
+            troop.inspection_round().unwrap();
         }
 
         let actual = troop.monkeys.iter().map(|m| m.items.clone()).collect::<Vec<_>>();
@@ -356,17 +401,17 @@ mod tests {
     #[test]
     fn test_monkey_business() {
         let expected = 10605;
-        let mut troop = MonkeyTroop::new(input_data().monkeys, false);
-        let actual = troop.monkey_business(20);
+        let mut troop = MonkeyTroop::new(input_data().monkeys, Relief::Divide(3)).unwrap();
+        let actual = troop.monkey_business(20).unwrap();
         
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn test_part2_inspection_round_1000() {
-        let mut troop = MonkeyTroop::new(input_data().monkeys, true);
+        let mut troop = MonkeyTroop::new(input_data().monkeys, Relief::tests(&input_data().monkeys)).unwrap();
         for _ in 0..1000 {
-            troop.inspection_round();
+            troop.inspection_round().unwrap();
         }
 
         assert_eq!(troop.monkeys[0].inspections, 5204);
@@ -377,9 +422,9 @@ mod tests {
     
     #[test]
     fn test_part2_inspection_round_10000() {
-        let mut troop = MonkeyTroop::new(input_data().monkeys, true);
+        let mut troop = MonkeyTroop::new(input_data().monkeys, Relief::tests(&input_data().monkeys)).unwrap();
         for _ in 0..10000 {
-            troop.inspection_round();
+            troop.inspection_round().unwrap();
         }
 
         assert_eq!(troop.monkeys[0].inspections, 52166);
@@ -387,4 +432,85 @@ mod tests {
         assert_eq!(troop.monkeys[2].inspections, 1938);
         assert_eq!(troop.monkeys[3].inspections, 52013);
     }
+
+    #[test]
+    fn test_inspection_stats() {
+        let mut troop = MonkeyTroop::new(input_data().monkeys, Relief::Divide(3)).unwrap();
+        let inspections = troop.play(20).unwrap();
+
+        assert_eq!(inspections.rounds()[0], vec![2, 4, 3, 5]);
+        assert_eq!(inspections.totals(), vec![101, 95, 7, 105]);
+        assert_eq!(inspections.monkey_business(), 10605);
+        assert_eq!(
+            inspections.to_string(),
+            "== After round 20 ==
+Monkey 0 inspected items 101 times.
+Monkey 1 inspected items 95 times.
+Monkey 2 inspected items 7 times.
+Monkey 3 inspected items 105 times.
+"
+        );
+    }
+
+    #[test]
+    fn test_relief_policies() {
+        let monkeys = input_data().monkeys;
+        assert_eq!(Relief::tests(&monkeys), Relief::Modulo(23 * 19 * 13 * 17));
+
+        // without relief the worry levels soon overflow
+        let mut troop = MonkeyTroop::new(monkeys.clone(), Relief::None).unwrap();
+        assert!(matches!(troop.play(100), Err(AocError::InvalidWorry { .. })));
+
+        // an explicit modulus works like the one of the tests
+        let mut troop = MonkeyTroop::new(monkeys.clone(), Relief::Modulo(2 * 96577)).unwrap();
+        assert_eq!(troop.play(1000).unwrap().totals(), vec![5204, 4792, 199, 5192]);
+
+        assert!(matches!(MonkeyTroop::new(monkeys, Relief::Divide(0)), Err(AocError::InvalidRelief)));
+    }
+
+    #[test]
+    fn test_custom_operations() {
+        let input = "Monkey 0:
+  Starting items: 10
+  Operation: new = (old + 2) * old - 1
+  Test: divisible by 7
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 3
+  Operation: new = old % 5
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 2";
+        let model = input.parse::<InputModel>().unwrap();
+
+        assert_eq!(model.monkeys.len(), 2);
+        assert_eq!(model.monkeys[0].operation.to_string(), "(old + 2) * old - 1");
+        assert!(matches!(MonkeyTroop::new(model.monkeys.clone(), Relief::None), Err(AocError::UnknownMonkey(1))));
+
+        let mut monkeys = model.monkeys;
+        monkeys[1].test.if_false = MonkeyId(1);
+        let mut troop = MonkeyTroop::new(monkeys, Relief::None).unwrap();
+        troop.inspection_round().unwrap();
+        // 10 becomes 119 and goes to monkey 1, which passes it back as 4 and keeps 3
+        assert_eq!(troop.monkeys()[0].items, vec![Item(4)]);
+        assert_eq!(troop.monkeys()[1].items, vec![Item(3)]);
+    }
+
+    #[test]
+    fn test_modulo_relief_needs_exact_operations() {
+        let mut monkeys = input_data().monkeys;
+        let relief = Relief::tests(&monkeys);
+
+        monkeys[1].operation = "old / 2".parse().unwrap();
+        assert!(matches!(MonkeyTroop::new(monkeys.clone(), relief), Err(AocError::ModuloRelief(1))));
+        assert!(MonkeyTroop::new(monkeys.clone(), Relief::Divide(3)).is_ok());
+
+        // a remainder by a divisor of the modulus gives the same result either way
+        monkeys[1].operation = "old % 13 + 6".parse().unwrap();
+        assert!(MonkeyTroop::new(monkeys.clone(), relief).is_ok());
+        monkeys[1].operation = "old % 5 + 6".parse().unwrap();
+        assert!(matches!(MonkeyTroop::new(monkeys, relief), Err(AocError::ModuloRelief(1))));
+    }
 }
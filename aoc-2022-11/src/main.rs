@@ -5,13 +5,13 @@ const INPUT: &str = include_str!("../data/input.txt");
 
 
 fn part1(input: &InputModel) -> Result<String,AocError> {
-    let mut troop = MonkeyTroop::new(input.monkeys.clone(), false);
-    Ok(troop.monkey_business(20).to_string())
+    let mut troop = MonkeyTroop::new(input.monkeys.clone(), Relief::Divide(3))?;
+    Ok(troop.monkey_business(20)?.to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
-    let mut troop = MonkeyTroop::new(input.monkeys.clone(), true);
-    Ok(troop.monkey_business(10000).to_string())
+    let mut troop = MonkeyTroop::new(input.monkeys.clone(), Relief::tests(&input.monkeys))?;
+    Ok(troop.monkey_business(10000)?.to_string())
 }
 
 fn main() -> Result<(), AocError> {
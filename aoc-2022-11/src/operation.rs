@@ -0,0 +1,196 @@
+use std::{
+    fmt::{Display, Formatter},
+    ops::{Add, Div, Mul, Rem, Sub},
+    str::FromStr,
+};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1, one_of, space0},
+    combinator::{all_consuming, map, map_res},
+    multi::fold_many0,
+    sequence::{delimited, pair},
+    IResult,
+};
+
+use crate::AocError;
+
+/// Arithmetic over the old worry level, like `old * (old + 3) % 7`.
+///
+/// `*`, `/` and `%` bind tighter than `+` and `-`, all operators associate to the left.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Operation {
+    Old,
+    Literal(i64),
+    Add(Box<Operation>, Box<Operation>),
+    Subtract(Box<Operation>, Box<Operation>),
+    Multiply(Box<Operation>, Box<Operation>),
+    Divide(Box<Operation>, Box<Operation>),
+    Remainder(Box<Operation>, Box<Operation>),
+}
+
+impl Operation {
+    fn binary(op: char, a: Operation, b: Operation) -> Operation {
+        match op {
+            '+' => a + b,
+            '-' => a - b,
+            '*' => a * b,
+            '/' => a / b,
+            _ => a % b,
+        }
+    }
+
+    fn factor(input: &str) -> IResult<&str, Self> {
+        alt((
+            map(tag("old"), |_| Operation::Old),
+            map_res(digit1, |s: &str| s.parse().map(Operation::Literal)),
+            delimited(pair(char('('), space0), Operation::expression, pair(space0, char(')'))),
+        ))(input)
+    }
+
+    fn term(input: &str) -> IResult<&str, Self> {
+        let (input, first) = Operation::factor(input)?;
+        fold_many0(
+            pair(delimited(space0, one_of("*/%"), space0), Operation::factor),
+            move || first.clone(),
+            |acc, (op, b)| Operation::binary(op, acc, b),
+        )(input)
+    }
+
+    pub(crate) fn expression(input: &str) -> IResult<&str, Self> {
+        let (input, first) = Operation::term(input)?;
+        fold_many0(
+            pair(delimited(space0, one_of("+-"), space0), Operation::term),
+            move || first.clone(),
+            |acc, (op, b)| Operation::binary(op, acc, b),
+        )(input)
+    }
+
+    // the new worry level, or nothing when it overflows or divides by zero
+    pub fn evaluate(&self, old: i64) -> Option<i64> {
+        let operands = |a: &Operation, b: &Operation| Some((a.evaluate(old)?, b.evaluate(old)?));
+        match self {
+            Operation::Old => Some(old),
+            Operation::Literal(n) => Some(*n),
+            Operation::Add(a, b) => operands(a, b).and_then(|(a, b)| a.checked_add(b)),
+            Operation::Subtract(a, b) => operands(a, b).and_then(|(a, b)| a.checked_sub(b)),
+            Operation::Multiply(a, b) => operands(a, b).and_then(|(a, b)| a.checked_mul(b)),
+            Operation::Divide(a, b) => operands(a, b).and_then(|(a, b)| a.checked_div(b)),
+            Operation::Remainder(a, b) => operands(a, b).and_then(|(a, b)| a.checked_rem(b)),
+        }
+    }
+
+    // whether working modulo `m` gives the same remainders for every divisor of `m`, which
+    // holds for `+`, `-` and `*` but not after a division or a remainder that does not divide it
+    pub fn keeps_modulus(&self, m: i64) -> bool {
+        match self {
+            Operation::Old | Operation::Literal(_) => true,
+            Operation::Add(a, b) | Operation::Subtract(a, b) | Operation::Multiply(a, b) => {
+                a.keeps_modulus(m) && b.keeps_modulus(m)
+            }
+            Operation::Divide(_, _) => false,
+            Operation::Remainder(a, b) => {
+                matches!(**b, Operation::Literal(k) if k != 0 && m % k == 0) && a.keeps_modulus(m)
+            }
+        }
+    }
+
+    // binding strength, used to put parentheses back when printing
+    fn precedence(&self) -> u8 {
+        match self {
+            Operation::Add(_, _) | Operation::Subtract(_, _) => 1,
+            Operation::Multiply(_, _) | Operation::Divide(_, _) | Operation::Remainder(_, _) => 2,
+            Operation::Old | Operation::Literal(_) => 3,
+        }
+    }
+}
+
+impl FromStr for Operation {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Operation::expression)(s.trim())
+            .map(|(_, operation)| operation)
+            .map_err(|_| AocError::ParseError)
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (a, op, b) = match self {
+            Operation::Old => return write!(f, "old"),
+            Operation::Literal(n) => return write!(f, "{}", n),
+            Operation::Add(a, b) => (a, '+', b),
+            Operation::Subtract(a, b) => (a, '-', b),
+            Operation::Multiply(a, b) => (a, '*', b),
+            Operation::Divide(a, b) => (a, '/', b),
+            Operation::Remainder(a, b) => (a, '%', b),
+        };
+        // the right side needs parentheses on a tie as the operators associate to the left
+        if a.precedence() < self.precedence() {
+            write!(f, "({})", a)?;
+        } else {
+            write!(f, "{}", a)?;
+        }
+        write!(f, " {} ", op)?;
+        if b.precedence() <= self.precedence() && b.precedence() < 3 {
+            write!(f, "({})", b)
+        } else {
+            write!(f, "{}", b)
+        }
+    }
+}
+
+macro_rules! build {
+    ($op:ident, $method:ident, $variant:ident) => {
+        impl $op for Operation {
+            type Output = Operation;
+
+            fn $method(self, other: Operation) -> Operation {
+                Operation::$variant(Box::new(self), Box::new(other))
+            }
+        }
+    };
+}
+
+build!(Add, add, Add);
+build!(Sub, sub, Subtract);
+build!(Mul, mul, Multiply);
+build!(Div, div, Divide);
+build!(Rem, rem, Remainder);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_precedence() {
+        let operation = "old + 3 * old - 4 / 2".parse::<Operation>().unwrap();
+
+        assert_eq!(operation.evaluate(5), Some(5 + 3 * 5 - 4 / 2));
+        assert_eq!(
+            operation,
+            Operation::Old + Operation::Literal(3) * Operation::Old
+                - Operation::Literal(4) / Operation::Literal(2)
+        );
+        assert_eq!("(old + 1) * (old - 1) % 7".parse::<Operation>().unwrap().evaluate(6), Some(35 % 7));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for text in ["old * 19", "old * old", "old - (3 - old)", "(old + 1) * 2 % 5", "old / 2 / 3"] {
+            let operation = text.parse::<Operation>().unwrap();
+            assert_eq!(operation.to_string(), text);
+        }
+        assert!("old ^ 2".parse::<Operation>().is_err());
+        assert!("(old + 1".parse::<Operation>().is_err());
+    }
+
+    #[test]
+    fn test_evaluate_fails() {
+        assert_eq!("old / (old - 1)".parse::<Operation>().unwrap().evaluate(1), None);
+        assert_eq!("old * old".parse::<Operation>().unwrap().evaluate(i64::MAX / 2), None);
+    }
+}
@@ -1,8 +1,13 @@
-use std::{str::FromStr, collections::HashSet};
-use aoc_common::position::*;
+use std::{collections::VecDeque, fmt::{Display, Formatter}, str::FromStr};
+use aoc_common::{direction::Direction, grid::Grid, position::Position};
 
 
-pub type InputModel = Grid; 
+#[derive(Debug, PartialEq, Eq)]
+pub struct InputModel {
+    pub heightmap: Heightmap,
+    pub start: Position,
+    pub end: Position,
+}
 
 #[derive(thiserror::Error, Debug)]
 pub enum AocError {
@@ -11,167 +16,191 @@ pub enum AocError {
     #[error("No solution found")]
     NoSolution,
 }
-        
+
 impl FromStr for InputModel {
     type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let grid = s.lines()
-            .map(|line| line.bytes().collect())
-            .collect::<Vec<Vec<u8>>>();
-        Ok(InputModel { grid })
+        let heightmap = s.parse::<Heightmap>()?;
+        let find = |target: Square| cells(&heightmap)
+            .find(|pos| heightmap[*pos] == target)
+            .ok_or(AocError::ParseError);
+        Ok(InputModel { start: find(Square::Start)?, end: find(Square::End)?, heightmap })
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct Grid{ 
-    pub grid: Vec<Vec<u8>> 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Square {
+    Start,
+    End,
+    Elevation(u8),
 }
 
-impl Grid {
+impl Square {
+    // the start is at the lowest elevation and the end at the highest
+    pub fn height(&self) -> u8 {
+        match self {
+            Square::Start => 0,
+            Square::End => b'z' - b'a',
+            Square::Elevation(h) => *h,
+        }
+    }
+}
+
+impl FromStr for Square {
+    type Err = AocError;
 
-    fn get(&self, pos: Position) -> Option<u8> {
-        self.grid
-            .get(pos.y as usize)
-            .and_then(|row| row.get(pos.x as usize).copied())
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            b"S" => Ok(Square::Start),
+            b"E" => Ok(Square::End),
+            [c @ b'a'..=b'z'] => Ok(Square::Elevation(c - b'a')),
+            _ => Err(AocError::ParseError),
+        }
     }
+}
 
+impl Display for Square {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Square::Start => write!(f, "S"),
+            Square::End => write!(f, "E"),
+            Square::Elevation(h) => write!(f, "{}", (b'a' + h) as char),
+        }
+    }
 }
 
-fn find_char(input: &Grid, target: u8) -> Position {
-    input.grid.iter()
+pub type Heightmap = Grid<Square>;
+
+// every position of the grid, row by row
+pub fn cells<T>(grid: &Grid<T>) -> impl Iterator<Item = Position> + '_ {
+    grid.iter()
         .enumerate()
-        .find(|(_, row)| row.contains(&target))
-        .map(|(y, row)| ( row.iter().position(|&c| c == target).unwrap() as i32, y as i32 ).into())
-        .unwrap()
+        .flat_map(|(y, row)| (0..row.len()).map(move |x| Position::new(x as i32, y as i32)))
 }
 
-pub fn find_start(input: &Grid) -> Position {
-    find_char(input, b'S' )
+/// Finds the fewest steps over a heightmap, where a rule on the heights at both ends decides
+/// which steps can be taken.
+pub struct Planner<'a, F> {
+    heightmap: &'a Heightmap,
+    can_step: F,
 }
 
-pub fn find_end(input: &Grid) -> Position {
-    find_char(input, b'E')
-}
+impl<'a, F: Fn(u8, u8) -> bool> Planner<'a, F> {
+    pub fn new(heightmap: &'a Heightmap, can_step: F) -> Self {
+        Planner { heightmap, can_step }
+    }
 
-pub fn next_moves(grid: &Grid, current: &Position) -> Vec<Position> {
-    let possible_moves: Vec<(i32, i32)> = vec![(-1, 0), (1, 0), (0, -1), (0, 1)];
-    let current_height = grid.grid[current.y as usize][current.x as usize];
-    // ensure we can leave start by forcing start to be a higher value than the surrounding tiles
-    let current_height = if current_height == b'S' { b'a'} else { current_height };  
-    possible_moves.into_iter()
-        .map(|m| *current + m.into())
-        .filter(|&pos| grid.get(pos).is_some())
-        .filter(|&pos| {
-            let pos_height = grid.get(pos).unwrap();
-            let pos_height = if pos_height == b'E' { b'z' } else { pos_height };
-            pos_height <= current_height + 1 
-        })
-        .collect()
-}
+    fn step_allowed(&self, from: Position, to: Position) -> bool {
+        (self.can_step)(self.heightmap[from].height(), self.heightmap[to].height())
+    }
 
+    /// The fewest steps from the nearest source to every square.
+    pub fn distances(&self, sources: impl IntoIterator<Item = Position>) -> DistanceMap {
+        self.search(sources, false)
+    }
 
-// use the A* algorithm to find the shortest path between two points
-pub fn shortest_path(grid: &Grid, start: &Position, end: &Position) -> Result<Vec<Position>, AocError> {
-    let mut open_set = vec![*start];
-    let mut came_from = std::collections::HashMap::new();
-    let mut g_score = std::collections::HashMap::new();
-    let mut f_score = std::collections::HashMap::new();
-    g_score.insert(*start, 0);
-    f_score.insert(*start, start.manhattan(end));
-
-    while !open_set.is_empty() {
-        let current = *open_set.iter()
-            .min_by_key(|pos| f_score.get(pos).unwrap())
-            .unwrap();
-        if current == *end {
-            return Ok(reconstruct_path(&came_from, current));
+    /// The fewest steps from every square to the nearest target, searching backwards from the
+    /// targets.
+    pub fn distances_to(&self, targets: impl IntoIterator<Item = Position>) -> DistanceMap {
+        self.search(targets, true)
+    }
+
+    fn search(&self, sources: impl IntoIterator<Item = Position>, reversed: bool) -> DistanceMap {
+        let (width, height) = (self.heightmap.width(), self.heightmap.height());
+        let mut distances = Grid::from(vec![vec![None; width]; height]);
+        let mut previous = Grid::from(vec![vec![None; width]; height]);
+        let mut open = VecDeque::new();
+        for source in sources {
+            if distances.get(source) == Some(&None) {
+                distances.set(source, Some(0));
+                open.push_back(source);
+            }
         }
-        open_set.retain(|pos| pos != &current);
-        for neighbor in next_moves(grid, &current) {
-            let tentative_g_score = g_score.get(&current).unwrap() + 1;
-            if g_score.get(&neighbor).is_none() || tentative_g_score < *g_score.get(&neighbor).unwrap() {
-                came_from.insert(neighbor, current);
-                g_score.insert(neighbor, tentative_g_score);
-                f_score.insert(neighbor, tentative_g_score + neighbor.manhattan(end));
-                if !open_set.contains(&neighbor) {
-                    open_set.push(neighbor);
+
+        while let Some(current) = open.pop_front() {
+            let steps = distances[current].unwrap_or(0) + 1;
+            for direction in Direction::iter() {
+                let next = direction.step(current);
+                if distances.get(next) != Some(&None) {
+                    continue;
+                }
+                let allowed = if reversed {
+                    self.step_allowed(next, current)
+                } else {
+                    self.step_allowed(current, next)
+                };
+                if allowed {
+                    distances.set(next, Some(steps));
+                    previous.set(next, Some(current));
+                    open.push_back(next);
                 }
             }
         }
+        DistanceMap { distances, previous, reversed }
     }
-    Err(AocError::NoSolution)
 }
 
-fn reconstruct_path(came_from: &std::collections::HashMap<Position, Position>, current: Position) -> Vec<Position> {
-    let mut total_path = vec![current];
-    let mut current = current;
-    while came_from.contains_key(&current) {
-        current = *came_from.get(&current).unwrap();
-        total_path.push(current);
-    }
-    total_path.reverse();
-    total_path
+// climb at most one up, and drop down as far as needed
+pub fn climbing(heightmap: &Heightmap) -> Planner<'_, impl Fn(u8, u8) -> bool> {
+    Planner::new(heightmap, |from, to| to <= from + 1)
 }
 
+/// The result of a breadth first search, which remembers where each square was reached from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DistanceMap {
+    distances: Grid<Option<usize>>,
+    previous: Grid<Option<Position>>,
+    reversed: bool,
+}
+
+impl DistanceMap {
+    pub fn distance(&self, pos: Position) -> Option<usize> {
+        self.distances.get(pos).copied().flatten()
+    }
 
-pub fn shortest_path_bfs(grid: &Grid, start: &Position, end: &Position) -> Result<i32, AocError> {
-    let mut open = std::collections::VecDeque::new();
-    open.push_front((0, *start));
-    let mut seen: HashSet<Position> = HashSet::new();
-    loop {
-        let (steps, current) = open.pop_front().ok_or(AocError::NoSolution)?;
-        if current == *end {
-            return Ok(steps);
+    /// A shortest path between the square and the nearest source, in walking order.
+    pub fn path(&self, pos: Position) -> Option<Vec<Position>> {
+        self.distance(pos)?;
+        let mut path = std::iter::successors(Some(pos), |p| self.previous[*p]).collect::<Vec<_>>();
+        if !self.reversed {
+            path.reverse();
         }
-        next_moves(grid, &current).into_iter()
-            .for_each(|pos| {
-                if !seen.contains(&pos) {
-                    open.push_back((steps+1, pos));
-                    seen.insert(pos);
-                };
-            });
+        Some(path)
+    }
+
+    // the closest of the squares and its distance
+    pub fn nearest(&self, squares: impl IntoIterator<Item = Position>) -> Option<(Position, usize)> {
+        squares.into_iter()
+            .filter_map(|pos| self.distance(pos).map(|d| (pos, d)))
+            .min_by_key(|(_, d)| *d)
     }
 }
 
-pub fn scenic_route(grid: &Grid, end:&Position) -> Result<i32, AocError> {
-    grid.grid.iter()
-        .enumerate()
-        .filter(|(_, row)| row.contains(&b'a') || row.contains(&b'S'))
-        .flat_map(|(y, row)| {
-            row.iter()
-                .enumerate()
-                .filter(|(_, &c)| c == b'a' || c == b'S')
-                .filter_map(move |(x, _)| shortest_path_bfs(grid, &(x as i32, y as i32).into(), end).ok())
-    })
-        .min()
+pub fn shortest_path(input: &InputModel) -> Result<usize, AocError> {
+    climbing(&input.heightmap)
+        .distances([input.start])
+        .distance(input.end)
         .ok_or(AocError::NoSolution)
 }
 
-pub fn scenic_route_astar(grid: &Grid, end:&Position) -> Result<i32, AocError> {
-    grid.grid.iter()
-        .enumerate()
-        .filter(|(_, row)| row.contains(&b'a') || row.contains(&b'S'))
-        .flat_map(|(y, row)| {
-            row.iter()
-                .enumerate()
-                .filter(|(_, &c)| c == b'a' || c == b'S')
-                .filter_map(move |(x, _)| shortest_path(grid, &(x as i32, y as i32).into(), end).map(|path| path.len() as i32 - 1).ok())
-    })
-        .min()
+// the shortest climb from any of the lowest squares
+pub fn scenic_route(input: &InputModel) -> Result<usize, AocError> {
+    let lowest = cells(&input.heightmap).filter(|pos| input.heightmap[*pos].height() == 0);
+    climbing(&input.heightmap)
+        .distances(lowest)
+        .distance(input.end)
         .ok_or(AocError::NoSolution)
 }
 
+pub const TEST_INPUT: &str = "Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi";
+
 pub fn test_data() -> InputModel {
-    InputModel {
-        grid: vec![
-            "Sabqponm".bytes().collect(),
-            "abcryxxl".bytes().collect(),
-            "accszExk".bytes().collect(),
-            "acctuvwj".bytes().collect(),
-            "abdefghi".bytes().collect(),
-        ],
-    }
+    TEST_INPUT.parse::<InputModel>().unwrap()
 }
 
 
@@ -179,96 +208,67 @@ pub fn test_data() -> InputModel {
 mod tests {
     use super::*;
 
-    const TEST_INPUT: &str = "Sabqponm
-abcryxxl
-accszExk
-acctuvwj
-abdefghi";
-
     #[test]
     fn test_parse() {
         let actual = TEST_INPUT.parse::<InputModel>().unwrap();
-        let expected = test_data();
-
-        assert_eq!(actual, expected);
-    }
-
-    #[test]
-    fn test_find_start() {
-        let input = test_data();
-        let actual = find_start(&input);
-        let expected = (0, 0).into();
-
-        assert_eq!(actual, expected);
-    }
-    
-    #[test]
-    fn test_find_end() {
-        let input = test_data();
-        let actual = find_end(&input);
-        let expected = (5, 2).into();
 
-        assert_eq!(actual, expected);
+        assert_eq!(actual.start, (0, 0).into());
+        assert_eq!(actual.end, (5, 2).into());
+        assert_eq!(actual.heightmap[Position::new(3, 1)], Square::Elevation(17));
+        assert_eq!(actual.heightmap.to_string(), format!("{TEST_INPUT}\n"));
+        assert!("Sab\nabc".parse::<InputModel>().is_err());
+        assert!("SaE\na1c".parse::<InputModel>().is_err());
     }
 
     #[test]
-    fn test_next_moves() {
+    fn test_shortest_path() {
         let input = test_data();
-        let actual = next_moves(&input, &(0, 0).into());
-        let expected = vec![(1, 0).into(), (0, 1).into()];
+        let actual = shortest_path(&input).unwrap();
 
-        assert_eq!(actual, expected);
+        assert_eq!(actual, 31);
+        assert_eq!(scenic_route(&input).unwrap(), 29);
     }
 
     #[test]
-    fn test_shortest_path() {
+    fn test_reverse_distances() {
         let input = test_data();
-        let path = shortest_path(&input, &(0, 0).into(), &(5, 2).into());
-        let expected = 31;
-
-        println!("Path: {:?}", path);
-
-        let actual = path.unwrap().len() - 1;
-
-        assert_eq!(actual, expected);
+        let planner = climbing(&input.heightmap);
+        let to_end = planner.distances_to([input.end]);
+
+        assert_eq!(to_end.distance(input.start), Some(31));
+        let lowest = cells(&input.heightmap).filter(|pos| input.heightmap[*pos].height() == 0);
+        assert_eq!(to_end.nearest(lowest).map(|(_, d)| d), Some(29));
+        // every square agrees with a search from that square alone
+        for pos in cells(&input.heightmap) {
+            assert_eq!(to_end.distance(pos), planner.distances([pos]).distance(input.end));
+        }
     }
 
     #[test]
-    fn test_shortest_path_bfs() {
+    fn test_path() {
         let input = test_data();
-        let steps = shortest_path_bfs(&input, &(0, 0).into(), &(5, 2).into());
-        let expected = 31;
-
-        println!("Path: {:?}", steps);
-
-        let actual = steps.unwrap();
+        let planner = climbing(&input.heightmap);
 
-        assert_eq!(actual, expected);
+        for map in [planner.distances([input.start]), planner.distances_to([input.end])] {
+            let path = map.path(if map.reversed { input.start } else { input.end }).unwrap();
+            assert_eq!(path.len(), 32);
+            assert_eq!((path[0], path[31]), (input.start, input.end));
+            assert!(path.windows(2).all(|w| w[0].manhattan(&w[1]) == 1 && planner.step_allowed(w[0], w[1])));
+        }
     }
-    
-    #[test]
-    fn test_scenic_route() {
-        let input = test_data();
-        let steps = scenic_route(&input, &(5, 2).into());
-        let expected = 29;
-
-        println!("Path: {:?}", steps);
-
-        let actual = steps.unwrap();
 
-        assert_eq!(actual, expected);
-    }
-    
     #[test]
-    fn test_scenic_route_astar() {
+    fn test_custom_rule() {
         let input = test_data();
-        let steps = scenic_route_astar(&input, &(5, 2).into());
-        let expected = 29;
-
-        println!("Path: {:?}", steps);
-
-        let actual = steps.unwrap();
-
-        assert_eq!(actual, expected);
+        // walking on the level only
+        let level = Planner::new(&input.heightmap, |from, to| from == to);
+        let map = level.distances([Position::new(3, 0)]);
+
+        assert_eq!(map.distance(Position::new(3, 0)), Some(0));
+        assert_eq!(map.distance(Position::new(4, 0)), None);
+        assert_eq!(map.path(input.end), None);
+        // descending is the climb turned around
+        let descending = Planner::new(&input.heightmap, |from, to| from <= to + 1);
+        assert_eq!(descending.distances([input.end]).distance(input.start), Some(31));
     }
 }
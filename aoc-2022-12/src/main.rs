@@ -5,28 +5,12 @@ const INPUT: &str = include_str!("../data/input.txt");
 
 
 fn part1(input: &InputModel) -> Result<String,AocError> {
-    let start = find_start(input);
-    let end = find_end(input);
-    let steps = shortest_path_bfs(input, &start, &end)?;
+    let steps = shortest_path(input)?;
     Ok(steps.to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
-    let end = find_end(input);
-    let steps = scenic_route(input, &end)?;
-    Ok(steps.to_string())
-}
-
-fn part1_astar(input: &InputModel) -> Result<String,AocError> {
-    let start = find_start(input);
-    let end = find_end(input);
-    let steps = shortest_path(input, &start, &end).map(|path| path.len())?;
-    Ok(steps.to_string())
-}
-
-fn part2_astar(input: &InputModel) -> Result<String, AocError> {
-    let end = find_end(input);
-    let steps = scenic_route_astar(input, &end)?;
+    let steps = scenic_route(input)?;
     Ok(steps.to_string())
 }
 
@@ -34,11 +18,9 @@ fn main() -> Result<(), AocError> {
     let input:InputModel = INPUT.parse::<InputModel>()?;
     let part1_result = part1(&input)?;
     println!("Part1: {}", part1_result);
-    println!("Part1: {}", part1_astar(&input)?);
     println!("--------------");
     let part2_result = part2(&input)?;
     println!("Part2: {}", part2_result);
-    println!("Part2: {}", part2_astar(&input)?);
     Ok(())
 }
 
@@ -48,12 +30,6 @@ mod tests {
     use super::*;
     use test::Bencher;
 
-    const TEST_INPUT: &str = "Sabqponm
-abcryxxl
-accszExk
-acctuvwj
-abdefghi";
-
     pub fn input_data() -> InputModel {
         test_data()
     }
@@ -96,14 +72,4 @@ abdefghi";
     fn bench_part2(b: &mut Bencher) {
         b.iter(|| part2(&input_data()))
     }
-
-    #[bench]
-    fn bench_part1_astar(b: &mut Bencher) {
-        b.iter(|| part1_astar(&input_data()))
-    }
-
-    #[bench]
-    fn bench_part2_astar(b: &mut Bencher) {
-        b.iter(|| part2_astar(&input_data()))
-    }
 }
@@ -1,4 +1,6 @@
-use std::str::FromStr;
+use std::{cmp::Ordering, fmt::{Display, Formatter}, str::FromStr};
+use rand::Rng;
+use serde_json::Value;
 use nom::{
     IResult, Parser,
     branch::*,
@@ -49,6 +51,145 @@ impl Packet {
             )
         ))(input)
     }
+
+    /// The first place where comparing the packets decides their order, `None` when they are equal.
+    pub fn diff(&self, other: &Packet) -> Option<Difference> {
+        let mut path = Vec::new();
+        let reason = Packet::diff_at(self, other, &mut path)?;
+        Some(Difference { path, reason })
+    }
+
+    fn diff_at(left: &Packet, right: &Packet, path: &mut Vec<usize>) -> Option<Reason> {
+        match (left, right) {
+            (Packet::Int(a), Packet::Int(b)) if a == b => None,
+            (Packet::Int(a), Packet::Int(b)) => Some(Reason::Values(*a, *b)),
+            (Packet::List(a), Packet::List(b)) => {
+                for (i, (x, y)) in a.iter().zip(b.iter()).enumerate() {
+                    path.push(i);
+                    if let Some(reason) = Packet::diff_at(x, y, path) {
+                        return Some(reason);
+                    }
+                    path.pop();
+                }
+                if a.len() == b.len() {
+                    None
+                } else {
+                    Some(Reason::Lengths(a.len(), b.len()))
+                }
+            }
+            // a lone integer is compared as a list holding just that integer
+            (Packet::Int(a), Packet::List(_)) => Packet::diff_at(&Packet::List(vec![Packet::Int(*a)]), right, path),
+            (Packet::List(_), Packet::Int(b)) => Packet::diff_at(left, &Packet::List(vec![Packet::Int(*b)]), path),
+        }
+    }
+
+    // a random list packet nested at most `depth` levels deep, integers are kept small so
+    // that equal values show up often enough to exercise the deeper comparisons
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, depth: usize) -> Packet {
+        let len = rng.gen_range(0..=4);
+        Packet::List(
+            (0..len)
+                .map(|_| {
+                    if depth > 0 && rng.gen_bool(0.3) {
+                        Packet::random(rng, depth - 1)
+                    } else {
+                        Packet::Int(rng.gen_range(0..=10))
+                    }
+                })
+                .collect()
+        )
+    }
+
+    // packets are valid JSON arrays
+    pub fn to_json(&self) -> Value {
+        match self {
+            Packet::Int(i) => Value::from(*i),
+            Packet::List(l) => Value::Array(l.iter().map(Packet::to_json).collect()),
+        }
+    }
+
+    pub fn from_json(value: &Value) -> Result<Packet, AocError> {
+        match value {
+            Value::Number(n) => n.as_i64()
+                .filter(|i| *i >= 0)
+                .map(Packet::Int)
+                .ok_or_else(|| AocError::NotAPacket(value.to_string())),
+            Value::Array(l) => l.iter()
+                .map(Packet::from_json)
+                .collect::<Result<Vec<_>, _>>()
+                .map(Packet::List),
+            _ => Err(AocError::NotAPacket(value.to_string())),
+        }
+    }
+}
+
+impl Display for Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Int(i) => write!(f, "{}", i),
+            Packet::List(l) => {
+                write!(f, "[")?;
+                for (i, p) in l.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", p)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl FromStr for Packet {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Packet::parser)(s.trim())
+            .map(|(_, packet)| packet)
+            .map_err(|_| AocError::ParseError)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Reason {
+    // two integers differ
+    Values(i64, i64),
+    // one list ran out of items first
+    Lengths(usize, usize),
+}
+
+/// Where two packets decide their order; `path` holds the list indices leading there.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Difference {
+    pub path: Vec<usize>,
+    pub reason: Reason,
+}
+
+impl Difference {
+    pub fn ordering(&self) -> Ordering {
+        match self.reason {
+            Reason::Values(a, b) => a.cmp(&b),
+            Reason::Lengths(a, b) => a.cmp(&b),
+        }
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path = match self.path.is_empty() {
+            true => "top level".to_string(),
+            false => self.path.iter().map(|i| format!("[{}]", i)).collect(),
+        };
+        let order = match self.ordering() {
+            Ordering::Less => "right order",
+            _ => "wrong order",
+        };
+        match self.reason {
+            Reason::Values(a, b) => write!(f, "at {}: {} vs {}, {}", path, a, b, order),
+            Reason::Lengths(a, b) => write!(f, "at {}: {} vs {} items, {}", path, a, b, order),
+        }
+    }
 }
 
 
@@ -56,6 +197,8 @@ impl Packet {
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("Not a packet: {0}")]
+    NotAPacket(String),
 }
         
 #[derive(Debug, PartialEq, Eq)]
@@ -241,4 +384,71 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_display_round_trip() {
+        for line in PART2_DATA.lines() {
+            let packet = line.parse::<Packet>().unwrap();
+
+            assert_eq!(packet.to_string(), line);
+        }
+        assert!("[1,2]x".parse::<Packet>().is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let packet = "[1,[2,[]],3]".parse::<Packet>().unwrap();
+        let json: Value = serde_json::from_str("[1, [2, []], 3]").unwrap();
+
+        assert_eq!(packet.to_json(), json);
+        assert_eq!(Packet::from_json(&json).unwrap(), packet);
+        assert!(Packet::from_json(&serde_json::json!([1, "2"])).is_err());
+        assert!(Packet::from_json(&serde_json::json!([-1])).is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let input = test_input();
+        let actual = input.pairs.iter()
+            .map(|(a, b)| a.diff(b).unwrap().to_string())
+            .collect::<Vec<_>>();
+        let expected = vec![
+            "at [2]: 3 vs 5, right order",
+            "at [1][0]: 2 vs 4, right order",
+            "at [0][0]: 9 vs 8, wrong order",
+            "at top level: 3 vs 4 items, right order",
+            "at top level: 4 vs 3 items, wrong order",
+            "at top level: 0 vs 1 items, right order",
+            "at [0]: 1 vs 0 items, wrong order",
+            "at [1][1][1][1][2]: 7 vs 0, wrong order",
+        ];
+
+        assert_eq!(actual, expected);
+        assert_eq!(make_divider(2).diff(&make_divider(2)), None);
+    }
+
+    #[test]
+    fn test_random_packets_ordering() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(13);
+        let packets = (0..60)
+            .map(|_| Packet::random(&mut rng, 3))
+            .collect::<Vec<_>>();
+
+        for a in &packets {
+            assert_eq!(a.to_string().parse::<Packet>().unwrap(), *a);
+            assert_eq!(Packet::from_json(&a.to_json()).unwrap(), *a);
+            for b in &packets {
+                let ordering = a.cmp(b);
+                assert_eq!(ordering, b.cmp(a).reverse());
+                assert_eq!(a.diff(b).map(|d| d.ordering()).unwrap_or(Ordering::Equal), ordering);
+                for c in &packets {
+                    if a <= b && b <= c {
+                        assert!(a <= c, "{} <= {} <= {}", a, b, c);
+                    }
+                }
+            }
+        }
+    }
+
 }
@@ -19,50 +19,147 @@ pub struct InputModel  {
 
 type Path = Vec<Position>;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Material {
     Air,
     Rock,
     Sand,
 }
 
+/// What stops the sand below the rocks.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Floor {
+    // sand falling past the deepest rock is lost
+    Abyss,
+    // a solid floor this many rows below the deepest rock
+    Below(i32),
+}
+
+// the cells a grain fell through from a source, kept so the next grain resumes where the last
+// one settled instead of falling all the way from the top again
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct Trail {
+    path: Vec<Position>,
+    // the grains in the cave when the trail was last followed
+    grains: usize,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Cave {
     grid: HashMap<Position, Material>,
     depth: i32,
+    floor: Floor,
+    grains: usize,
+    trails: HashMap<Position, Trail>,
 }
 
 impl Cave {
     pub fn new(paths: &[Path]) -> Cave {
+        Cave::with_floor(paths, Floor::Abyss)
+    }
+
+    pub fn with_floor(paths: &[Path], floor: Floor) -> Cave {
         let grid = expand_paths(paths);
         let depth = grid.keys()
             .map(|pos| pos.y)
             .max()
             .unwrap_or(0);
-        Cave { grid, depth }
+        Cave { grid, depth, floor, grains: 0, trails: HashMap::new() }
     }
 
     fn get(&self, pos: &Position) -> Material {
-        self.grid
-            .get(pos)
-            .unwrap_or(&Material::Air)
-            .clone()
+        match self.floor {
+            Floor::Below(gap) if pos.y >= self.depth + gap => Material::Rock,
+            _ => *self.grid.get(pos).unwrap_or(&Material::Air),
+        }
     }
 
     fn put(&mut self, pos: &Position, material: Material) {
         self.grid.insert(*pos, material);
     }
 
-    pub fn drop_sand(&mut self, pos: &Position) -> Option<Position> {
-        rest_position(self, pos, self.depth)
-            .inspect(|pos| self.put(pos, Material::Sand))
+    pub fn sand(&self) -> usize {
+        self.grains
     }
-    
-    pub fn drop_sand_with_floor(&mut self, pos: &Position) -> Option<Position> {
-        rest_position_with_floor(self, pos, self.depth + 2)
-            .inspect(|pos| self.put(pos, Material::Sand))
+
+    /// Let a grain of sand fall from the source, returning where it comes to rest or nothing
+    /// when it falls into the abyss or the source is blocked.
+    pub fn drop_sand(&mut self, source: &Position) -> Option<Position> {
+        let mut trail = self.trails.remove(source).unwrap_or_default();
+        // sand from other sources may have settled on the trail, cut it off there
+        if trail.grains != self.grains {
+            if let Some(blocked) = trail.path.iter().position(|pos| self.get(pos) != Material::Air) {
+                trail.path.truncate(blocked);
+            }
+        }
+        if trail.path.is_empty() && self.get(source) == Material::Air {
+            trail.path.push(*source);
+        }
+
+        let rest = self.follow(&mut trail.path);
+        if let Some(pos) = rest {
+            self.put(&pos, Material::Sand);
+            self.grains += 1;
+        }
+        trail.grains = self.grains;
+        self.trails.insert(*source, trail);
+        rest
+    }
+
+    // fall from the end of the path until the grain settles, which takes it off the path
+    fn follow(&self, path: &mut Vec<Position>) -> Option<Position> {
+        loop {
+            let pos = *path.last()?;
+            if self.floor == Floor::Abyss && pos.y > self.depth {
+                return None;
+            }
+            match [0, -1, 1]
+                .into_iter()
+                .map(|dx| Position::new(pos.x + dx, pos.y + 1))
+                .find(|next| self.get(next) == Material::Air)
+            {
+                Some(next) => path.push(next),
+                None => return path.pop(),
+            }
+        }
+    }
+
+    /// Drop a grain from every source in turn until none of them settles any more, returning
+    /// the number of grains that came to rest.
+    pub fn pour(&mut self, sources: &[Position]) -> usize {
+        let start = self.grains;
+        while sources.iter().filter(|source| self.drop_sand(source).is_some()).count() > 0 {}
+        self.grains - start
+    }
+
+    /// Draw the cave like the puzzle does, with `+` for the sources and `o` for the sand.
+    pub fn render(&self, sources: &[Position]) -> String {
+        let cells = self.grid.keys().chain(sources);
+        let (min_x, max_x) = cells.clone().fold((i32::MAX, i32::MIN), |(lo, hi), pos| {
+            (lo.min(pos.x), hi.max(pos.x))
+        });
+        let min_y = cells.map(|pos| pos.y).min().unwrap_or(0).min(0);
+        let max_y = match self.floor {
+            Floor::Abyss => self.depth,
+            Floor::Below(gap) => self.depth + gap,
+        };
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| {
+                        let pos = Position::new(x, y);
+                        match self.get(&pos) {
+                            Material::Rock => '#',
+                            Material::Sand => 'o',
+                            Material::Air if sources.contains(&pos) => '+',
+                            Material::Air => '.',
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
     }
-    
 }
 
 
@@ -85,46 +182,6 @@ fn expand_path(path: &Path) -> Vec<Position> {
         .collect()
 }
 
-fn rest_position(cave: &Cave, pos: &Position, max_y: i32) -> Option<Position> {
-    let mut last = *pos;
-    let mut next = Some(*pos);
-    while let Some(pos) = next {
-        if pos.y > max_y {
-            return None;
-        }
-        last = pos;
-        next = vec![Position::new(pos.x, pos.y + 1),
-             Position::new(pos.x - 1, pos.y + 1),
-             Position::new(pos.x + 1, pos.y + 1)]
-         .into_iter()
-         .find(|pos| cave.get(pos) == Material::Air);
-    }
-    Some(last)
-}
-
-fn rest_position_with_floor(cave: &Cave, pos: &Position, floor: i32) -> Option<Position> {
-    let mut last = *pos;
-    let mut next = Some(*pos);
-
-    // stop if drop location is blocked
-    if cave.get(&Position::new(pos.x, pos.y)) != Material::Air {
-        return None;
-    }
-
-    while let Some(pos) = next {
-        if pos.y >= floor {
-            break;
-        }
-        last = pos;
-        next = vec![Position::new(pos.x, pos.y + 1),
-             Position::new(pos.x - 1, pos.y + 1),
-             Position::new(pos.x + 1, pos.y + 1)]
-         .into_iter()
-         .find(|pos| cave.get(pos) == Material::Air);
-    }
-    Some(last)
-}
-
 #[derive(thiserror::Error, Debug)]
 pub enum AocError {
     #[error("Error parsing the input")]
@@ -231,4 +288,58 @@ mod tests {
             }
         }
     }
+
+    // drop a grain all the way from the source, the way it was done before trails
+    fn retrace(cave: &mut Cave, source: &Position) -> Option<Position> {
+        let mut path = vec![*source];
+        if cave.get(source) != Material::Air {
+            return None;
+        }
+        let rest = cave.follow(&mut path);
+        if let Some(pos) = rest {
+            cave.put(&pos, Material::Sand);
+        }
+        rest
+    }
+
+    #[test]
+    fn test_floor() {
+        let mut cave = Cave::with_floor(&test_input().paths, Floor::Below(2));
+
+        assert_eq!(cave.pour(&[Position::new(500, 0)]), 93);
+        assert_eq!(cave.get(&Position::new(500, 0)), Material::Sand);
+        assert_eq!(cave.drop_sand(&Position::new(500, 0)), None);
+    }
+
+    #[test]
+    fn test_trails_match_retracing() {
+        let sources = [Position::new(500, 0), Position::new(497, 2), Position::new(503, 1)];
+        let mut cave = Cave::with_floor(&test_input().paths, Floor::Below(2));
+        let mut expected = cave.clone();
+
+        for step in 0..400 {
+            let source = &sources[step * 7 % sources.len()];
+            assert_eq!(cave.drop_sand(source), retrace(&mut expected, source));
+        }
+        assert_eq!(cave.grid, expected.grid);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut cave = Cave::new(&test_input().paths);
+        let source = Position::new(500, 0);
+
+        assert_eq!(cave.pour(&[source]), 24);
+        assert_eq!(cave.render(&[source]), "\
+......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########.");
+    }
 }
@@ -1,6 +1,5 @@
 #![feature(test)]
 use aoc_2022_14::*;
-use std::iter::from_fn;
 use aoc_common::position::Position;
 
 const INPUT: &str = include_str!("../data/input.txt");
@@ -9,16 +8,14 @@ const INPUT: &str = include_str!("../data/input.txt");
 fn part1(input: &InputModel) -> Result<String,AocError> {
     let drop_pos = Position::new(500, 0);
     let mut cave = Cave::new(&input.paths);
-    let count = from_fn(|| cave.drop_sand(&drop_pos))
-        .count();
+    let count = cave.pour(&[drop_pos]);
     Ok(count.to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
     let drop_pos = Position::new(500, 0);
-    let mut cave = Cave::new(&input.paths);
-    let count = from_fn(|| cave.drop_sand_with_floor(&drop_pos))
-        .count();
+    let mut cave = Cave::with_floor(&input.paths, Floor::Below(2));
+    let count = cave.pour(&[drop_pos]);
     Ok(count.to_string())
 }
 
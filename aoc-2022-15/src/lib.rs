@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 use std::str::FromStr;
 use aoc_common::position::Position;
+use rotated::{Coverage, Rect, Region};
 use nom::{
     IResult, Parser, 
     bytes::complete::tag,
@@ -10,6 +11,8 @@ use nom::{
     sequence::{pair, tuple,}
 };
 
+pub mod rotated;
+
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel  {
@@ -70,6 +73,10 @@ impl Sensor {
         Some((self.location.x - dx, self.location.x + dx))
     }
 
+    pub fn coverage(&self) -> Region {
+        Region::diamond(self.location, self.range() as i64)
+    }
+
 }
 
 fn position_parser(input: &str) -> IResult<&str, Position> {
@@ -98,6 +105,7 @@ fn integer_parser(input: &str) -> IResult<&str, i32> {
 pub struct Cave {
     sensors: Vec<Sensor>,
     taken_positions: HashSet<Position>,
+    coverage: Coverage,
 }
 
 impl Cave {
@@ -108,67 +116,51 @@ impl Cave {
             taken_positions.insert(sensor.location);
             taken_positions.insert(sensor.beacon);
         }
+        let coverage = Coverage::new(sensors.iter().map(Sensor::coverage).collect());
         Self {
             sensors,
             taken_positions,
+            coverage,
         }
     }
 
-    // finc unique positions on line y for all sensors
-    pub fn covered_positions(&self, y: i32) -> i32 {
+    pub fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
 
-        let covered = self.covered_parts(y);
+    // the columns any sensor reaches
+    fn columns(&self) -> (i32, i32) {
+        self.sensors.iter()
+            .fold((i32::MAX, i32::MIN), |(min, max), sensor| (
+                min.min(sensor.location.x - sensor.range()),
+                max.max(sensor.location.x + sensor.range()),
+            ))
+    }
 
+    // the positions on line y where no beacon can be
+    pub fn covered_positions(&self, y: i32) -> i64 {
+        let (min, max) = self.columns();
+        let row = Rect::new(Position::new(min, y), Position::new(max, y));
         let taken = self.taken_positions.iter()
-            .filter(|p| p.y == y)
-            .filter(|p| covered.iter().map(|(l, r)| p.x >= *l && p.x <= *r).any(|b| b))
+            .filter(|p| row.contains(p))
             .count();
 
-        covered.iter().map(|p| p.1 - p.0 + 1).sum::<i32>() - taken as i32
+        self.coverage.covered_in(&row) - taken as i64
     }
 
-    pub fn covered_parts(&self, y: i32) -> Vec<(i32, i32)> {
-        let mut covered_parts: Vec<(i32, i32)>= self.sensors.iter()
-            .filter_map(|sensor| sensor.intersect_y(y))
-            .collect();
-        covered_parts.sort();
-
-        let mut covered = vec![covered_parts[0]];
-        for curr in covered_parts.iter().skip(1) {
-            let prev = covered.pop().unwrap();
-            if prev.1 >= curr.0 {
-                if prev.1 < curr.1 {
-                    covered.push((prev.0, curr.1));
-                } else {
-                    covered.push(prev);
-                }
-            } else {
-                covered.push(prev);
-                covered.push(*curr);
-            }
-        }
-        covered
+    // the cells of the square from the origin to the bound no sensor covers
+    pub fn gaps(&self, bound: i32) -> Vec<Position> {
+        self.coverage.gaps(&Rect::new(Position::new(0, 0), Position::new(bound, bound)))
     }
 
-    pub fn uncovered_areas(&self, bound: i32) -> Vec<(i32, Vec<(i32, i32)>)> {
-        (0.. bound)
-            .map(|y| (y, self.covered_parts(y)))
-            .filter(|(_, parts)| parts.len() > 1)
-            .map(|(y, parts)| (y, parts.iter()
-                 .zip(parts.iter().skip(1))
-                 .map(|(l, r)| (l.1 + 1, r.0 - 1))
-                 .filter(|(l, r)| l <= r)
-                 .collect::<Vec<(i32, i32)>>()))
-            .filter(|(_, parts)| !parts.is_empty())
+    pub fn tuning_frequencies(&self, bound: i32) -> Vec<i64> {
+        self.gaps(bound).iter()
+            .map(|p| (p.x as i64) * 4_000_000 + (p.y as i64))
             .collect()
     }
 
     pub fn tuning_frequency(&self, bound: i32) -> Option<i64> {
-        let uncovered_areas = self.uncovered_areas(bound);
-        uncovered_areas.first()
-            .map(|(y, r)| (r.first().unwrap().0, y))
-            .filter(|(x, _)| *x > 0 && *x <= bound)
-            .map(|(x, y)| (x as i64) * 4_000_000 + (*y as i64))
+        self.tuning_frequencies(bound).first().copied()
     }
 }
 
@@ -239,18 +231,38 @@ mod tests {
         let actual = cave.covered_positions(10);
         let expected = 26;
 
-        println!("covered positions: {:?}", actual);
-
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn test_uncovered_areas() {
+    fn test_gaps() {
         let sensors = input_data().sensors;
         let cave = Cave::new(sensors);
-        let actual = cave.uncovered_areas(20);
-        let expected = vec![(11, vec![(14, 14)])];
+        let actual = cave.gaps(20);
+        let expected = vec![Position { x: 14, y: 11 }];
         assert_eq!(actual, expected);
+        // further out the sensors leave more open
+        let expected = (0..=26)
+            .flat_map(|y| (0..=26).map(move |x| Position::new(x, y)))
+            .filter(|p| !cave.coverage().contains(p))
+            .collect::<Vec<_>>();
+        assert!(expected.len() > 1);
+        assert_eq!(cave.gaps(26), expected);
+    }
+
+    #[test]
+    fn test_covered_rectangles() {
+        let cave = Cave::new(input_data().sensors);
+        let coverage = cave.coverage();
+
+        for (min, max) in [((0, 0), (20, 20)), ((-5, 3), (30, 9)), ((-10, -10), (-1, 40)), ((7, 7), (7, 7))] {
+            let rect = Rect::new(Position::from(min), Position::from(max));
+            let expected = (rect.min.x..=rect.max.x)
+                .flat_map(|x| (rect.min.y..=rect.max.y).map(move |y| Position::new(x, y)))
+                .filter(|p| coverage.contains(p))
+                .count() as i64;
+            assert_eq!(coverage.covered_in(&rect), expected, "{rect:?}");
+        }
     }
 
     #[test]
@@ -0,0 +1,261 @@
+use std::cmp::Ordering;
+
+use aoc_common::position::Position;
+
+/// An axis-aligned rectangle of cells, bounds included.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rect {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl Rect {
+    pub fn new(min: Position, max: Position) -> Rect {
+        Rect { min, max }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y
+    }
+
+    pub fn area(&self) -> i64 {
+        if self.is_empty() {
+            return 0;
+        }
+        (self.max.x - self.min.x + 1) as i64 * (self.max.y - self.min.y + 1) as i64
+    }
+
+    pub fn contains(&self, pos: &Position) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x) && (self.min.y..=self.max.y).contains(&pos.y)
+    }
+
+    // the smallest region holding the rectangle
+    fn rotated(&self) -> Region {
+        Region {
+            u: (self.min.x as i64 + self.min.y as i64, self.max.x as i64 + self.max.y as i64),
+            v: (self.min.x as i64 - self.max.y as i64, self.max.x as i64 - self.min.y as i64),
+        }
+    }
+}
+
+/// A rectangle in coordinates turned by 45°, `u = x + y` and `v = x - y`, bounds included.
+///
+/// Only the points where `u` and `v` are both even or both odd are cells of the grid.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Region {
+    pub u: (i64, i64),
+    pub v: (i64, i64),
+}
+
+impl Region {
+    // the cells within a manhattan distance of the centre
+    pub fn diamond(center: Position, radius: i64) -> Region {
+        let (u, v) = (center.x as i64 + center.y as i64, center.x as i64 - center.y as i64);
+        Region {
+            u: (u - radius, u + radius),
+            v: (v - radius, v + radius),
+        }
+    }
+
+    pub fn contains(&self, pos: &Position) -> bool {
+        let (u, v) = (pos.x as i64 + pos.y as i64, pos.x as i64 - pos.y as i64);
+        (self.u.0..=self.u.1).contains(&u) && (self.v.0..=self.v.1).contains(&v)
+    }
+
+    /// The number of cells of the rectangle in the region.
+    pub fn count_in(&self, rect: &Rect) -> i64 {
+        if rect.is_empty() || self.u.0 > self.u.1 || self.v.0 > self.v.1 {
+            return 0;
+        }
+        let (u0, u1, v0, v1) = (self.u.0 - 1, self.u.1, self.v.0 - 1, self.v.1);
+        (below(rect, u1, v1) - below(rect, u0, v1) - below(rect, u1, v0) + below(rect, u0, v0))
+            as i64
+    }
+
+    /// The cells of the rectangle in the region, column by column.
+    pub fn cells_in<'a>(&'a self, rect: &'a Rect) -> impl Iterator<Item = Position> + 'a {
+        let (u0, u1, v0, v1) = (self.u.0, self.u.1, self.v.0, self.v.1);
+        let first = (rect.min.x as i64).max((u0 + v0).div_euclid(2));
+        let last = (rect.max.x as i64).min((u1 + v1).div_euclid(2));
+        (first..=last).flat_map(move |x| {
+            let low = (rect.min.y as i64).max(u0 - x).max(x - v1);
+            let high = (rect.max.y as i64).min(u1 - x).min(x - v0);
+            (low..=high).map(move |y| Position::new(x as i32, y as i32))
+        })
+    }
+}
+
+// the sum of max(0, c + d * x) for x in a..=b
+fn positive_sum(c: i128, d: i128, a: i128, b: i128) -> i128 {
+    let (a, b) = match d.cmp(&0) {
+        Ordering::Equal if c > 0 => (a, b),
+        Ordering::Equal => return 0,
+        Ordering::Greater => (a.max(-(c.div_euclid(d))), b),
+        Ordering::Less => (a, b.min(c.div_euclid(-d))),
+    };
+    if a > b {
+        return 0;
+    }
+    let n = b - a + 1;
+    c * n + d * ((a + b) * n / 2)
+}
+
+// the number of cells of the rectangle with x + y <= u and x - y <= v
+fn below(rect: &Rect, u: i64, v: i64) -> i128 {
+    let (x0, x1) = (rect.min.x as i128, rect.max.x as i128);
+    let (y0, y1) = (rect.min.y as i128, rect.max.y as i128);
+    let (u, v) = (u as i128, v as i128);
+    // past these columns the diagonals rather than the rectangle bound the column
+    let (lower_turn, upper_turn) = (y0 + v, u - y1);
+
+    let mut cuts = vec![x0, x1 + 1, lower_turn + 1, upper_turn + 1];
+    cuts.retain(|x| (x0..=x1 + 1).contains(x));
+    cuts.sort();
+    cuts.dedup();
+    cuts.windows(2)
+        .map(|w| {
+            let (a, b) = (w[0], w[1] - 1);
+            // each bound as c + d * x
+            let low = if a <= lower_turn { (y0, 0) } else { (-v, 1) };
+            let high = if a <= upper_turn { (y1, 0) } else { (u, -1) };
+            positive_sum(high.0 - low.0 + 1, high.1 - low.1, a, b)
+        })
+        .sum()
+}
+
+/// The union of a set of regions, typically the diamonds the sensors cover.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Coverage {
+    regions: Vec<Region>,
+}
+
+impl Coverage {
+    pub fn new(regions: Vec<Region>) -> Coverage {
+        Coverage { regions }
+    }
+
+    pub fn contains(&self, pos: &Position) -> bool {
+        self.regions.iter().any(|region| region.contains(pos))
+    }
+
+    /// The parts of the rectangle no region covers, as disjoint regions.
+    ///
+    /// The edges of all regions cut the rotated plane into cells that are either covered as a
+    /// whole or not at all, the uncovered ones are merged into runs and then into blocks.
+    pub fn uncovered(&self, rect: &Rect) -> Vec<Region> {
+        if rect.is_empty() {
+            return vec![];
+        }
+        let bounds = rect.rotated();
+        let cuts = |bound: (i64, i64), edges: &dyn Fn(&Region) -> (i64, i64)| {
+            let mut cuts = self.regions.iter()
+                .flat_map(|region| {
+                    let (low, high) = edges(region);
+                    [low, high + 1]
+                })
+                .filter(|c| (bound.0..=bound.1).contains(c))
+                .chain([bound.0, bound.1 + 1])
+                .collect::<Vec<_>>();
+            cuts.sort();
+            cuts.dedup();
+            cuts
+        };
+        let (u_cuts, v_cuts) = (cuts(bounds.u, &|r| r.u), cuts(bounds.v, &|r| r.v));
+
+        let mut blocks: Vec<Region> = vec![];
+        // the blocks that reached the previous strip
+        let mut open: Vec<usize> = vec![];
+        for u in u_cuts.windows(2) {
+            let (ua, ub) = (u[0], u[1] - 1);
+            let mut runs: Vec<(i64, i64)> = vec![];
+            for v in v_cuts.windows(2) {
+                let (va, vb) = (v[0], v[1] - 1);
+                let covered = self.regions.iter().any(|r| {
+                    (r.u.0..=r.u.1).contains(&ua) && (r.v.0..=r.v.1).contains(&va)
+                });
+                match runs.last_mut() {
+                    _ if covered => continue,
+                    Some(run) if run.1 + 1 == va => run.1 = vb,
+                    _ => runs.push((va, vb)),
+                }
+            }
+            open = runs.into_iter()
+                .map(|run| {
+                    match open.iter().find(|i| blocks[**i].v == run) {
+                        Some(i) => {
+                            blocks[*i].u.1 = ub;
+                            *i
+                        }
+                        None => {
+                            blocks.push(Region { u: (ua, ub), v: run });
+                            blocks.len() - 1
+                        }
+                    }
+                })
+                .collect();
+        }
+        blocks.retain(|block| block.count_in(rect) > 0);
+        blocks
+    }
+
+    /// The number of cells of the rectangle that are covered.
+    pub fn covered_in(&self, rect: &Rect) -> i64 {
+        rect.area() - self.uncovered(rect).iter().map(|r| r.count_in(rect)).sum::<i64>()
+    }
+
+    /// Every cell of the rectangle that is not covered, which should be a sparse set.
+    pub fn gaps(&self, rect: &Rect) -> Vec<Position> {
+        let uncovered = self.uncovered(rect);
+        let mut gaps = uncovered.iter()
+            .flat_map(|region| region.cells_in(rect))
+            .collect::<Vec<_>>();
+        gaps.sort_by_key(|pos| (pos.y, pos.x));
+        gaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // count the cells one by one
+    fn count_slowly(region: &Region, rect: &Rect) -> i64 {
+        (rect.min.x..=rect.max.x)
+            .flat_map(|x| (rect.min.y..=rect.max.y).map(move |y| Position::new(x, y)))
+            .filter(|pos| region.contains(pos))
+            .count() as i64
+    }
+
+    #[test]
+    fn test_count_in() {
+        let rects = [
+            Rect::new(Position::new(-3, -2), Position::new(4, 5)),
+            Rect::new(Position::new(0, 0), Position::new(0, 0)),
+            Rect::new(Position::new(-6, 1), Position::new(7, 1)),
+        ];
+        for rect in rects {
+            for (u0, u1, v0, v1) in [(-3, 4, -2, 6), (0, 0, 0, 0), (1, 1, 0, 0), (-9, 9, 2, 3), (5, 20, -20, -4)] {
+                let region = Region { u: (u0, u1), v: (v0, v1) };
+                assert_eq!(region.count_in(&rect), count_slowly(&region, &rect), "{region:?} in {rect:?}");
+                assert_eq!(region.cells_in(&rect).count() as i64, region.count_in(&rect));
+            }
+        }
+    }
+
+    #[test]
+    fn test_uncovered() {
+        let coverage = Coverage::new(vec![
+            Region::diamond(Position::new(0, 0), 2),
+            Region::diamond(Position::new(4, 0), 2),
+        ]);
+        let rect = Rect::new(Position::new(-2, -2), Position::new(6, 2));
+
+        assert_eq!(coverage.covered_in(&rect), 25);
+        assert_eq!(coverage.gaps(&rect).len(), 20);
+        assert!(coverage.gaps(&rect).iter().all(|pos| !coverage.contains(pos)));
+        let row = Rect::new(Position::new(-2, 0), Position::new(6, 0));
+        assert_eq!(coverage.gaps(&row), vec![]);
+        assert_eq!(coverage.uncovered(&row), vec![]);
+    }
+}
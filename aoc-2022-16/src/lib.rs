@@ -1,4 +1,5 @@
 use std::hash::Hash;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use std::collections::HashMap;
 use nom::{
@@ -10,10 +11,16 @@ use nom::{
     combinator::opt,
 };
 
+pub mod planner;
+
 #[derive(thiserror::Error, Debug)]
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("Unknown valve {0}")]
+    UnknownValve(ValveId),
+    #[error("Too many valves with flow to plan for: {0}")]
+    TooManyValves(usize),
 }
         
 #[derive(Debug, PartialEq, Eq)]
@@ -55,6 +62,12 @@ impl ValveId {
     }
 }
 
+impl Display for ValveId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct Tunnel {
     to: ValveId,
@@ -111,12 +124,13 @@ impl Valve {
 }
 
 
-fn simplify_once(valves: &[Valve]) -> Option<Vec<Valve>> {
+// bypass a valve without flow sitting in the middle of a corridor, `keep` is never bypassed
+fn simplify_once(valves: &[Valve], keep: &ValveId) -> Option<Vec<Valve>> {
     let mut valve_map: HashMap<ValveId, Valve> = valves.iter()
         .map(|v| (v.id.clone(), v.clone()))
         .collect();
 
-    valves.iter().find(|v| v.flow == 0 && v.tunnels.len() == 2)
+    valves.iter().find(|v| v.flow == 0 && v.tunnels.len() == 2 && &v.id != keep)
         .map(|valve | {
             let tunnels = valve.tunnels.iter().collect::<Vec<_>>();
             let valve_id0 = tunnels[0].0.clone();
@@ -142,10 +156,10 @@ fn simplify_once(valves: &[Valve]) -> Option<Vec<Valve>> {
 
 }
 
-fn simplify(valves: &[Valve]) -> Vec<Valve> {
+fn simplify(valves: &[Valve], keep: &ValveId) -> Vec<Valve> {
     let mut valves = valves.to_vec();
     let _ = std::iter::from_fn(|| {
-        simplify_once(&valves).map(|v| {
+        simplify_once(&valves, keep).map(|v| {
             valves = v;
         })
     })
@@ -268,9 +282,9 @@ pub fn simplified_graph() -> Vec<Valve> {
 
 
 // use Floyd-Warshall algorithm to simplify the graph
-fn extend_distances(valves: &[Valve]) -> Vec<Valve> {
+fn extend_distances(valves: &[Valve], keep: &ValveId) -> Vec<Valve> {
 
-    let mut valves = simplify(valves);
+    let mut valves = simplify(valves, keep);
 
     let valve_map: HashMap<ValveId, Valve> = valves.iter()
         .map(|v| (v.id.clone(), v.clone()))
@@ -293,95 +307,15 @@ fn extend_distances(valves: &[Valve]) -> Vec<Valve> {
     valves
 }
 
-pub fn best_path(valves: &[Valve], start: &ValveId, time: u32) -> u32{
-
-    let valves = extend_distances(&simplify(valves));
-
-
-    let valve_map: HashMap<ValveId, Valve> = valves.iter()
-        .map(|v| (v.id.clone(), v.clone()))
-        .collect();
-
-    let mut open = vec![(vec![start.clone()], vec![(0, time)], 0, time)];
-    let mut best = (vec![], vec![], 0, 0);
-
-    while let Some((path, contrib, pressure, time_remaining)) = open.pop() {
-        if pressure > best.2 {
-            best = (path.clone(), contrib.clone(), pressure, time_remaining);
-        }
-
-        let last_valve_id = path.last().unwrap();
-        let valve = valve_map.get(last_valve_id).unwrap();
-
-        for (to, distance) in &valve.tunnels {
-            if path.contains(to) || time_remaining < distance + 1 {
-                continue;
-            }
-            let mut new_path = path.clone();
-            new_path.push(to.clone());
-            let time_remaining = time_remaining - distance - 1;
-            let mut new_contrib = contrib.clone();
-            let to_valve = valve_map.get(to).unwrap();
-            new_contrib.push((to_valve.flow, time_remaining));
-            let new_pressure = pressure + to_valve.flow * time_remaining;
-            open.push((new_path, new_contrib, new_pressure, time_remaining));
-        }
-    };
-
-    best.2
+pub fn best_path(valves: &[Valve], start: &ValveId, time: u32) -> Result<u32, AocError> {
+    planner::plan(valves, start, time, 1).map(|plan| plan.pressure)
 }
 
-pub fn best_path2(valves: &[Valve], start: &ValveId, time: u32) -> u32{
-
-    let valves = extend_distances(&simplify(valves));
-
-    let valve_map: HashMap<ValveId, Valve> = valves.iter()
-        .map(|v| (v.id.clone(), v.clone()))
-        .collect();
-
-    let mut open = vec![(vec![start.clone()], 0, time, vec![start.clone()], 0, time)];
-    let mut best = (vec![], 0, 0, vec![], 0, 0);
-
-    while let Some((path_h, pressure_h, time_remaining_h, path_e, pressure_e, time_remaining_e))  = open.pop() {
-        if pressure_h + pressure_e  > best.1 + best.4 {
-            best = (path_h.clone(), pressure_h, time_remaining_h, path_e.clone(), pressure_e, time_remaining_e);
-        }
-
-        if time_remaining_h > time_remaining_e {
-            let last_valve_id = path_h.last().unwrap();
-            let valve = valve_map.get(last_valve_id).unwrap();
-
-            for (to, distance) in &valve.tunnels {
-                if path_h.contains(to) || path_e.contains(to) || time_remaining_h < distance + 1 {
-                    continue;
-                }
-                let mut new_path = path_h.clone();
-                new_path.push(to.clone());
-                let time_remaining = time_remaining_h - distance - 1;
-                let to_valve = valve_map.get(to).unwrap();
-                let new_pressure = pressure_h + to_valve.flow * time_remaining;
-                open.push((new_path, new_pressure, time_remaining, path_e.clone(), pressure_e, time_remaining_e));
-            }
-        } else {
-            let last_valve_id = path_e.last().unwrap();
-            let valve = valve_map.get(last_valve_id).unwrap();
-
-            for (to, distance) in &valve.tunnels {
-                if path_e.contains(to) || path_h.contains(to) || time_remaining_e < distance + 1 {
-                    continue;
-                }
-                let mut new_path = path_e.clone();
-                new_path.push(to.clone());
-                let time_remaining = time_remaining_e - distance - 1;
-                let to_valve = valve_map.get(to).unwrap();
-                let new_pressure = pressure_e + to_valve.flow * time_remaining;
-                open.push((path_h.clone(), pressure_h, time_remaining_h, new_path, new_pressure, time_remaining));
-            }
-        };
-    };
-
-    best.1 + best.4
+// you and the elephant
+pub fn best_path2(valves: &[Valve], start: &ValveId, time: u32) -> Result<u32, AocError> {
+    planner::plan(valves, start, time, 2).map(|plan| plan.pressure)
 }
+
 #[cfg(test)]
 mod tests {
 
@@ -401,7 +335,7 @@ mod tests {
     fn test_simplify() {
         let input = input_data();
         let expected = simplified_graph();
-        let actual = simplify(&input.valves);
+        let actual = simplify(&input.valves, &ValveId::new("AA"));
         assert_eq!(actual.len(), expected.len());
     }
 
@@ -437,13 +371,13 @@ mod tests {
             Tunnel::new_with_distance("BB", 2)])},
         ];
 
-        let actual = extend_distances(&input);
+        let actual = extend_distances(&input, &ValveId::new("AA"));
         assert_eq!(actual.len(), expected.len());
     }
 
     #[test]
     fn test_extend_distances2() {
-        let actual = extend_distances(&input_data().valves);
+        let actual = extend_distances(&input_data().valves, &ValveId::new("AA"));
         assert_eq!(actual.len(), 7);
     }
 
@@ -452,7 +386,7 @@ mod tests {
     fn test_best_path() {
 
         let input = input_data();
-        let actual = best_path(&input.valves, &ValveId::new("AA"), 30);
+        let actual = best_path(&input.valves, &ValveId::new("AA"), 30).unwrap();
         let expected = 1651;
         assert_eq!(actual, expected);
 
@@ -462,7 +396,7 @@ mod tests {
     fn test_best_path2() {
 
         let input = input_data();
-        let actual = best_path2(&input.valves, &ValveId::new("AA"), 26);
+        let actual = best_path2(&input.valves, &ValveId::new("AA"), 26).unwrap();
         let expected = 1707;
         assert_eq!(actual, expected);
 
@@ -5,11 +5,11 @@ const INPUT: &str = include_str!("../data/input.txt");
 
 
 fn part1(input: &InputModel) -> Result<String,AocError> {
-    Ok(best_path(&input.valves, &ValveId::new("AA"), 30).to_string())
+    Ok(best_path(&input.valves, &ValveId::new("AA"), 30)?.to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
-    Ok(best_path2(&input.valves, &ValveId::new("AA"), 26).to_string())
+    Ok(best_path2(&input.valves, &ValveId::new("AA"), 26)?.to_string())
 }
 
 fn main() -> Result<(), AocError> {
@@ -0,0 +1,325 @@
+use std::{collections::HashMap, fmt::{Display, Formatter}};
+
+use crate::{extend_distances, AocError, Valve, ValveId};
+
+/// A valve opened by an agent during `minute`, it releases pressure from the next minute on.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Opening {
+    pub valve: ValveId,
+    pub minute: u32,
+}
+
+/// The best total pressure with the valves every agent opens, in order.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Plan {
+    pub pressure: u32,
+    pub schedules: Vec<Vec<Opening>>,
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} pressure released", self.pressure)?;
+        for (agent, schedule) in self.schedules.iter().enumerate() {
+            let openings = schedule.iter()
+                .map(|o| format!("{}@{}", o.valve, o.minute))
+                .collect::<Vec<_>>();
+            writeln!(f, "agent {}: {}", agent + 1, openings.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+const UNREACHABLE: u32 = u32::MAX / 2;
+
+// the search keeps a best pressure for every subset of valves and splits each subset between the
+// agents, which takes 3^n steps per extra agent, past this many valves it runs out of time
+const MAX_VALVES: usize = 16;
+
+// valve and minute it is opened in, valves are numbered by their bit in the masks
+type Route = Vec<(usize, u32)>;
+
+// the valves worth opening, numbered as bits of a mask, with the shortest distances between
+// them and from the start
+struct Network {
+    ids: Vec<ValveId>,
+    flows: Vec<u32>,
+    distances: Vec<Vec<u32>>,
+    from_start: Vec<u32>,
+}
+
+impl Network {
+    fn new(valves: &[Valve], start: &ValveId) -> Result<Network, AocError> {
+        if !valves.iter().any(|v| &v.id == start) {
+            return Err(AocError::UnknownValve(start.clone()));
+        }
+        let valves = extend_distances(valves, start);
+        let distance = |from: &Valve, to: &ValveId| match &from.id == to {
+            true => Some(0),
+            false => from.tunnels.get(to).copied(),
+        };
+        let start = valves.iter().find(|v| &v.id == start).unwrap();
+
+        let useful = valves.iter()
+            .filter(|v| v.flow > 0 && distance(start, &v.id).is_some())
+            .collect::<Vec<_>>();
+        if useful.len() > MAX_VALVES {
+            return Err(AocError::TooManyValves(useful.len()));
+        }
+        Ok(Network {
+            ids: useful.iter().map(|v| v.id.clone()).collect(),
+            flows: useful.iter().map(|v| v.flow).collect(),
+            distances: useful.iter()
+                .map(|from| useful.iter().map(|to| distance(from, &to.id).unwrap_or(UNREACHABLE)).collect())
+                .collect(),
+            from_start: useful.iter().map(|v| distance(start, &v.id).unwrap()).collect(),
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+// a valve opened on a route, linked to the opening before it
+struct Step {
+    previous: Option<usize>,
+    valve: usize,
+    minute: u32,
+}
+
+// the best one agent can do opening exactly the valves of each mask
+struct Search<'a> {
+    network: &'a Network,
+    time: u32,
+    steps: Vec<Step>,
+    // the pressure and last step of the best route for each mask
+    best: Vec<Option<(u32, Option<usize>)>>,
+    // the most pressure any route had at a valve, with the same valves open and time left
+    seen: HashMap<(Option<usize>, usize, u32), u32>,
+}
+
+impl Search<'_> {
+    fn explore(&mut self, at: Option<usize>, remaining: u32, mask: usize, pressure: u32, last: Option<usize>) {
+        let key = (at, mask, remaining);
+        if self.seen.get(&key).is_some_and(|p| *p >= pressure) {
+            return;
+        }
+        self.seen.insert(key, pressure);
+        if self.best[mask].is_none_or(|(p, _)| pressure > p) {
+            self.best[mask] = Some((pressure, last));
+        }
+        for next in 0..self.network.len() {
+            if mask & 1 << next != 0 {
+                continue;
+            }
+            let distance = match at {
+                Some(at) => self.network.distances[at][next],
+                None => self.network.from_start[next],
+            };
+            // opening a valve in the last minute releases nothing
+            if distance + 1 >= remaining {
+                continue;
+            }
+            let remaining = remaining - distance - 1;
+            self.steps.push(Step { previous: last, valve: next, minute: self.time - remaining });
+            let step = Some(self.steps.len() - 1);
+            self.explore(Some(next), remaining, mask | 1 << next, pressure + self.network.flows[next] * remaining, step);
+        }
+    }
+
+    // the openings of the best route for the mask, in order
+    fn route(&self, mask: usize) -> Route {
+        let last = self.best[mask].and_then(|(_, last)| last);
+        let mut route = std::iter::successors(last, |i| self.steps[*i].previous)
+            .map(|i| (self.steps[i].valve, self.steps[i].minute))
+            .collect::<Vec<_>>();
+        route.reverse();
+        route
+    }
+}
+
+// iterate over all subsets of the mask, including the empty set
+fn submasks(mask: usize) -> impl Iterator<Item = usize> {
+    let mut next = Some(mask);
+    std::iter::from_fn(move || {
+        let sub = next?;
+        next = if sub == 0 { None } else { Some((sub - 1) & mask) };
+        Some(sub)
+    })
+}
+
+/// Plan `agents` agents starting together at `start`, each opening a different set of valves.
+///
+/// Every agent is solved on its own for every subset of the valves, then the subsets are
+/// shared out between the agents by dynamic programming over the masks.
+pub fn plan(valves: &[Valve], start: &ValveId, time: u32, agents: usize) -> Result<Plan, AocError> {
+    let network = Network::new(valves, start)?;
+    let full = (1 << network.len()) - 1;
+
+    let mut search = Search {
+        network: &network,
+        time,
+        steps: Vec::new(),
+        best: vec![None; full + 1],
+        seen: HashMap::new(),
+    };
+    search.explore(None, time, 0, 0, None);
+
+    // the best subset a single agent can open within every mask
+    let mut within = search.best.iter()
+        .enumerate()
+        .map(|(mask, b)| match b {
+            Some((pressure, _)) => (*pressure, mask),
+            None => (0, 0),
+        })
+        .collect::<Vec<_>>();
+    for bit in 0..network.len() {
+        for mask in 0..=full {
+            if mask & 1 << bit != 0 && within[mask ^ 1 << bit].0 > within[mask].0 {
+                within[mask] = within[mask ^ 1 << bit];
+            }
+        }
+    }
+
+    // team[mask] is the best the agents so far do with the valves of the mask, the choices
+    // remember which valves each extra agent takes
+    let mut team = within.iter().map(|(pressure, _)| *pressure).collect::<Vec<_>>();
+    let mut choices: Vec<Vec<usize>> = Vec::new();
+    for _ in 1..agents {
+        let mut next = vec![0; full + 1];
+        let mut choice = vec![0; full + 1];
+        for mask in 0..=full {
+            for sub in submasks(mask) {
+                let pressure = within[sub].0 + team[mask ^ sub];
+                if pressure > next[mask] {
+                    next[mask] = pressure;
+                    choice[mask] = sub;
+                }
+            }
+        }
+        team = next;
+        choices.push(choice);
+    }
+
+    let mut mask = full;
+    let mut subsets = Vec::new();
+    for choice in choices.iter().rev() {
+        subsets.push(within[choice[mask]].1);
+        mask ^= choice[mask];
+    }
+    if agents > 0 {
+        subsets.push(within[mask].1);
+    }
+    let schedules = subsets.iter()
+        .map(|&subset| {
+            search.route(subset)
+                .into_iter()
+                .map(|(valve, minute)| Opening { valve: network.ids[valve].clone(), minute })
+                .collect()
+        })
+        .collect();
+
+    Ok(Plan {
+        pressure: if agents > 0 { team[full] } else { 0 },
+        schedules,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::input_data;
+
+    fn opening(valve: &str, minute: u32) -> Opening {
+        Opening { valve: ValveId::new(valve), minute }
+    }
+
+    // the pressure the schedules release on their own
+    fn released(valves: &[Valve], plan: &Plan, time: u32) -> u32 {
+        plan.schedules.iter()
+            .flatten()
+            .map(|o| valves.iter().find(|v| v.id == o.valve).unwrap().flow * (time - o.minute))
+            .sum()
+    }
+
+    #[test]
+    fn test_plan_one_agent() {
+        let valves = input_data().valves;
+        let actual = plan(&valves, &ValveId::new("AA"), 30, 1).unwrap();
+        let expected = Plan {
+            pressure: 1651,
+            schedules: vec![vec![
+                opening("DD", 2),
+                opening("BB", 5),
+                opening("JJ", 9),
+                opening("HH", 17),
+                opening("EE", 21),
+                opening("CC", 24),
+            ]],
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_plan_with_elephant() {
+        let valves = input_data().valves;
+        let mut actual = plan(&valves, &ValveId::new("AA"), 26, 2).unwrap();
+        actual.schedules.sort_by_key(|s| s[0].minute);
+
+        assert_eq!(actual.pressure, 1707);
+        assert_eq!(actual.schedules, vec![
+            vec![opening("DD", 2), opening("HH", 7), opening("EE", 11)],
+            vec![opening("JJ", 3), opening("BB", 7), opening("CC", 9)],
+        ]);
+        assert_eq!(released(&valves, &actual, 26), 1707);
+    }
+
+    #[test]
+    fn test_plan_many_agents() {
+        let valves = input_data().valves;
+        let pressures = (0..=4)
+            .map(|agents| plan(&valves, &ValveId::new("AA"), 26, agents).unwrap())
+            .inspect(|p| assert_eq!(released(&valves, p, 26), p.pressure))
+            .map(|p| p.pressure)
+            .collect::<Vec<_>>();
+
+        assert_eq!(pressures[0], 0);
+        assert_eq!(pressures[2], 1707);
+        assert!(pressures.windows(2).all(|w| w[0] <= w[1]));
+        assert!(plan(&valves, &ValveId::new("ZZ"), 26, 1).is_err());
+    }
+
+    // a hub with a tunnel to each of the valves that have flow
+    fn star(valves: usize) -> Vec<Valve> {
+        let valve = |id: &str, flow, tunnels: &[String]| Valve {
+            id: ValveId::new(id),
+            flow,
+            tunnels: tunnels.iter().map(|t| (ValveId::new(t), 1)).collect(),
+        };
+        let ids = ('A'..='Z').map(|c| format!("B{c}")).take(valves).collect::<Vec<_>>();
+        ids.iter()
+            .map(|id| valve(id, 1, &["AA".to_string()]))
+            .chain([valve("AA", 0, &ids)])
+            .collect()
+    }
+
+    #[test]
+    fn test_too_many_valves() {
+        let start = ValveId::new("AA");
+
+        assert!(matches!(
+            plan(&star(MAX_VALVES + 1), &start, 30, 1),
+            Err(AocError::TooManyValves(n)) if n == MAX_VALVES + 1
+        ));
+        // the most valves there may be, with two agents and the whole time for them
+        let valves = star(MAX_VALVES);
+        let started = std::time::Instant::now();
+        let actual = plan(&valves, &start, 30, 2).unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(60));
+        // eight valves each, opened every three minutes from minute 2 on
+        assert_eq!(actual.pressure, 2 * (28 + 25 + 22 + 19 + 16 + 13 + 10 + 7));
+        assert_eq!(released(&valves, &actual, 30), actual.pressure);
+    }
+}
@@ -1,6 +1,7 @@
 use std::str::FromStr;
-use std::collections::HashMap;
-use aoc_common::position::Position;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use aoc_common::{bitset::BitSet, position::Position};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel  {
@@ -11,6 +12,12 @@ pub struct InputModel  {
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("Not a rock:\n{0}")]
+    InvalidShape(String),
+    #[error("Invalid chamber rules: {0}")]
+    InvalidRules(String),
+    #[error("No jets to push the rocks")]
+    NoJets,
 }
         
 impl FromStr for InputModel {
@@ -34,105 +41,146 @@ pub enum Jet {
     Right,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Shape {
-    Bar,
-    Plus,
-    Hook,
-    Pole,
-    Square,
+// the five rocks of the puzzle, top row first, separated by blank lines
+pub const STANDARD_PIECES: &str = "\
+####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+
+/// A rock drawn with `#`, cells are relative to its top left corner with `y` going up.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Shape {
+    cells: Vec<Position>,
+    width: i32,
+    height: i32,
 }
 
 impl Shape {
-    const VALUES: [Shape; 5] = [
-        Shape::Bar, 
-        Shape::Plus, 
-        Shape::Hook, 
-        Shape::Pole, 
-        Shape::Square
-    ];
+    pub fn parse(art: &str) -> Result<Shape, AocError> {
+        let mut cells = Vec::new();
+        for (row, line) in art.lines().enumerate() {
+            for (column, c) in line.trim_end().chars().enumerate() {
+                match c {
+                    '#' => cells.push(Position::new(column as i32, -(row as i32))),
+                    '.' => {}
+                    _ => return Err(AocError::InvalidShape(art.to_string())),
+                }
+            }
+        }
+        // trim empty rows and columns around the rock
+        let left = cells.iter().map(|p| p.x).min()
+            .ok_or_else(|| AocError::InvalidShape(art.to_string()))?;
+        let top = cells.iter().map(|p| p.y).max().unwrap();
+        let cells = cells.iter()
+            .map(|p| Position::new(p.x - left, p.y - top))
+            .collect::<Vec<_>>();
+        let width = cells.iter().map(|p| p.x + 1).max().unwrap();
+        let height = cells.iter().map(|p| 1 - p.y).max().unwrap();
+        Ok(Shape { cells, width, height })
+    }
+
+    // shapes separated by blank lines
+    pub fn parse_set(art: &str) -> Result<Vec<Shape>, AocError> {
+        art.trim()
+            .split("\n\n")
+            .map(Shape::parse)
+            .collect()
+    }
 
     fn size(&self) -> (i32, i32) {
-        match self {
-            Shape::Bar => (4, 1),
-            Shape::Plus => (3, 3),
-            Shape::Hook => (3, 3),
-            Shape::Pole => (1, 4),
-            Shape::Square => (2, 2),
-        }
+        (self.width, self.height)
     }
 
     // give relative positions of the shape starting from the top left corner
-    fn shape(&self) -> Vec<Position> {
-        match self {
-            Shape::Bar => vec![
-                Position::new(0, 0),
-                Position::new(1, 0),
-                Position::new(2, 0),
-                Position::new(3, 0),
-            ],
-            Shape::Plus => vec![
-                Position::new(1, 0),
-                Position::new(0, -1),
-                Position::new(1, -1),
-                Position::new(2, -1),
-                Position::new(1, -2),
-            ],
-            Shape::Hook => vec![
-                Position::new(2, 0),
-                Position::new(2, -1),
-                Position::new(0, -2),
-                Position::new(1, -2),
-                Position::new(2, -2),
-            ],
-            Shape::Pole => vec![
-                Position::new(0, 0),
-                Position::new(0, -1),
-                Position::new(0, -2),
-                Position::new(0, -3),
-            ],
-            Shape::Square => vec![
-                Position::new(0, 0),
-                Position::new(1, 0),
-                Position::new(0, -1),
-                Position::new(1, -1),
-            ],
+    fn shape(&self) -> &[Position] {
+        &self.cells
+    }
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            let row = (0..self.width)
+                .map(|x| if self.cells.contains(&Position::new(x, -y)) { '#' } else { '.' })
+                .collect::<String>();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// How wide the chamber is, where rocks appear and which rocks fall in turn.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Rules {
+    width: i32,
+    // empty columns between the left wall and a new rock
+    spawn_left: i32,
+    // empty rows between the top of the tower and a new rock
+    spawn_gap: i32,
+    pieces: Vec<Shape>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            width: 7,
+            spawn_left: 2,
+            spawn_gap: 3,
+            pieces: Shape::parse_set(STANDARD_PIECES).unwrap(),
         }
     }
 }
 
+impl Rules {
+    pub fn new(width: i32, spawn_left: i32, spawn_gap: i32, pieces: &str) -> Result<Rules, AocError> {
+        let rules = Rules { width, spawn_left, spawn_gap, pieces: Shape::parse_set(pieces)? };
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    fn validate(&self) -> Result<(), AocError> {
+        if self.width <= 0 {
+            return Err(AocError::InvalidRules("the chamber has no room".to_string()));
+        }
+        if self.spawn_left < 0 || self.spawn_gap < 0 {
+            return Err(AocError::InvalidRules("rocks must appear inside the chamber".to_string()));
+        }
+        if self.pieces.is_empty() {
+            return Err(AocError::InvalidRules("there are no rocks to drop".to_string()));
+        }
+        if let Some(piece) = self.pieces.iter().find(|p| self.spawn_left + p.width > self.width) {
+            return Err(AocError::InvalidRules(format!("rock does not fit the chamber:\n{}", piece)));
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct Block {
+pub struct Block {
     position: Position,
-    shape: Shape,
+    // index into the pieces of the rules
+    shape: usize,
     stuck: bool,
 }
 
 impl Block {
-    fn new(position: Position, shape: Shape) -> Self {
+    fn new(position: Position, shape: usize) -> Self {
         Self { position, shape, stuck: false }
     }
-    
-    // return top left corner and bottom right corner
-    fn bounds(&self) -> (Position, Position) {
-        let (width, height) = self.shape.size();
-        (
-            self.position,
-            Position::new(
-                self.position.x + width - 1,
-                self.position.y - height + 1,
-            ) 
-        )
-    }
-
-    fn shape(&self) -> Vec<Position> {
-        self.shape
-            .shape()
-            .iter()
-            .map(|p| *p + self.position)
-            .collect()
-    }
 
     fn fall(&self) -> Self {
         Self {
@@ -157,94 +205,76 @@ impl Block {
             ..*self
         }
     }
-
-    fn collide(&self, other: &Self) -> bool {
-        let my_shape = self.shape();
-        let other_shape = other.shape();
-
-        my_shape
-            .iter()
-            .any(|p| other_shape.contains(p))
-    }
-
-    fn encode(&self) -> i64 {
-        let shape_code = match self.shape {
-            Shape::Bar => 0,
-            Shape::Plus => 1,
-            Shape::Hook => 2,
-            Shape::Pole => 3,
-            Shape::Square => 4,
-        };
-        let position_code = self.position.x as i64;
-        shape_code + 10 * position_code
-    }
-
 }
 
-pub struct Chamber {
+// surface, jet index and rock index
+type StateKey = (Vec<(i32, i32)>, usize, usize);
 
-    // we keep track of the position of the blocks resting
-    // place in order they are fallen.
-    // i.e. the highest blocks are at the end of the vector
-    blocks: Vec<Block>,
+pub struct Chamber {
+    rules: Rules,
+    // settled rock per level, the floor is just below the first row
+    rows: Vec<BitSet>,
     jets: Vec<Jet>,
-    cache: HashMap<(usize, usize), (i64, i64)>,
     j: usize,
     s: usize,
     n: usize,
 }
 
-
 impl Chamber {
-    const WIDTH: usize = 7;
+    pub fn new(jets: &[Jet]) -> Result<Chamber, AocError> {
+        Chamber::with_rules(jets, Rules::default())
+    }
 
-    pub fn new(jets: &[Jet]) -> Chamber {
-        Chamber {
-            blocks: Vec::with_capacity(10000),
+    pub fn with_rules(jets: &[Jet], rules: Rules) -> Result<Chamber, AocError> {
+        if jets.is_empty() {
+            return Err(AocError::NoJets);
+        }
+        rules.validate()?;
+        Ok(Chamber {
+            rules,
+            rows: Vec::new(),
             jets: jets.to_vec(),
-            cache: HashMap::new(),
             j: 0,
             s: 0,
             n: 0,
-        }
+        })
     }
 
-    fn max_height(&self) -> i32 {
-        self.last_blocks().iter()
-            .map(|b| b.position.y + 1)
-            .max()
-            .unwrap_or(0)
+    pub fn max_height(&self) -> i32 {
+        self.rows.len() as i32
     }
 
-    // check if position is between the walls and above the floor
-    fn is_position_in_chamber(&self, position: Position) -> bool {
-        position.x >= 0 && position.y >= 0 && position.x < Chamber::WIDTH as i32
+    // the number of rocks which came to rest
+    pub fn rocks(&self) -> usize {
+        self.n
     }
 
-    fn is_block_in_chamber(&self, block: &Block) -> bool {
-        let (top_left, bottom_right) = block.bounds();
-        self.is_position_in_chamber(top_left) && self.is_position_in_chamber(bottom_right)
+    fn cells<'a>(&'a self, block: &'a Block) -> impl Iterator<Item = Position> + 'a {
+        self.rules.pieces[block.shape]
+            .shape()
+            .iter()
+            .map(|p| *p + block.position)
     }
 
-    fn add_shape(&mut self, shape: Shape) -> Block {
-        let x = 2;
-        let y = self.max_height() + shape.size().1 + 2;
-        let position = Position::new(x, y);
-        Block::new(position, shape)
+    // walls and floor count as occupied
+    fn is_occupied(&self, position: Position) -> bool {
+        if position.x < 0 || position.y < 0 || position.x >= self.rules.width {
+            return true;
+        }
+        self.rows
+            .get(position.y as usize)
+            .is_some_and(|row| row.contains(position.x as usize))
     }
 
-    fn last_blocks(&self) -> &[Block] {
-        let from = self.blocks.len();
-        let from = if from < 100 { 0 } else { from - 100 };
-        &self.blocks.as_slice()[from..]
+    fn is_free(&self, block: &Block) -> bool {
+        self.cells(block).all(|p| !self.is_occupied(p))
     }
 
-    fn is_free(&self, block: &Block) -> bool {
-        if !self.is_block_in_chamber(block) {
-            return false;
-        }
-        self.last_blocks().iter()
-            .all(|b| !b.collide(block))
+    fn add_shape(&mut self, shape: usize) -> Block {
+        let x = self.rules.spawn_left;
+        let y = self.max_height() + self.rules.pieces[shape].size().1 + self.rules.spawn_gap - 1;
+        let position = Position::new(x, y);
+        Block::new(position, shape)
     }
 
     fn next_position(&mut self, block: &Block, jet: Jet) -> Block {
@@ -266,11 +296,17 @@ impl Chamber {
     }
 
     fn fix_block(&mut self, block: Block) {
-        self.blocks.push(block);
+        let cells = self.cells(&block).collect::<Vec<_>>();
+        for p in cells {
+            while self.rows.len() <= p.y as usize {
+                self.rows.push(BitSet::new());
+            }
+            self.rows[p.y as usize].insert(p.x as usize);
+        }
         self.n += 1;
     }
 
-    fn let_shape_fall(&mut self, shape: Shape) -> Block {
+    fn let_shape_fall(&mut self, shape: usize) -> Block {
         let mut block = self.add_shape(shape);
         loop {
             let jet = self.jets[self.j];
@@ -284,62 +320,103 @@ impl Chamber {
         block
     }
 
-    pub fn play_rounds(&mut self, rounds: usize) -> usize {
-        for i in 0..rounds {
-            if i % 10000 == 0 {
-                println!("play round {}", i);
+    // drop the next rock of the rules
+    pub fn drop_rock(&mut self) -> Block {
+        let shape = self.s;
+        self.s = (self.s + 1) % self.rules.pieces.len();
+        self.let_shape_fall(shape)
+    }
+
+    // the open cells a rock could still reach from above, relative to the top of the tower;
+    // whatever lies below them can never matter again
+    fn surface(&self) -> Vec<(i32, i32)> {
+        let top = self.max_height();
+        let mut seen = HashSet::new();
+        let mut open = (0..self.rules.width)
+            .map(|x| Position::new(x, top))
+            .collect::<Vec<_>>();
+        while let Some(p) = open.pop() {
+            if p.y > top || self.is_occupied(p) || !seen.insert(p) {
+                continue;
             }
+            open.extend([
+                p + Position::new(-1, 0),
+                p + Position::new(1, 0),
+                p + Position::new(0, -1),
+            ]);
+        }
+        let mut surface = seen.iter()
+            .map(|p| (p.x, top - p.y))
+            .collect::<Vec<_>>();
+        surface.sort();
+        surface
+    }
 
-            let key = (self.s, self.j);
-            let height = self.max_height() as i64;
-            if let Some((n, h)) = self.cache.get(&key) {
-                let period = self.n as i64 - n;
-                if ((rounds as i64 - *n) % period) == 0 {
-                    let delta : i64 = height - h;
-                    let rest : i64 = rounds as i64 - *n;
-                    let result = h + delta*(rest/period);
-                    return result as usize
+    /// Height of the tower after the rocks fell.
+    ///
+    /// Once the surface, the next jet and the next rock repeat a state seen before, the tower
+    /// grows the same from there on, so whole periods are skipped.
+    pub fn play_rounds(&mut self, rounds: usize) -> usize {
+        let mut seen: HashMap<StateKey, (usize, usize)> = HashMap::new();
+        let mut skipped = None;
+        while self.n < rounds {
+            if skipped.is_none() {
+                let key = (self.surface(), self.j, self.s);
+                let height = self.max_height() as usize;
+                if let Some((n, h)) = seen.insert(key, (self.n, height)) {
+                    let period = self.n - n;
+                    let periods = (rounds - self.n) / period;
+                    skipped = Some(periods * (height - h));
+                    self.n += periods * period;
+                    continue;
                 }
-            } else {
-                self.cache.insert(key, (self.n as i64, height));
             }
-
-            let shape = Shape::VALUES[self.s];
-            self.s = (self.s + 1) % 5;
-            self.let_shape_fall(shape);
+            self.drop_rock();
         }
-        self.max_height() as usize
+        self.max_height() as usize + skipped.unwrap_or(0)
     }
 
-    fn find_start_repeating_pattern(&mut self, period: usize) -> Option<(usize, i64)> {
-        println!("playing {} rounds", 2*period);
-        self.play_rounds(2*period);
-        let codes: Vec<i64> = self.blocks.iter().map(|b| b.encode()).collect();
-        (0..period)
-            .inspect(|i| if i % 10000 == 0 {println!("checking round {}", i)})
-            .find(|i| (*i..*i+100).all(|j| codes[j] == codes[j+period]))
-            .inspect(|i| println!("found round {}", i))
-            .map(|i| (
-                    i, 
-                    (self.blocks[i+period].position.y - self.blocks[i].position.y) as i64)
-                )
+    /// The top rows of the tower as the puzzle draws it, a falling rock is drawn with `@`.
+    pub fn frame(&self, falling: Option<&Block>, rows: usize) -> String {
+        let falling = falling
+            .map(|b| self.cells(b).collect::<HashSet<_>>())
+            .unwrap_or_default();
+        let top = falling.iter()
+            .map(|p| p.y + 1)
+            .max()
+            .unwrap_or(0)
+            .max(self.max_height());
+        let bottom = (top - rows as i32).max(0);
+        let mut frame = String::new();
+        for y in (bottom..top).rev() {
+            frame.push('|');
+            for x in 0..self.rules.width {
+                let p = Position::new(x, y);
+                frame.push(match (falling.contains(&p), self.is_occupied(p)) {
+                    (true, _) => '@',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                });
+            }
+            frame.push_str("|\n");
+        }
+        if bottom == 0 {
+            frame.push_str(&format!("+{}+\n", "-".repeat(self.rules.width as usize)));
+        }
+        frame
     }
+}
 
+// the whole tower
+impl Display for Chamber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.frame(None, self.rows.len()))
+    }
 }
 
-pub fn play_many_rounds(jets: Vec<Jet>, rounds: i64) -> i64 {
-    let mut chamber = Chamber::new(&jets.clone());
-    let period = 7*5*jets.len();
-    let (offset, growth) = chamber.find_start_repeating_pattern(period).unwrap();
-    let period = period as i64;
-    println!("offset: {}, growth: {}", offset, growth);
-    let multiple = (rounds - offset as i64) / period;
-    let remainder = (rounds - offset as i64) % period;
-    println!("multiple: {}, remainder: {}", multiple, remainder);
-    let mut chamber = Chamber::new(&jets.clone());
-    let base_height = chamber.play_rounds(offset + remainder as usize) as i64;
-    println!("base_height: {}", base_height);
-    base_height + (multiple * growth)
+pub fn play_many_rounds(jets: Vec<Jet>, rounds: usize) -> Result<usize, AocError> {
+    let mut chamber = Chamber::new(&jets)?;
+    Ok(chamber.play_rounds(rounds))
 }
 
 pub const TEST_INPUT: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
@@ -352,6 +429,13 @@ pub fn input_data() -> InputModel {
 mod tests {
     use super::*;
 
+    // the standard pieces in order
+    const BAR: usize = 0;
+    const PLUS: usize = 1;
+    const HOOK: usize = 2;
+    const POLE: usize = 3;
+    const SQUARE: usize = 4;
+
     #[test]
     fn test_parse() {
         let actual = TEST_INPUT.parse::<InputModel>().unwrap();
@@ -364,17 +448,17 @@ mod tests {
     #[test]
     fn add_shape() {
         let jets = TEST_INPUT.parse::<InputModel>().unwrap().moves;
-        let mut chamber = Chamber::new(&jets);
-        let actual = chamber.add_shape(Shape::Bar);
-        let expected = Block{position: Position::new(2, 3), shape: Shape::Bar, stuck: false};
+        let mut chamber = Chamber::new(&jets).unwrap();
+        let actual = chamber.add_shape(BAR);
+        let expected = Block{position: Position::new(2, 3), shape: BAR, stuck: false};
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn test_max_height() {
         let jets = TEST_INPUT.parse::<InputModel>().unwrap().moves;
-        let mut chamber = Chamber::new(&jets);
-        chamber.blocks.push(Block::new(Position::new(0, 0), Shape::Bar));
+        let mut chamber = Chamber::new(&jets).unwrap();
+        chamber.fix_block(Block::new(Position::new(0, 0), BAR));
         let actual = chamber.max_height();
         assert_eq!(actual, 1);
     }
@@ -382,76 +466,76 @@ mod tests {
     #[test]
     fn next_position() {
         let jets = TEST_INPUT.parse::<InputModel>().unwrap().moves;
-        let mut chamber = Chamber::new(&jets);
-        let block = Block::new(Position::new(2, 3), Shape::Bar);
+        let mut chamber = Chamber::new(&jets).unwrap();
+        let block = Block::new(Position::new(2, 3), BAR);
         let block = chamber.next_position(&block, Jet::Right);
-        let expected = Block::new(Position::new(3, 2), Shape::Bar);
+        let expected = Block::new(Position::new(3, 2), BAR);
         assert_eq!(block, expected);
         let block = chamber.next_position(&block, Jet::Right);
-        let expected = Block::new(Position::new(3, 1), Shape::Bar);
+        let expected = Block::new(Position::new(3, 1), BAR);
         assert_eq!(block, expected);
         let block = chamber.next_position(&block, Jet::Right);
-        let expected = Block::new(Position::new(3, 0), Shape::Bar);
+        let expected = Block::new(Position::new(3, 0), BAR);
         assert_eq!(block, expected);
         let block = chamber.next_position(&block, Jet::Left);
-        let expected = Block::new(Position::new(2, 0), Shape::Bar).stuck();
+        let expected = Block::new(Position::new(2, 0), BAR).stuck();
         assert_eq!(block, expected);
 
         // next shape should be added
-        let block = chamber.add_shape(Shape::Plus);
-        let expected = Block::new(Position::new(2, 6), Shape::Plus);
+        let block = chamber.add_shape(PLUS);
+        let expected = Block::new(Position::new(2, 6), PLUS);
         assert_eq!(block, expected);
         let block = chamber.next_position(&block, Jet::Left);
-        let expected = Block::new(Position::new(1, 5), Shape::Plus);
+        let expected = Block::new(Position::new(1, 5), PLUS);
         assert_eq!(block, expected);
         let block = chamber.next_position(&block, Jet::Right);
-        let expected = Block::new(Position::new(2, 4), Shape::Plus);
+        let expected = Block::new(Position::new(2, 4), PLUS);
         assert_eq!(block, expected);
         let block = chamber.next_position(&block, Jet::Left);
-        let expected = Block::new(Position::new(1, 3), Shape::Plus);
+        let expected = Block::new(Position::new(1, 3), PLUS);
         assert_eq!(block, expected);
         let block = chamber.next_position(&block, Jet::Right);
-        let expected = Block::new(Position::new(2, 3), Shape::Plus).stuck();
+        let expected = Block::new(Position::new(2, 3), PLUS).stuck();
         assert_eq!(block, expected);
     }
 
     #[test]
     fn test_drop_shape() {
         let jets = TEST_INPUT.parse::<InputModel>().unwrap().moves;
-        let mut chamber = Chamber::new(&jets);
-        let block = chamber.let_shape_fall(Shape::Bar);
-        let expected = Block::new(Position::new(2, 0), Shape::Bar).stuck();
+        let mut chamber = Chamber::new(&jets).unwrap();
+        let block = chamber.let_shape_fall(BAR);
+        let expected = Block::new(Position::new(2, 0), BAR).stuck();
         assert_eq!(block, expected);
-        let block = chamber.let_shape_fall(Shape::Plus);
-        let expected = Block::new(Position::new(2, 3), Shape::Plus).stuck();
+        let block = chamber.let_shape_fall(PLUS);
+        let expected = Block::new(Position::new(2, 3), PLUS).stuck();
         assert_eq!(block, expected);
-        let block = chamber.let_shape_fall(Shape::Hook);
-        let expected = Block::new(Position::new(0, 5), Shape::Hook).stuck();
+        let block = chamber.let_shape_fall(HOOK);
+        let expected = Block::new(Position::new(0, 5), HOOK).stuck();
         assert_eq!(block, expected);
-        let block = chamber.let_shape_fall(Shape::Pole);
-        let expected = Block::new(Position::new(4, 6), Shape::Pole).stuck();
+        let block = chamber.let_shape_fall(POLE);
+        let expected = Block::new(Position::new(4, 6), POLE).stuck();
         assert_eq!(block, expected);
-        let block = chamber.let_shape_fall(Shape::Square);
-        let expected = Block::new(Position::new(4, 8), Shape::Square).stuck();
+        let block = chamber.let_shape_fall(SQUARE);
+        let expected = Block::new(Position::new(4, 8), SQUARE).stuck();
         assert_eq!(block, expected);
     }
 
     #[test]
     fn test_play_rounds() {
         let jets = TEST_INPUT.parse::<InputModel>().unwrap().moves;
-        let mut chamber = Chamber::new(&jets.clone());
+        let mut chamber = Chamber::new(&jets.clone()).unwrap();
         let actual = chamber.play_rounds(1);
         assert_eq!(actual, 1);
-        let mut chamber = Chamber::new(&jets.clone());
+        let mut chamber = Chamber::new(&jets.clone()).unwrap();
         let actual = chamber.play_rounds(2);
         assert_eq!(actual, 4);
 
-        let mut chamber = Chamber::new(&jets.clone());
+        let mut chamber = Chamber::new(&jets.clone()).unwrap();
         let actual = chamber.play_rounds(10);
         assert_eq!(actual, 17);
 
         // final test
-        let mut chamber = Chamber::new(&jets);
+        let mut chamber = Chamber::new(&jets).unwrap();
         let actual = chamber.play_rounds(2022);
         assert_eq!(actual, 3068);
     }
@@ -459,7 +543,7 @@ mod tests {
     #[test]
     fn find_height_large() {
         let jets = TEST_INPUT.parse::<InputModel>().unwrap().moves;
-        let mut chamber = Chamber::new(&jets.clone());
+        let mut chamber = Chamber::new(&jets.clone()).unwrap();
 
         let rounds = 1000000000000;
         let actual = chamber.play_rounds(rounds);
@@ -468,4 +552,81 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_parse_shape() {
+        let pieces = Shape::parse_set(STANDARD_PIECES).unwrap();
+
+        assert_eq!(pieces.len(), 5);
+        assert_eq!(pieces[PLUS].size(), (3, 3));
+        assert_eq!(pieces[HOOK].to_string(), "..#\n..#\n###\n");
+        assert_eq!(Shape::parse("...\n.#.\n.##").unwrap().to_string(), "#.\n##\n");
+        assert!(Shape::parse("..\n..").is_err());
+        assert!(Shape::parse("#x").is_err());
+    }
+
+    #[test]
+    fn test_frame() {
+        let jets = TEST_INPUT.parse::<InputModel>().unwrap().moves;
+        let mut chamber = Chamber::new(&jets).unwrap();
+        (0..3).for_each(|_| { chamber.drop_rock(); });
+        let falling = chamber.add_shape(POLE);
+
+        let expected = "\
+|..@....|
+|..@....|
+|..@....|
+|..@....|
+|.......|
+|.......|
+|.......|
+|..#....|
+|..#....|
+|####...|
+|..###..|
+|...#...|
+|..####.|
++-------+
+";
+        assert_eq!(chamber.frame(Some(&falling), 20), expected);
+        assert_eq!(chamber.frame(None, 2), "|..#....|\n|..#....|\n");
+        assert_eq!(chamber.to_string(), &expected[expected.find('#').unwrap() - 3..]);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let jets = TEST_INPUT.parse::<InputModel>().unwrap().moves;
+        let rules = Rules::new(5, 1, 2, "##\n\n#\n#\n#").unwrap();
+
+        // the height after each number of rocks, dropped one by one
+        let mut short = Chamber::with_rules(&jets, rules.clone()).unwrap();
+        let simulated = [0].into_iter()
+            .chain((0..2000).map(|_| { short.drop_rock(); short.max_height() as usize }))
+            .collect::<Vec<_>>();
+        for n in (1..=2000).step_by(7) {
+            let mut chamber = Chamber::with_rules(&jets, rules.clone()).unwrap();
+            assert_eq!(chamber.play_rounds(n), simulated[n], "after {n} rocks");
+        }
+
+        // the period the simulated tower settles into, which the horizon holds many times over
+        let growth = |n: usize, period: usize| simulated[n + period] - simulated[n];
+        let period = (1..250)
+            .find(|p| (1000..1500).all(|n| growth(n, *p) == growth(1000, *p)))
+            .unwrap();
+        assert!(period * 4 <= 500);
+        let rounds = 1000000000000;
+        let base = 1000 + (rounds - 1000) % period;
+        let expected = simulated[base] + (rounds - base) / period * growth(1000, period);
+        let mut chamber = Chamber::with_rules(&jets, rules.clone()).unwrap();
+        assert_eq!(chamber.play_rounds(rounds), expected);
+
+        assert!(matches!(Chamber::with_rules(&[], rules), Err(AocError::NoJets)));
+        assert!(Rules::new(3, 1, 3, STANDARD_PIECES).is_err());
+        assert!(Rules::new(0, 0, 3, "#").is_err());
+        assert!(Rules::new(2, -1, 3, "#").is_err());
+        let no_pieces = Rules { pieces: vec![], ..Rules::default() };
+        assert!(matches!(Chamber::with_rules(&jets, no_pieces), Err(AocError::InvalidRules(_))));
+        let too_wide = Rules { width: 3, ..Rules::default() };
+        assert!(matches!(Chamber::with_rules(&jets, too_wide), Err(AocError::InvalidRules(_))));
+    }
+
 }
@@ -6,14 +6,14 @@ const INPUT: &str = include_str!("../data/input.txt");
 
 fn part1(input: &InputModel) -> Result<String,AocError> {
     let jets = input.moves.clone();
-    let mut chamber = Chamber::new(&jets);
+    let mut chamber = Chamber::new(&jets)?;
     Ok(chamber.play_rounds(2022).to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
     let jets = input.moves.clone();
     let rounds = 1000000000000;
-    let mut chamber = Chamber::new(&jets);
+    let mut chamber = Chamber::new(&jets)?;
     Ok(chamber.play_rounds(rounds).to_string())
 }
 
@@ -1,6 +1,6 @@
-use std::str::FromStr;
-use std::ops::Add;
 use std::collections::HashSet;
+use std::str::FromStr;
+use std::ops::{Add, Sub};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel  {
@@ -11,8 +11,10 @@ pub struct InputModel  {
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("The lava at {0:?} is too close to the edge of the coordinates")]
+    OutOfRange(Pos),
 }
-        
+
 impl FromStr for InputModel {
     type Err = AocError;
 
@@ -25,16 +27,16 @@ impl FromStr for InputModel {
 }
 
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-struct Pos (i32, i32, i32);
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Pos (pub i32, pub i32, pub i32);
 
 impl FromStr for Pos {
     type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords: Vec<i32> = s.split(',')
-            .map(|p| p.parse::<i32>().unwrap())
-            .collect();
+        let coords = s.split(',')
+            .map(|p| p.trim().parse::<i32>().map_err(|_| AocError::ParseError))
+            .collect::<Result<Vec<i32>, AocError>>()?;
         if coords.len() == 3 {
             Ok(Pos(coords[0], coords[1], coords[2]))
         } else {
@@ -51,6 +53,14 @@ impl Add for Pos {
     }
 }
 
+impl Sub for Pos {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Pos(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Material {
     Air,
@@ -58,119 +68,233 @@ enum Material {
     Water,
 }
 
-pub struct Cube {
-    matrix: [[[Material; Cube::WIDTH as usize]; Cube::WIDTH as usize]; Cube::WIDTH as usize],
-    default: Material,
+const NEIGHBOURS: &[Pos] = &[
+    Pos(0, 0, -1), Pos(0, 0, 1),
+    Pos(0, -1, 0), Pos(0, 1, 0),
+    Pos(-1, 0, 0), Pos(1, 0, 0),
+];
+
+// a box of positions, both corners included
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Bounds {
+    min: Pos,
+    max: Pos,
 }
 
-impl Cube {
-    const WIDTH: i32 = 22;
+impl Bounds {
+    // the box around the cells with one cell to spare on every side, so water can flow all around
+    fn around(cells: &[Pos]) -> Bounds {
+        let (min, max) = cells.iter().fold((cells[0], cells[0]), |(min, max), p| (
+            Pos(min.0.min(p.0), min.1.min(p.1), min.2.min(p.2)),
+            Pos(max.0.max(p.0), max.1.max(p.1), max.2.max(p.2)),
+        ));
+        Bounds { min: min - Pos(1, 1, 1), max: max + Pos(1, 1, 1) }
+    }
 
-    fn new(material: Material) -> Self {
-        Cube {
-            matrix: [[[material; Cube::WIDTH as usize]; Cube::WIDTH as usize]; Cube::WIDTH as usize],
-            default: material,
-        }
+    fn contains(&self, pos: &Pos) -> bool {
+        (self.min.0..=self.max.0).contains(&pos.0)
+            && (self.min.1..=self.max.1).contains(&pos.1)
+            && (self.min.2..=self.max.2).contains(&pos.2)
     }
 
-    fn set_default(&mut self, material: Material) {
-        self.default = material;
+    fn overlaps(&self, other: &Bounds) -> bool {
+        self.min.0 <= other.max.0 && other.min.0 <= self.max.0
+            && self.min.1 <= other.max.1 && other.min.1 <= self.max.1
+            && self.min.2 <= other.max.2 && other.min.2 <= self.max.2
     }
 
-    fn get(&self, pos: &Pos) -> Material {
-        let Pos(x, y, z) = pos;
-        if *x < 0 || *x >= Cube::WIDTH || *y < 0 || *y >= Cube::WIDTH || *z < 0 || *z >= Cube::WIDTH {
-            self.default
-        } else {
-            self.matrix[*x as usize][*y as usize][*z as usize]
+    fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: Pos(self.min.0.min(other.min.0), self.min.1.min(other.min.1), self.min.2.min(other.min.2)),
+            max: Pos(self.max.0.max(other.max.0), self.max.1.max(other.max.1), self.max.2.max(other.max.2)),
         }
     }
 
-    fn set(&mut self, pos: &Pos, value: Material) {
-        let Pos(x, y, z) = pos;
-        if *x < 0 || *x >= Cube::WIDTH || *y < 0 || *y >= Cube::WIDTH || *z < 0 || *z >= Cube::WIDTH {
-            panic!("Out of bounds{pos:?}");
-        } else {
-            self.matrix[*x as usize][*y as usize][*z as usize] = value;
-        };
+    fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        (self.min.0..=self.max.0).flat_map(move |x| {
+            (self.min.1..=self.max.1)
+                .flat_map(move |y| (self.min.2..=self.max.2).map(move |z| Pos(x, y, z)))
+        })
     }
-    
 }
 
-const NEIGHBOURS: &[Pos] = &[
-    Pos(0, 0, -1), Pos(0, 0, 1),
-    Pos(0, -1, 0), Pos(0, 1, 0),
-    Pos(-1, 0, 0), Pos(1, 0, 0),
-];
-
-
-pub fn soak(cube: &mut Cube) {
-    cube.set_default(Material::Water);
-    // make a list of positions just outside the cube
-    let mut open: Vec<Pos> = (0..Cube::WIDTH)
-        .flat_map(|i| (0..Cube::WIDTH).map(move |j| (i, j)))
-        .flat_map(|(i, j)| vec![
-                  Pos(-1, i, j), Pos(Cube::WIDTH, i, j), 
-                  Pos(i, -1, j), Pos(i, Cube::WIDTH, j),
-                  Pos(i, j, -1), Pos(i, j, Cube::WIDTH), 
-        ])
-        .collect();
-    let seen: HashSet<Pos> = HashSet::from_iter(open.iter().cloned());
+// the cells reachable from the start through cells the filter accepts
+fn fill(start: &Pos, filter: &impl Fn(&Pos) -> bool, seen: &mut HashSet<Pos>) -> Vec<Pos> {
+    let mut region = vec![];
+    let mut open = vec![*start];
+    seen.insert(*start);
     while let Some(pos) = open.pop() {
+        region.push(pos);
         for neighbour in NEIGHBOURS {
-            let new_pos = pos + *neighbour;
-            if cube.get(&new_pos) == Material::Air {
-                cube.set(&new_pos, Material::Water);
-                if !seen.contains(&new_pos) {
-                    open.push(new_pos);
-                }
+            let next = pos + *neighbour;
+            if filter(&next) && seen.insert(next) {
+                open.push(next);
             }
         }
     }
+    region.sort();
+    region
+}
+
+// split the cells into face connected regions
+fn regions(cells: &HashSet<Pos>) -> Vec<Vec<Pos>> {
+    let mut starts = cells.iter().copied().collect::<Vec<_>>();
+    starts.sort();
+    let mut seen = HashSet::new();
+    starts.iter()
+        .filter_map(|start| {
+            if seen.contains(start) {
+                return None;
+            }
+            Some(fill(start, &|pos| cells.contains(pos), &mut seen))
+        })
+        .collect()
 }
 
-pub fn exposed_surface(input: &InputModel) -> i32 {
-    let mut matrix = Cube::new(Material::Air);
+// the lava and the air it traps, everything else is under water
+struct Scan {
+    lava: HashSet<Pos>,
+    pockets: HashSet<Pos>,
+}
 
-    for cube in &input.cubes {
-        matrix.set(cube, Material::Lava);
+impl Scan {
+    fn new(cubes: &[Pos]) -> Result<Scan, AocError> {
+        // the spare cells around the lava and their neighbours have to be positions as well
+        let near_edge = |c: i32| c.checked_sub(2).is_none() || c.checked_add(2).is_none();
+        if let Some(cube) = cubes.iter().find(|p| near_edge(p.0) || near_edge(p.1) || near_edge(p.2)) {
+            return Err(AocError::OutOfRange(*cube));
+        }
+        let mut scan = Scan {
+            lava: cubes.iter().copied().collect(),
+            pockets: HashSet::new(),
+        };
+        scan.pockets = scan.trapped_air();
+        Ok(scan)
     }
 
-    let mut covered_sides = 0;
-    for cube in &input.cubes {
-        for neighbour in NEIGHBOURS {
-            let pos: Pos = *cube + *neighbour;
-            if matrix.get(&pos) == Material::Lava {
-                covered_sides += 1;
+    fn get(&self, pos: &Pos) -> Material {
+        if self.lava.contains(pos) {
+            Material::Lava
+        } else if self.pockets.contains(pos) {
+            Material::Air
+        } else {
+            Material::Water
+        }
+    }
+
+    // boxes around the lava components, merged until none overlap, so all the lava around a
+    // pocket ends up in the same box however far apart the rest of the droplet is
+    fn enclosures(&self) -> Vec<Bounds> {
+        let mut boxes = regions(&self.lava)
+            .iter()
+            .map(|cells| Bounds::around(cells))
+            .collect::<Vec<_>>();
+        loop {
+            let mut merged: Vec<Bounds> = vec![];
+            for bounds in &boxes {
+                let mut bounds = *bounds;
+                merged.retain(|other| {
+                    let overlaps = other.overlaps(&bounds);
+                    if overlaps {
+                        bounds = bounds.union(other);
+                    }
+                    !overlaps
+                });
+                merged.push(bounds);
             }
+            if merged.len() == boxes.len() {
+                return merged;
+            }
+            boxes = merged;
         }
     }
 
-    let surface = (input.cubes.len() * 6) - covered_sides;
-    surface as i32
-}
+    // flood each box from its corner, which is outside the lava, the air left dry is trapped
+    fn trapped_air(&self) -> HashSet<Pos> {
+        self.enclosures()
+            .iter()
+            .flat_map(|bounds| {
+                let mut water = HashSet::new();
+                fill(&bounds.min, &|pos| bounds.contains(pos) && !self.lava.contains(pos), &mut water);
+                bounds.positions()
+                    .filter(|pos| !self.lava.contains(pos) && !water.contains(pos))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
 
+    // the sides of the cells facing the material
+    fn sides_facing(&self, cells: &[Pos], material: Material) -> usize {
+        cells.iter()
+            .flat_map(|cell| NEIGHBOURS.iter().map(move |n| *cell + *n))
+            .filter(|pos| self.get(pos) == material)
+            .count()
+    }
+}
 
-pub fn exposed_to_water(input: &InputModel) -> i32 {
-    let mut matrix = Cube::new(Material::Air);
+/// Air trapped inside the lava, out of reach of the water.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Pocket {
+    pub cells: Vec<Pos>,
+    // the lava sides around the pocket
+    pub surface: usize,
+}
 
-    for cube in &input.cubes {
-        matrix.set(cube, Material::Lava);
+impl Pocket {
+    pub fn volume(&self) -> usize {
+        self.cells.len()
     }
+}
 
-    soak(&mut matrix);
+/// A piece of the droplet whose cubes share faces.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Component {
+    pub cubes: Vec<Pos>,
+    // the sides not touching lava, and the ones of those the water reaches
+    pub surface: usize,
+    pub exterior: usize,
+}
 
-    let mut exposed_sides = 0;
-    for cube in &input.cubes {
-        for neighbour in NEIGHBOURS {
-            let pos: Pos = *cube + *neighbour;
-            if matrix.get(&pos) == Material::Water {
-                exposed_sides += 1;
-            }
-        }
+impl Component {
+    pub fn volume(&self) -> usize {
+        self.cubes.len()
     }
+}
+
+pub fn exposed_surface(input: &InputModel) -> Result<i32, AocError> {
+    let scan = Scan::new(&input.cubes)?;
+    Ok((scan.sides_facing(&input.cubes, Material::Air)
+        + scan.sides_facing(&input.cubes, Material::Water)) as i32)
+}
+
 
-    exposed_sides
+pub fn exposed_to_water(input: &InputModel) -> Result<i32, AocError> {
+    let scan = Scan::new(&input.cubes)?;
+    Ok(scan.sides_facing(&input.cubes, Material::Water) as i32)
+}
+
+pub fn pockets(input: &InputModel) -> Result<Vec<Pocket>, AocError> {
+    let scan = Scan::new(&input.cubes)?;
+    Ok(regions(&scan.pockets)
+        .into_iter()
+        .map(|cells| Pocket {
+            surface: scan.sides_facing(&cells, Material::Lava),
+            cells,
+        })
+        .collect())
+}
+
+pub fn components(input: &InputModel) -> Result<Vec<Component>, AocError> {
+    let scan = Scan::new(&input.cubes)?;
+    Ok(regions(&scan.lava)
+        .into_iter()
+        .map(|cubes| Component {
+            surface: scan.sides_facing(&cubes, Material::Air)
+                + scan.sides_facing(&cubes, Material::Water),
+            exterior: scan.sides_facing(&cubes, Material::Water),
+            cubes,
+        })
+        .collect())
 }
 
 pub const TEST_INPUT: &str = "2,2,2
@@ -222,7 +346,7 @@ mod tests {
     #[test]
     fn test_exposed_surface() {
         let input = input_data();
-        let actual = exposed_surface(&input);
+        let actual = exposed_surface(&input).unwrap();
         let expected = 64;
 
         assert_eq!(actual, expected);
@@ -231,9 +355,106 @@ mod tests {
     #[test]
     fn test_exposed_to_water() {
         let input = input_data();
-        let actual = exposed_to_water(&input);
+        let actual = exposed_to_water(&input).unwrap();
         let expected = 58;
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_pockets() {
+        let actual = pockets(&input_data()).unwrap();
+        let expected = vec![Pocket { cells: vec![Pos(2, 2, 5)], surface: 6 }];
+
+        assert_eq!(actual, expected);
+        assert_eq!(
+            actual.iter().map(|p| p.surface as i32).sum::<i32>(),
+            exposed_surface(&input_data()).unwrap() - exposed_to_water(&input_data()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_split_pockets() {
+        // a hollow 5x3x3 box with a wall in the middle
+        let cubes = (0..5)
+            .flat_map(|x| (0..3).flat_map(move |y| (0..3).map(move |z| Pos(x, y, z))))
+            .filter(|pos| !matches!(pos, Pos(1 | 3, 1, 1)))
+            .collect::<Vec<_>>();
+        let input = InputModel { cubes };
+
+        let actual = pockets(&input).unwrap();
+        assert_eq!(actual.len(), 2);
+        assert!(actual.iter().all(|pocket| pocket.volume() == 1 && pocket.surface == 6));
+        assert_eq!(exposed_to_water(&input).unwrap(), 2 * (15 + 15 + 9));
+        assert_eq!(components(&input).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_components() {
+        let mut actual = components(&input_data()).unwrap();
+        actual.sort_by_key(|component| component.volume());
+
+        assert_eq!(actual.len(), 6);
+        assert_eq!(actual[5].volume(), 8);
+        assert_eq!(actual[5].surface, 34);
+        assert_eq!(actual.iter().map(|c| c.surface).sum::<usize>(), 64);
+        assert_eq!(actual.iter().map(|c| c.exterior).sum::<usize>(), 58);
+    }
+
+    #[test]
+    fn test_far_coordinates() {
+        let offset = Pos(-1_000_000, 50_000_000, -3);
+        let input = InputModel { cubes: input_data().cubes.iter().map(|c| *c + offset).collect() };
+
+        assert_eq!(exposed_surface(&input).unwrap(), 64);
+        assert_eq!(exposed_to_water(&input).unwrap(), 58);
+        assert_eq!(pockets(&input).unwrap()[0].cells, vec![Pos(2, 2, 5) + offset]);
+        assert_eq!(exposed_surface(&InputModel { cubes: vec![] }).unwrap(), 0);
+        assert!("1,2".parse::<InputModel>().is_err());
+        assert!("1,x,2".parse::<InputModel>().is_err());
+    }
+
+    #[test]
+    fn test_spread_out_lava() {
+        let apart = "0,0,0\n2000,2000,2000".parse::<InputModel>().unwrap();
+        assert_eq!(exposed_surface(&apart).unwrap(), 12);
+        assert_eq!(exposed_to_water(&apart).unwrap(), 12);
+        assert!(pockets(&apart).unwrap().is_empty());
+        assert_eq!(components(&apart).unwrap().len(), 2);
+
+        // hollow droplets far apart keep a pocket each
+        let shell = |offset: Pos| {
+            (0..3)
+                .flat_map(|x| (0..3).flat_map(move |y| (0..3).map(move |z| Pos(x, y, z))))
+                .filter(|pos| *pos != Pos(1, 1, 1))
+                .map(move |pos| pos + offset)
+        };
+        let far = Pos(1_000_000_000, -1_000_000_000, 2_000_000_000);
+        let hollow = InputModel { cubes: shell(Pos(0, 0, 0)).chain(shell(far)).collect() };
+        let actual = pockets(&hollow).unwrap();
+        assert_eq!(
+            actual,
+            vec![
+                Pocket { cells: vec![Pos(1, 1, 1)], surface: 6 },
+                Pocket { cells: vec![Pos(1, 1, 1) + far], surface: 6 },
+            ]
+        );
+        assert_eq!(exposed_to_water(&hollow).unwrap(), 2 * 54);
+
+        let edge = InputModel { cubes: vec![Pos(i32::MAX - 2, 0, 0), Pos(i32::MIN + 2, 0, 0)] };
+        assert_eq!(exposed_to_water(&edge).unwrap(), 12);
+        let edge = InputModel { cubes: vec![Pos(i32::MAX, 0, 0)] };
+        assert!(matches!(exposed_to_water(&edge), Err(AocError::OutOfRange(Pos(i32::MAX, 0, 0)))));
+    }
+
+    #[test]
+    fn test_pocket_between_components() {
+        // six cubes touching only at their edges still close in the cell between them
+        let input = InputModel { cubes: NEIGHBOURS.to_vec() };
+
+        assert_eq!(components(&input).unwrap().len(), 6);
+        assert_eq!(pockets(&input).unwrap(), vec![Pocket { cells: vec![Pos(0, 0, 0)], surface: 6 }]);
+        assert_eq!(exposed_surface(&input).unwrap(), 36);
+        assert_eq!(exposed_to_water(&input).unwrap(), 30);
+    }
 }
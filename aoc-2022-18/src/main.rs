@@ -5,11 +5,11 @@ const INPUT: &str = include_str!("../data/input.txt");
 
 
 fn part1(input: &InputModel) -> Result<String,AocError> {
-   Ok(exposed_surface(input).to_string())
+   Ok(exposed_surface(input)?.to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
-   Ok(exposed_to_water(input).to_string())
+   Ok(exposed_to_water(input)?.to_string())
 }
 
 fn main() -> Result<(), AocError> {
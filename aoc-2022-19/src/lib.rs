@@ -1,3 +1,4 @@
+use aoc_common::interner::{Interner, Symbol};
 use nom::{
     bytes::complete::tag,
     character::complete::{alpha1, digit1, line_ending},
@@ -7,8 +8,9 @@ use nom::{
 };
 use std::{
     collections::HashMap,
+    fmt::Write,
     ops::{Add, Index, IndexMut, Sub},
-    str::FromStr, cmp::Ordering,
+    str::FromStr,
 };
 
 #[derive(Debug, PartialEq, Eq)]
@@ -20,6 +22,8 @@ pub struct InputModel {
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("Blueprint {0} knows no resource {1}")]
+    UnknownResource(u32, String),
 }
 
 impl FromStr for InputModel {
@@ -33,89 +37,58 @@ impl FromStr for InputModel {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub enum Resource {
-    Ore,
-    Clay,
-    Obsidian,
-    Geode,
-}
-
-impl Resource {
-    const ALL: [Resource; 4] = [
-        Resource::Geode,
-        Resource::Obsidian,
-        Resource::Clay,
-        Resource::Ore,
-    ];
-}
+// resources are whatever the blueprint mentions, named by its interner
+pub type Resource = Symbol;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd, Hash)]
-pub struct Inventory {
-    geode: u32,
-    obsidian: u32,
-    clay: u32,
-    ore: u32,
-}
+/// Amount of every resource, indexed by the resource symbols of a blueprint.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default)]
+pub struct Inventory(Vec<u32>);
 
-impl Default for Inventory {
-    fn default() -> Self {
-        Self::new()
+impl Inventory {
+    pub fn new(resources: usize) -> Self {
+        Inventory(vec![0; resources])
     }
-}
 
-impl Inventory {
-    pub fn new() -> Self {
-        Inventory {
-            ore: 0,
-            clay: 0,
-            obsidian: 0,
-            geode: 0,
-        }
+    pub fn len(&self) -> usize {
+        self.0.len()
     }
 
-    fn parser(input: &str) -> IResult<&str, Self> {
-        separated_list1(
-            tag(" and "),
-            separated_pair(
-                digit1.map(|s: &str| s.parse::<u32>().unwrap()),
-                tag(" "),
-                alpha1.map(|s: &str| s.parse::<Resource>().unwrap()),
-            ),
-        )
-        .map(|resources| {
-            let mut result = Inventory::new();
-            resources.iter().for_each(|(quantity, resource)| {
-                result[*resource] = *quantity;
-            });
-            result
-        })
-        .parse(input)
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
 
     fn add_resource(&mut self, index: Resource, value: u32) {
-        match index {
-            Resource::Ore => self.ore += value,
-            Resource::Clay => self.clay += value,
-            Resource::Obsidian => self.obsidian += value,
-            Resource::Geode => self.geode += value,
-        }
+        self[index] += value;
+    }
+
+    fn has_all_resources(&self, other: &Inventory) -> bool {
+        other.0.iter()
+            .enumerate()
+            .all(|(i, amount)| self.0.get(i).copied().unwrap_or(0) >= *amount)
+    }
+
+    // resources which are present, as `(resource, amount)`
+    fn iter(&self) -> impl Iterator<Item = (usize, u32)> + '_ {
+        self.0.iter().copied().enumerate().filter(|(_, amount)| *amount > 0)
     }
 
-    fn remove_resource(&mut self, index: Resource, value: u32) {
-        match index {
-            Resource::Ore => self.ore -= value,
-            Resource::Clay => self.clay -= value,
-            Resource::Obsidian => self.obsidian -= value,
-            Resource::Geode => self.geode -= value,
+    fn describe(&self, names: &Interner) -> String {
+        let parts = self.0.iter()
+            .zip(names.iter())
+            .filter(|(amount, _)| **amount > 0)
+            .map(|(amount, (_, name))| format!("{} {}", amount, name))
+            .collect::<Vec<_>>();
+        if parts.is_empty() {
+            "nothing".to_string()
+        } else {
+            parts.join(", ")
         }
     }
+}
 
-    fn has_all_resources(&self, other: &Inventory) -> bool {
-        self.ore >= other.ore
-            && self.clay >= other.clay
-            && self.obsidian >= other.obsidian
-            && self.geode >= other.geode
+impl<const N: usize> From<[u32; N]> for Inventory {
+    fn from(amounts: [u32; N]) -> Self {
+        Inventory(amounts.to_vec())
     }
 }
 
@@ -123,73 +96,55 @@ impl Index<Resource> for Inventory {
     type Output = u32;
 
     fn index(&self, index: Resource) -> &Self::Output {
-        match index {
-            Resource::Ore => &self.ore,
-            Resource::Clay => &self.clay,
-            Resource::Obsidian => &self.obsidian,
-            Resource::Geode => &self.geode,
-        }
+        self.0.get(index.index()).unwrap_or(&0)
     }
 }
 
 impl IndexMut<Resource> for Inventory {
     fn index_mut(&mut self, index: Resource) -> &mut Self::Output {
-        match index {
-            Resource::Ore => &mut self.ore,
-            Resource::Clay => &mut self.clay,
-            Resource::Obsidian => &mut self.obsidian,
-            Resource::Geode => &mut self.geode,
+        if index.index() >= self.0.len() {
+            self.0.resize(index.index() + 1, 0);
         }
+        &mut self.0[index.index()]
     }
 }
 
-impl Add for Inventory {
-    type Output = Self;
+impl Add for &Inventory {
+    type Output = Inventory;
 
     fn add(self, rhs: Self) -> Self::Output {
-        let mut result = self;
-        for resource in Resource::ALL.iter() {
-            result[*resource] += rhs[*resource];
+        let mut result = self.clone();
+        result.0.resize(self.len().max(rhs.len()), 0);
+        for (amount, other) in result.0.iter_mut().zip(rhs.0.iter()) {
+            *amount += other;
         }
         result
     }
 }
 
-impl Sub for Inventory {
-    type Output = Self;
+impl Sub for &Inventory {
+    type Output = Inventory;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        let mut result = self;
-        for resource in Resource::ALL.iter() {
-            result.remove_resource(*resource, rhs[*resource]);
+        let mut result = self.clone();
+        result.0.resize(self.len().max(rhs.len()), 0);
+        for (amount, other) in result.0.iter_mut().zip(rhs.0.iter()) {
+            *amount -= other;
         }
         result
     }
 }
 
-impl FromStr for Resource {
-    type Err = AocError;
-
-    fn from_str(s: &str) -> Result<Self, AocError> {
-        match s {
-            "ore" => Ok(Resource::Ore),
-            "clay" => Ok(Resource::Clay),
-            "obsidian" => Ok(Resource::Obsidian),
-            "geode" => Ok(Resource::Geode),
-            _ => Err(AocError::ParseError),
-        }
-    }
-}
-
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct BluePrint {
     pub id: u32,
+    pub resources: Interner,
     pub recipes: HashMap<Resource, Inventory>,
 }
 
 impl BluePrint {
-    pub fn new(id: u32, recipes: HashMap<Resource, Inventory>) -> Self {
-        Self { id, recipes }
+    pub fn new(id: u32, resources: Interner, recipes: HashMap<Resource, Inventory>) -> Self {
+        Self { id, resources, recipes }
     }
 
     pub fn parser(input: &str) -> IResult<&str, Self> {
@@ -197,152 +152,286 @@ impl BluePrint {
             tag("Blueprint "),
             digit1.map(|s: &str| s.parse::<u32>().unwrap()),
             tag(": "),
-            separated_list1(tag(" "), recipe_parser)
-                .map(|v| v.into_iter().collect::<HashMap<_, _>>()),
+            separated_list1(tag(" "), recipe_parser),
         ))
-        .map(|(_, id, _, recipes)| BluePrint::new(id, recipes))
+        .map(|(_, id, _, recipes)| {
+            // robots first so the resources are numbered the way the blueprint lists them
+            let mut resources = Interner::new();
+            for (robot, _) in &recipes {
+                resources.intern(robot);
+            }
+            for (_, ingredients) in &recipes {
+                for (_, name) in ingredients {
+                    resources.intern(name);
+                }
+            }
+            let recipes = recipes.iter()
+                .map(|(robot, ingredients)| {
+                    let mut inventory = Inventory::new(resources.len());
+                    for (amount, name) in ingredients {
+                        inventory[resources.intern(name)] = *amount;
+                    }
+                    (resources.intern(robot), inventory)
+                })
+                .collect();
+            BluePrint::new(id, resources, recipes)
+        })
         .parse(input)
     }
 
-    pub fn best_production(&self, period: usize) -> Factory {
-        let mut start = Factory::new();
-        start.robots.add_resource(Resource::Ore, 1);
-
-        let mut open = Vec::with_capacity(10000);
-        open.push(start);
-        let mut best = start;
-
-        // keep a list of maximums inputs for each recipe per resource
-        let max_needed : HashMap<Resource, u32> = Resource::ALL.iter()
-            .map(|r| (*r, self.recipes.values()
-                      .map(|v| v[*r])
-                      .max().unwrap_or(0)))
-            .collect();
-
-        for _ in 0..period {
-            let mut new_open = Vec::with_capacity(10000);
-            while let Some(factory) = open.pop() {
-                // check if we can produce a robot
-                let can_make: Vec<Resource> = self.recipes.iter()
-                    .filter(|(_, inventory)| factory.resources.has_all_resources(inventory))
-                    .map(|(resource, _)| *resource)
-                    .collect();
-                for resource in can_make.iter() {
-                    // no need for more robots than to produce the most needed 
-                    if max_needed[resource] <= factory.robots[*resource] && *resource != Resource::Geode {
-                        continue;
-                    }
-                    let inventory = self.recipes.get(resource).unwrap();
-                    if factory.resources.has_all_resources(inventory) {
-                        let factory = factory.produce_robot(resource, inventory);
-                        new_open.push(factory);
-
-                        // build geode robot if we can
-                        if resource == &Resource::Geode {
-                            break;
-                        }
-                    } 
-                }
-
-                // do not pile resources if we can build can_build_anything
-                if can_make.len() < 4 {
-                    new_open.push(factory.produce_nothing());
-                }
+    pub fn resource(&self, name: &str) -> Result<Resource, AocError> {
+        self.resources.get(name)
+            .ok_or_else(|| AocError::UnknownResource(self.id, name.to_string()))
+    }
 
+    // the puzzle starts with a single ore robot
+    pub fn start(&self) -> Result<Factory, AocError> {
+        let mut robots = Inventory::new(self.resources.len());
+        robots.add_resource(self.resource("ore")?, 1);
+        Ok(Factory::new(robots))
+    }
 
+    /// The plan collecting the most of `goal` within the minutes.
+    ///
+    /// A depth first search over which robot to build next, waiting as long as needed to
+    /// afford it, cut off when even a new goal robot every remaining minute cannot beat the
+    /// best plan found so far.
+    pub fn best_plan(&self, start: &Factory, goal: Resource, minutes: u32) -> Plan {
+        // building more robots than any recipe can spend in a minute does not help
+        let mut max_needed = Inventory::new(self.resources.len());
+        for ingredients in self.recipes.values() {
+            for (i, amount) in ingredients.iter() {
+                max_needed.0[i] = max_needed.0[i].max(amount);
             }
+        }
+        // try the goal robot first, then the robots closest to it
+        let mut robots = self.recipes.keys().copied().collect::<Vec<_>>();
+        robots.sort_by_key(|&robot| (robot != goal, std::cmp::Reverse(robot)));
+
+        let mut search = Search {
+            blueprint: self,
+            goal,
+            minutes,
+            max_needed,
+            robots,
+            path: vec![],
+            best: Plan {
+                goal,
+                minutes,
+                builds: vec![],
+                factory: start.idle(minutes),
+            },
+        };
+        search.explore(start, 0);
+        search.best
+    }
 
-            //println!("{}: {} factories", time, new_open.len());
+    pub fn best_production(&self, period: u32) -> Result<Plan, AocError> {
+        Ok(self.best_plan(&self.start()?, self.resource("geode")?, period))
+    }
 
-            new_open.sort();
-            open = last_n(new_open, 3000);
-            
-            if let Some(factory) = open.last() {
-                if factory.resources.geode > best.resources.geode {
-                    best = *factory;
-                    //println!("New best {}: {:?}", time, best);
-                }
-            }
+    pub fn quality(&self) -> Result<u32, AocError> {
+        Ok(self.max_geodes(24)? * self.id)
+    }
+
+    pub fn max_geodes(&self, rounds: u32) -> Result<u32, AocError> {
+        Ok(self.best_production(rounds)?.amount())
+    }
+}
+
+struct Search<'a> {
+    blueprint: &'a BluePrint,
+    goal: Resource,
+    minutes: u32,
+    max_needed: Inventory,
+    robots: Vec<Resource>,
+    path: Vec<Build>,
+    best: Plan,
+}
+
+impl Search<'_> {
+    fn explore(&mut self, factory: &Factory, minute: u32) {
+        let remaining = self.minutes - minute;
+        let idle = factory.idle(remaining);
+        if idle.resources[self.goal] > self.best.amount() {
+            self.best.builds = self.path.clone();
+            self.best.factory = idle;
         }
 
-        println!("Best result for blueprint {} : {:?}", self.id,  best.resources.geode);
-        best
+        // a goal robot finished every remaining minute is the best that can happen
+        let bound = factory.resources[self.goal]
+            + factory.robots[self.goal] * remaining
+            + remaining * remaining.saturating_sub(1) / 2;
+        if bound <= self.best.amount() {
+            return;
+        }
+
+        for i in 0..self.robots.len() {
+            let robot = self.robots[i];
+            // nor stocking up more than can be spent before the end
+            let needed = self.max_needed[robot];
+            if robot != self.goal
+                && (factory.robots[robot] >= needed || factory.resources[robot] >= needed * remaining)
+            {
+                continue;
+            }
+            let ingredients = &self.blueprint.recipes[&robot];
+            let Some(wait) = factory.time_to_afford(ingredients) else {
+                continue;
+            };
+            // a robot finished in the last minute collects nothing
+            if wait + 1 >= remaining {
+                continue;
+            }
+            let next = factory.idle(wait).produce_robot(robot, ingredients);
+            self.path.push(Build { minute: minute + wait + 1, robot });
+            self.explore(&next, minute + wait + 1);
+            self.path.pop();
+        }
     }
+}
+
+/// A robot the factory starts building during `minute`, it is ready at the end of it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Build {
+    pub minute: u32,
+    pub robot: Resource,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Plan {
+    pub goal: Resource,
+    pub minutes: u32,
+    pub builds: Vec<Build>,
+    pub factory: Factory,
+}
 
-    pub fn quality(&self) -> u32 {
-        self.max_geodes(24) * self.id
+impl Plan {
+    pub fn amount(&self) -> u32 {
+        self.factory.resources[self.goal]
     }
 
-    pub fn max_geodes(&self, rounds: usize) -> u32 {
-        self.best_production(rounds).resources.geode
+    // the factory at the end of every minute
+    pub fn replay(&self, blueprint: &BluePrint, start: &Factory) -> Vec<Factory> {
+        let mut factory = start.clone();
+        (1..=self.minutes)
+            .map(|minute| {
+                factory = match self.builds.iter().find(|b| b.minute == minute) {
+                    Some(build) => factory.produce_robot(build.robot, &blueprint.recipes[&build.robot]),
+                    None => factory.produce_nothing(),
+                };
+                factory.clone()
+            })
+            .collect()
     }
-}
 
-fn last_n<T: Clone>(v: Vec<T>, n: usize) -> Vec<T> {
-    let l = v.len();
-    if l > n {
-        (v[l - n..]).to_vec()
-    } else {
-        v
+    /// One line per minute with the robot built and the robots and resources afterwards.
+    pub fn explain(&self, blueprint: &BluePrint, start: &Factory) -> String {
+        let names = &blueprint.resources;
+        let mut text = String::new();
+        for (minute, factory) in (1..).zip(self.replay(blueprint, start)) {
+            let build = match self.builds.iter().find(|b| b.minute == minute) {
+                Some(build) => format!(
+                    "build {} robot for {}",
+                    names.display(build.robot),
+                    blueprint.recipes[&build.robot].describe(names)
+                ),
+                None => "wait".to_string(),
+            };
+            let _ = writeln!(
+                text,
+                "minute {:2}: {}; robots {}; stock {}",
+                minute,
+                build,
+                factory.robots.describe(names),
+                factory.resources.describe(names)
+            );
+        }
+        text
     }
 }
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct Factory {
     robots: Inventory,
     resources: Inventory,
 }
 
 impl Factory {
-    fn new() -> Self {
+    pub fn new(robots: Inventory) -> Self {
         Self {
-            resources: Inventory::new(),
-            robots: Inventory::new(),
+            resources: Inventory::new(robots.len()),
+            robots,
         }
     }
 
-    fn produce_robot(&self, resource: &Resource, ingredients: &Inventory) -> Self {
-        let mut result = *self;
-        result.resources = result.resources + result.robots - *ingredients;
-        result.robots[*resource] += 1;
-        result
+    pub fn robots(&self) -> &Inventory {
+        &self.robots
     }
 
-    fn produce_nothing(&self) -> Self {
-        let mut result = *self;
-        result.resources = result.resources + result.robots;
+    pub fn resources(&self) -> &Inventory {
+        &self.resources
+    }
+
+    fn produce_robot(&self, resource: Resource, ingredients: &Inventory) -> Self {
+        let mut result = self.clone();
+        result.resources = &(&result.resources + &result.robots) - ingredients;
+        result.robots[resource] += 1;
         result
     }
 
-}
+    fn produce_nothing(&self) -> Self {
+        self.idle(1)
+    }
 
-impl PartialOrd for Factory {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    // collect for a number of minutes without building
+    fn idle(&self, minutes: u32) -> Self {
+        let mut result = self.clone();
+        result.resources.0.resize(self.robots.len().max(self.resources.len()), 0);
+        for (amount, robots) in result.resources.0.iter_mut().zip(self.robots.0.iter()) {
+            *amount += robots * minutes;
+        }
+        result
     }
-}
 
-impl Ord for Factory {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.resources.geode.cmp(&other.resources.geode)
-        .then(self.robots.geode.cmp(&other.robots.geode))
-        .then(self.resources.obsidian.cmp(&other.resources.obsidian))
-        .then(self.robots.obsidian.cmp(&other.robots.obsidian))
-        .then(self.resources.clay.cmp(&other.resources.clay))
-        .then(self.robots.clay.cmp(&other.robots.clay))
-        .then(self.resources.ore.cmp(&other.resources.ore))
-        .then(self.robots.ore.cmp(&other.robots.ore))
+    // minutes to wait before the ingredients can be paid for, `None` when they never can
+    fn time_to_afford(&self, ingredients: &Inventory) -> Option<u32> {
+        if self.resources.has_all_resources(ingredients) {
+            return Some(0);
+        }
+        ingredients.iter()
+            .map(|(i, needed)| {
+                let have = self.resources.0.get(i).copied().unwrap_or(0);
+                let robots = self.robots.0.get(i).copied().unwrap_or(0);
+                match (have >= needed, robots) {
+                    (true, _) => Some(0),
+                    (false, 0) => None,
+                    (false, robots) => Some((needed - have).div_ceil(robots)),
+                }
+            })
+            .try_fold(0, |wait, w| w.map(|w| wait.max(w)))
     }
 }
 
-fn recipe_parser(input: &str) -> IResult<&str, (Resource, Inventory)> {
+// robot and its ingredients, before the names are interned
+type Recipe = (String, Vec<(u32, String)>);
+
+fn recipe_parser(input: &str) -> IResult<&str, Recipe> {
     tuple((
         tag("Each "),
-        alpha1.map(|s: &str| s.parse::<Resource>().unwrap()),
+        alpha1,
         tag(" robot costs "),
-        Inventory::parser,
+        separated_list1(
+            tag(" and "),
+            separated_pair(
+                digit1.map(|s: &str| s.parse::<u32>().unwrap()),
+                tag(" "),
+                alpha1.map(|s: &str| s.to_string()),
+            ),
+        ),
         tag("."),
     ))
-    .map(|(_, target, _, ingredients, _)| (target, ingredients))
+    .map(|(_, target, _, ingredients, _): (_, &str, _, _, _)| (target.to_string(), ingredients))
     .parse(input)
 }
 
@@ -357,48 +446,39 @@ pub fn test_input() -> InputModel {
 mod tests {
     use super::*;
 
+    fn inventory(ore: u32, clay: u32, obsidian: u32, geode: u32) -> Inventory {
+        Inventory::from([ore, clay, obsidian, geode])
+    }
+
+    fn resources(blueprint: &BluePrint) -> [Resource; 4] {
+        ["ore", "clay", "obsidian", "geode"].map(|name| blueprint.resource(name).unwrap())
+    }
+
     #[test]
     fn test_parse_blueprint() {
         let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.";
         let (rest, actual) = super::BluePrint::parser(input).unwrap();
+        let names = ["ore", "clay", "obsidian", "geode"].into_iter().collect::<Interner>();
+        let [ore, clay, obsidian, geode] = ["ore", "clay", "obsidian", "geode"].map(|n| names.get(n).unwrap());
         let expected = BluePrint::new(
             1,
+            names.clone(),
             vec![
                 (
-                    Resource::Ore,
-                    Inventory {
-                        ore: 4,
-                        clay: 0,
-                        obsidian: 0,
-                        geode: 0,
-                    },
+                    ore,
+                    inventory(4, 0, 0, 0),
                 ),
                 (
-                    Resource::Clay,
-                    Inventory {
-                        ore: 2,
-                        clay: 0,
-                        obsidian: 0,
-                        geode: 0,
-                    },
+                    clay,
+                    inventory(2, 0, 0, 0),
                 ),
                 (
-                    Resource::Obsidian,
-                    Inventory {
-                        ore: 3,
-                        clay: 14,
-                        obsidian: 0,
-                        geode: 0,
-                    },
+                    obsidian,
+                    inventory(3, 14, 0, 0),
                 ),
                 (
-                    Resource::Geode,
-                    Inventory {
-                        ore: 2,
-                        clay: 0,
-                        obsidian: 7,
-                        geode: 0,
-                    },
+                    geode,
+                    inventory(2, 0, 7, 0),
                 ),
             ]
             .into_iter()
@@ -417,219 +497,140 @@ mod tests {
 
     #[test]
     fn test_quality() {
-        let actual = test_input().blueprints[0].quality();
+        let actual = test_input().blueprints[0].quality().unwrap();
         assert_eq!(actual, 9);
-        let actual = test_input().blueprints[1].quality();
+        let actual = test_input().blueprints[1].quality().unwrap();
         assert_eq!(actual, 24);
     }
 
     #[test]
     fn test_factory_production() {
         let blueprint = test_input().blueprints[0].clone();
-        let mut factory = Factory::new();
-        factory.robots.add_resource(Resource::Ore, 1);
+        let [ore, clay, obsidian, geode] = resources(&blueprint);
+        let factory = blueprint.start().unwrap();
+        assert_eq!(factory.robots[ore], 1);
         let factory = factory.produce_nothing();
         assert_eq!(
             factory.resources,
-            Inventory {
-                ore: 1,
-                clay: 0,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 0, 0, 0)
         );
         assert_eq!(
             factory.robots,
-            Inventory {
-                ore: 1,
-                clay: 0,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 0, 0, 0)
         );
 
         assert!(!factory
             .resources
-            .has_all_resources(&blueprint.recipes[&Resource::Clay]));
+            .has_all_resources(&blueprint.recipes[&clay]));
 
         let factory = factory.produce_nothing();
         assert_eq!(
             factory.resources,
-            Inventory {
-                ore: 2,
-                clay: 0,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(2, 0, 0, 0)
         );
         assert_eq!(
             factory.robots,
-            Inventory {
-                ore: 1,
-                clay: 0,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 0, 0, 0)
         );
 
         assert!(factory
             .resources
-            .has_all_resources(&blueprint.recipes[&Resource::Clay]));
+            .has_all_resources(&blueprint.recipes[&clay]));
 
-        let factory = factory.produce_robot(&Resource::Clay, &blueprint.recipes[&Resource::Clay]);
+        let factory = factory.produce_robot(clay, &blueprint.recipes[&clay]);
         assert_eq!(
             factory.resources,
-            Inventory {
-                ore: 1,
-                clay: 0,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 0, 0, 0)
         );
         assert_eq!(
             factory.robots,
-            Inventory {
-                ore: 1,
-                clay: 1,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 1, 0, 0)
         );
 
         let factory = factory.produce_nothing();
-        let factory = factory.produce_robot(&Resource::Clay, &blueprint.recipes[&Resource::Clay]);
+        let factory = factory.produce_robot(clay, &blueprint.recipes[&clay]);
         assert_eq!(
             factory.resources,
-            Inventory {
-                ore: 1,
-                clay: 2,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 2, 0, 0)
         );
         assert_eq!(
             factory.robots,
-            Inventory {
-                ore: 1,
-                clay: 2,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 2, 0, 0)
         );
 
         let factory = factory.produce_nothing();
-        let factory = factory.produce_robot(&Resource::Clay, &blueprint.recipes[&Resource::Clay]);
+        let factory = factory.produce_robot(clay, &blueprint.recipes[&clay]);
         // minute 7
         assert_eq!(
             factory.resources,
-            Inventory {
-                ore: 1,
-                clay: 6,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 6, 0, 0)
         );
         assert_eq!(
             factory.robots,
-            Inventory {
-                ore: 1,
-                clay: 3,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(1, 3, 0, 0)
         );
 
         let factory = factory.produce_nothing();
         let factory = factory.produce_nothing();
         assert!(!factory
             .resources
-            .has_all_resources(&blueprint.recipes[&Resource::Obsidian]));
+            .has_all_resources(&blueprint.recipes[&obsidian]));
         let factory = factory.produce_nothing();
         assert!(factory
             .resources
-            .has_all_resources(&blueprint.recipes[&Resource::Obsidian]));
+            .has_all_resources(&blueprint.recipes[&obsidian]));
         let factory =
-            factory.produce_robot(&Resource::Obsidian, &blueprint.recipes[&Resource::Obsidian]);
+            factory.produce_robot(obsidian, &blueprint.recipes[&obsidian]);
         // minute 11
         assert_eq!(
             factory.resources,
-            Inventory {
-                ore: 2,
-                clay: 4,
-                obsidian: 0,
-                geode: 0
-            }
+            inventory(2, 4, 0, 0)
         );
         assert_eq!(
             factory.robots,
-            Inventory {
-                ore: 1,
-                clay: 3,
-                obsidian: 1,
-                geode: 0
-            }
+            inventory(1, 3, 1, 0)
         );
 
-        let factory = factory.produce_robot(&Resource::Clay, &blueprint.recipes[&Resource::Clay]);
+        let factory = factory.produce_robot(clay, &blueprint.recipes[&clay]);
         let factory = factory.produce_nothing();
         let factory = factory.produce_nothing();
         let factory =
-            factory.produce_robot(&Resource::Obsidian, &blueprint.recipes[&Resource::Obsidian]);
+            factory.produce_robot(obsidian, &blueprint.recipes[&obsidian]);
         // minute 15
 
         let factory = factory.produce_nothing();
         assert!(!factory
             .resources
-            .has_all_resources(&blueprint.recipes[&Resource::Geode]));
+            .has_all_resources(&blueprint.recipes[&geode]));
         let factory = factory.produce_nothing();
         assert!(factory
             .resources
-            .has_all_resources(&blueprint.recipes[&Resource::Geode]));
-        let factory = factory.produce_robot(&Resource::Geode, &blueprint.recipes[&Resource::Geode]);
+            .has_all_resources(&blueprint.recipes[&geode]));
+        let factory = factory.produce_robot(geode, &blueprint.recipes[&geode]);
         // minute 18
         assert_eq!(
             factory.resources,
-            Inventory {
-                ore: 2,
-                clay: 17,
-                obsidian: 3,
-                geode: 0
-            }
+            inventory(2, 17, 3, 0)
         );
         assert_eq!(
             factory.robots,
-            Inventory {
-                ore: 1,
-                clay: 4,
-                obsidian: 2,
-                geode: 1
-            }
+            inventory(1, 4, 2, 1)
         );
 
         let factory = factory.produce_nothing();
         let factory = factory.produce_nothing();
-        let factory = factory.produce_robot(&Resource::Geode, &blueprint.recipes[&Resource::Geode]);
+        let factory = factory.produce_robot(geode, &blueprint.recipes[&geode]);
         let factory = factory.produce_nothing();
         let factory = factory.produce_nothing();
         let factory = factory.produce_nothing();
         // minute 24
         assert_eq!(
             factory.resources,
-            Inventory {
-                ore: 6,
-                clay: 41,
-                obsidian: 8,
-                geode: 9
-            }
+            inventory(6, 41, 8, 9)
         );
         assert_eq!(
             factory.robots,
-            Inventory {
-                ore: 1,
-                clay: 4,
-                obsidian: 2,
-                geode: 2
-            }
+            inventory(1, 4, 2, 2)
         );
     }
 
@@ -638,7 +639,7 @@ mod tests {
 
         let blueprint = test_input().blueprints;
 
-        let actual = blueprint[0].max_geodes(24);
+        let actual = blueprint[0].max_geodes(24).unwrap();
         println!("actual max geode : {actual:?}");
         assert_eq!(actual, 9);
     }
@@ -648,10 +649,48 @@ mod tests {
 
         let blueprint = test_input().blueprints;
 
-        let actual = blueprint[1].max_geodes(24);
+        let actual = blueprint[1].max_geodes(24).unwrap();
         println!("actual max geode : {actual:?}");
         assert_eq!(actual, 12);
     }
 
+    #[test]
+    fn test_best_plan() {
+        let blueprint = &test_input().blueprints[0];
+        let [_, clay, obsidian, geode] = resources(blueprint);
+        let start = blueprint.start().unwrap();
+        let plan = blueprint.best_production(24).unwrap();
+        let replay = plan.replay(blueprint, &start);
+
+        assert_eq!(plan.amount(), 9);
+        assert_eq!(replay.last(), Some(&plan.factory));
+        for robot in [clay, obsidian, geode] {
+            let built = plan.builds.iter().filter(|b| b.robot == robot).count() as u32;
+            assert_eq!(plan.factory.robots()[robot], built);
+        }
 
+        let explained = plan.explain(blueprint, &start);
+        assert_eq!(explained.lines().count(), 24);
+        assert_eq!(explained.lines().next(), Some("minute  1: wait; robots 1 ore; stock 1 ore"));
+        assert!(explained.contains("minute 11: build obsidian robot for 3 ore, 14 clay; robots 1 ore, 3 clay, 1 obsidian; stock 2 ore, 4 clay\n"));
+        assert!(explained.ends_with("stock 6 ore, 41 clay, 8 obsidian, 9 geode\n"));
+    }
+
+    #[test]
+    fn test_other_resources() {
+        let input = "Blueprint 7: Each sand robot costs 1 sand. Each glass robot costs 3 sand. Each lens robot costs 2 sand and 2 glass.";
+        let (_, blueprint) = BluePrint::parser(input).unwrap();
+        let [sand, glass, lens] = ["sand", "glass", "lens"].map(|n| blueprint.resource(n).unwrap());
+        let start = Factory::new(Inventory::from([2]));
+
+        let plan = blueprint.best_plan(&start, lens, 12);
+        let replay = plan.replay(&blueprint, &start);
+
+        assert_eq!(blueprint.resources.len(), 3);
+        assert!(plan.amount() > 0);
+        assert_eq!(replay.last(), Some(&plan.factory));
+        assert!(plan.builds.iter().any(|b| b.robot == glass));
+        assert_eq!(plan.factory.robots()[sand], 2 + plan.builds.iter().filter(|b| b.robot == sand).count() as u32);
+        assert!(blueprint.max_geodes(24).is_err());
+    }
 }
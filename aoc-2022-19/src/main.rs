@@ -8,7 +8,7 @@ const INPUT: &str = include_str!("../data/input.txt");
 fn part1(_input: &InputModel) -> Result<String,AocError> {
     let qualities: Vec<u32> = _input.blueprints.iter()
         .map(BluePrint::quality)
-        .collect();
+        .collect::<Result<_, _>>()?;
     Ok(qualities.iter().sum::<u32>().to_string())
 }
 
@@ -16,7 +16,7 @@ fn part2(_input: &InputModel) -> Result<String, AocError> {
     let qualities: Vec<u32> = _input.blueprints.iter()
         .take(3)
         .map(|blue_print| blue_print.max_geodes(32))
-        .collect();
+        .collect::<Result<_, _>>()?;
     Ok(qualities.iter().product::<u32>().to_string())
 }
 
@@ -60,11 +60,11 @@ mod tests {
     #[test]
     fn test_part2() {
         let blue_prints = input_data().blueprints;
-        //let actual = blue_prints[0].max_geodes(32);
-        //let expected = 56;
-        //assert_eq!(actual, expected);
+        let actual = blue_prints[0].max_geodes(32).unwrap();
+        let expected = 56;
+        assert_eq!(actual, expected);
         
-        let actual = blue_prints[1].max_geodes(32);
+        let actual = blue_prints[1].max_geodes(32).unwrap();
         let expected = 62;
         assert_eq!(actual, expected);
     }
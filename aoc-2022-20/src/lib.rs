@@ -1,5 +1,9 @@
 use std::str::FromStr;
 
+use treap::MixingList;
+
+pub mod treap;
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel {
     pub coordinates: Vec<i64>,
@@ -9,6 +13,8 @@ pub struct InputModel {
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("The file has no 0 to find the grove coordinates from")]
+    NoZero,
 }
 
 impl FromStr for InputModel {
@@ -23,66 +29,81 @@ impl FromStr for InputModel {
     }
 }
 
+/// The decryption key of part 2.
+pub const DECRYPTION_KEY: i64 = 811589153;
+/// The number of times the file is mixed in part 2.
+pub const ROUNDS: usize = 10;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct CodeRing {
-    code: Vec<(usize, i64)>,
+    code: Vec<i64>,
+    key: i64,
+    // the positions in the original file, in mixed order
+    ring: MixingList,
 }
 
 impl CodeRing {
-   
     pub fn new(code: Vec<i64>) -> Self {
-        let code = code
-            .into_iter()
-            .enumerate()
-            .collect::<Vec<_>>();
-        CodeRing { code }
-    }
-
-    pub fn decode(&mut self) -> i64 {
-        let original = self.code.clone();
-        for (orig_pos, value) in original.iter() {
-            let from_idx = self.code.iter().position(|(pos, _)| pos == orig_pos).unwrap();
-            let to_idx = ((from_idx as i64 + value).rem_euclid((self.code.len() - 1) as i64)) as usize;
-            let t = self.code.remove(from_idx);
-            self.code.insert(to_idx, t);
-        };
-        let offset = self.code.iter().position(|(_, v)| *v == 0).unwrap();
-        
-        (1000..=3000).step_by(1000)
-            .map(|i| (i + offset) % self.code.len())
-            .map(|i| self.code[i].1)
-            .sum()
-        
-    }
-
-    pub fn decode_2(&mut self) -> i64 {
-        let magic = 811589153;
-        let original = self.code.clone()
-            .into_iter()
-            .map(|(pos, value)| (pos, value * magic))
-            .collect::<Vec<_>>();
-        self.code = original.clone();
-        for _ in 0..10 {
-            for (orig_pos, value) in original.iter() {
-                let from_idx = self.code.iter().position(|(pos, _)| pos == orig_pos).unwrap();
-                let to_idx = ((from_idx as i64 + value).rem_euclid((self.code.len() - 1) as i64)) as usize;
-                let t = self.code.remove(from_idx);
-                self.code.insert(to_idx, t);
-            };
-        };
-        let offset = self.code.iter().position(|(_, v)| *v == 0).unwrap();
-        
-        (1000..=3000).step_by(1000)
-            .map(|i| (i + offset) % self.code.len())
-            .map(|i| self.code[i].1 )
-            .sum()
-        
+        let ring = MixingList::new(code.len());
+        CodeRing { code, key: 1, ring }
+    }
+
+    pub fn with_key(mut self, key: i64) -> Self {
+        self.key = key;
+        self
     }
 
+    // move every number as often as its value says, in the order of the original file
+    pub fn mix(&mut self) {
+        let m = self.code.len() as i128 - 1;
+        if m < 1 {
+            return;
+        }
+        for (i, value) in self.code.iter().enumerate() {
+            let offset = (*value as i128 * self.key as i128).rem_euclid(m);
+            self.ring.move_by(i, offset as i64);
+        }
+    }
 
-}
+    // a number with the key applied, which need not fit the input numbers any more
+    fn value(&self, i: usize) -> i128 {
+        self.code[i] as i128 * self.key as i128
+    }
 
+    /// The numbers in their current order, with the key applied.
+    pub fn values(&self) -> Vec<i128> {
+        self.ring.iter().map(|i| self.value(i)).collect()
+    }
+
+    pub fn grove_coordinates(&self) -> Result<i128, AocError> {
+        let zero = self.code.iter().position(|v| *v == 0).ok_or(AocError::NoZero)?;
+        let offset = self.ring.index_of(zero);
+
+        Ok((1000..=3000)
+            .step_by(1000)
+            .filter_map(|i| self.ring.get((i + offset) % self.code.len()))
+            .map(|i| self.value(i))
+            .sum())
+    }
 
+    /// Mix the file from its original order the given number of rounds after applying the key.
+    pub fn decrypt(&mut self, key: i64, rounds: usize) -> Result<i128, AocError> {
+        self.key = key;
+        self.ring = MixingList::new(self.code.len());
+        for _ in 0..rounds {
+            self.mix();
+        }
+        self.grove_coordinates()
+    }
+
+    pub fn decode(&mut self) -> Result<i128, AocError> {
+        self.decrypt(1, 1)
+    }
+
+    pub fn decode_2(&mut self) -> Result<i128, AocError> {
+        self.decrypt(DECRYPTION_KEY, ROUNDS)
+    }
+}
 
 pub const TEST_INPUT: &str = "1
 2
@@ -121,13 +142,60 @@ mod tests {
     fn test_decode() {
         let input = super::input_data();
         let mut code_ring = super::CodeRing::new(input.coordinates);
-        assert_eq!(code_ring.decode(), 3);
+        assert_eq!(code_ring.decode().unwrap(), 3);
     }
     #[test]
     fn test_decode_2() {
         let input = super::input_data();
         let mut code_ring = super::CodeRing::new(input.coordinates);
-        assert_eq!(code_ring.decode_2(), 1623178306);
+        assert_eq!(code_ring.decode_2().unwrap(), 1623178306);
     }
 
-}
+    // the order starting at 0, the ring has no fixed start
+    fn from_zero(values: Vec<i128>) -> Vec<i128> {
+        let zero = values.iter().position(|v| *v == 0).unwrap();
+        values[zero..].iter().chain(&values[..zero]).copied().collect()
+    }
+
+    #[test]
+    fn test_mix_rounds() {
+        let input = super::input_data();
+        let mut code_ring = super::CodeRing::new(input.coordinates.clone());
+        code_ring.mix();
+        assert_eq!(from_zero(code_ring.values()), vec![0, 3, -2, 1, 2, -3, 4]);
+
+        let mut code_ring = super::CodeRing::new(input.coordinates).with_key(super::DECRYPTION_KEY);
+        code_ring.mix();
+        assert_eq!(
+            from_zero(code_ring.values()),
+            vec![0, -2434767459, 3246356612, -1623178306, 2434767459, 1623178306, 811589153]
+        );
+        for _ in 1..super::ROUNDS {
+            code_ring.mix();
+        }
+        assert_eq!(
+            from_zero(code_ring.values()),
+            vec![0, -2434767459, 1623178306, 3246356612, -1623178306, 2434767459, 811589153]
+        );
+    }
+
+    #[test]
+    fn test_decrypt() {
+        let input = super::input_data();
+        let mut code_ring = super::CodeRing::new(input.coordinates);
+
+        // decrypting starts over from the file as it was given
+        assert_eq!(code_ring.decrypt(super::DECRYPTION_KEY, 10).unwrap(), 1623178306);
+        assert_eq!(code_ring.decrypt(1, 1).unwrap(), 3);
+        assert_eq!(code_ring.decrypt(1, 0).unwrap(), -2 + 3 + -3);
+        assert!(matches!(
+            super::CodeRing::new(vec![1, 2, 3]).decode(),
+            Err(super::AocError::NoZero)
+        ));
+        // the key times a number need not fit an i64
+        let mut code_ring = super::CodeRing::new(vec![0, 2, -1]);
+        assert_eq!(code_ring.decrypt(i64::MAX, 0).unwrap(), i64::MAX as i128);
+        assert_eq!(code_ring.decrypt(i64::MAX, 1).unwrap(), code_ring.values().iter().sum::<i128>());
+    }
+
+}
\ No newline at end of file
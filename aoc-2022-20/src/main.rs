@@ -6,12 +6,12 @@ const INPUT: &str = include_str!("../data/input.txt");
 
 fn part1(input: &InputModel) -> Result<String,AocError> {
     let mut code_ring = CodeRing::new(input.coordinates.clone());
-    Ok(code_ring.decode().to_string())
+    Ok(code_ring.decode()?.to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
     let mut code_ring = CodeRing::new(input.coordinates.clone());
-    Ok(code_ring.decode_2().to_string())
+    Ok(code_ring.decrypt(DECRYPTION_KEY, ROUNDS)?.to_string())
 }
 
 fn main() -> Result<(), AocError> {
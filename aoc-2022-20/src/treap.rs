@@ -0,0 +1,221 @@
+/// A list of the items `0..n` kept in an implicit treap, so an item can be found, moved and
+/// looked up by index in logarithmic time.
+///
+/// The nodes live in an arena indexed by item and know their parent, which lets
+/// [`MixingList::index_of`] walk up from an item instead of searching for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixingList {
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    priority: u64,
+    size: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+    parent: Option<usize>,
+}
+
+// xorshift, the priorities only need to look random
+fn priorities(seed: u64) -> impl Iterator<Item = u64> {
+    let mut state = seed;
+    std::iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    })
+}
+
+impl MixingList {
+    pub fn new(n: usize) -> MixingList {
+        let nodes = priorities(0x9e37_79b9_7f4a_7c15)
+            .take(n)
+            .map(|priority| Node {
+                priority,
+                size: 1,
+                left: None,
+                right: None,
+                parent: None,
+            })
+            .collect();
+        let mut list = MixingList { nodes, root: None };
+        list.root = (0..n).fold(None, |root, item| list.merge(root, Some(item)));
+        list
+    }
+
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    fn size(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |n| self.nodes[n].size)
+    }
+
+    // recompute the size of the node and point its children back at it
+    fn update(&mut self, node: usize) {
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        self.nodes[node].size = 1 + self.size(left) + self.size(right);
+        for child in [left, right].into_iter().flatten() {
+            self.nodes[child].parent = Some(node);
+        }
+    }
+
+    fn merge(&mut self, a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        let (a, b) = match (a, b) {
+            (None, b) => return b,
+            (a, None) => return a,
+            (Some(a), Some(b)) => (a, b),
+        };
+        if self.nodes[a].priority > self.nodes[b].priority {
+            let right = self.merge(self.nodes[a].right, Some(b));
+            self.nodes[a].right = right;
+            self.update(a);
+            Some(a)
+        } else {
+            let left = self.merge(Some(a), self.nodes[b].left);
+            self.nodes[b].left = left;
+            self.update(b);
+            Some(b)
+        }
+    }
+
+    // the first k items and the rest
+    fn split(&mut self, node: Option<usize>, k: usize) -> (Option<usize>, Option<usize>) {
+        let Some(n) = node else {
+            return (None, None);
+        };
+        self.nodes[n].parent = None;
+        let left_size = self.size(self.nodes[n].left);
+        if k <= left_size {
+            let (l, r) = self.split(self.nodes[n].left, k);
+            self.nodes[n].left = r;
+            self.update(n);
+            (l, Some(n))
+        } else {
+            let (l, r) = self.split(self.nodes[n].right, k - left_size - 1);
+            self.nodes[n].right = l;
+            self.update(n);
+            (Some(n), r)
+        }
+    }
+
+    fn set_root(&mut self, root: Option<usize>) {
+        if let Some(r) = root {
+            self.nodes[r].parent = None;
+        }
+        self.root = root;
+    }
+
+    /// The position of the item in the list.
+    pub fn index_of(&self, item: usize) -> usize {
+        let mut index = self.size(self.nodes[item].left);
+        let mut node = item;
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].right == Some(node) {
+                index += self.size(self.nodes[parent].left) + 1;
+            }
+            node = parent;
+        }
+        index
+    }
+
+    /// The item at the position in the list.
+    pub fn get(&self, mut index: usize) -> Option<usize> {
+        let mut node = self.root?;
+        loop {
+            let left_size = self.size(self.nodes[node].left);
+            match index.cmp(&left_size) {
+                std::cmp::Ordering::Less => node = self.nodes[node].left?,
+                std::cmp::Ordering::Equal => return Some(node),
+                std::cmp::Ordering::Greater => {
+                    index -= left_size + 1;
+                    node = self.nodes[node].right?;
+                }
+            }
+        }
+    }
+
+    /// Take the item out and put it back `offset` places further on, wrapping around the
+    /// ends like a ring where the item does not count while it moves.
+    pub fn move_by(&mut self, item: usize, offset: i64) {
+        let n = self.len();
+        if n < 2 {
+            return;
+        }
+        let from = self.index_of(item);
+        let to = (from as i64 + offset).rem_euclid(n as i64 - 1) as usize;
+        if from == to {
+            return;
+        }
+        let (left, rest) = self.split(self.root, from);
+        let (_, right) = self.split(rest, 1);
+        let others = self.merge(left, right);
+        let (left, right) = self.split(others, to);
+        let left = self.merge(left, Some(item));
+        let root = self.merge(left, right);
+        self.set_root(root);
+    }
+
+    /// The items in list order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut stack = vec![];
+        let mut node = self.root;
+        std::iter::from_fn(move || {
+            while let Some(n) = node {
+                stack.push(n);
+                node = self.nodes[n].left;
+            }
+            let n = stack.pop()?;
+            node = self.nodes[n].right;
+            Some(n)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // the list as a plain vector, moving items the slow way
+    fn move_in_vec(list: &mut Vec<usize>, item: usize, offset: i64) {
+        let from = list.iter().position(|i| *i == item).unwrap();
+        let to = (from as i64 + offset).rem_euclid(list.len() as i64 - 1) as usize;
+        let item = list.remove(from);
+        list.insert(to, item);
+    }
+
+    #[test]
+    fn test_new_list() {
+        let list = MixingList::new(10);
+
+        assert_eq!(list.len(), 10);
+        assert_eq!(list.iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+        assert!((0..10).all(|i| list.index_of(i) == i && list.get(i) == Some(i)));
+        assert_eq!(list.get(10), None);
+        assert!(MixingList::new(0).is_empty());
+    }
+
+    #[test]
+    fn test_moves_match_vec() {
+        let n = 500;
+        let mut list = MixingList::new(n);
+        let mut expected = (0..n).collect::<Vec<_>>();
+        let offsets = priorities(42).map(|p| (p % 4001) as i64 - 2000);
+        for (step, offset) in offsets.take(3000).enumerate() {
+            let item = step * 7 % n;
+            list.move_by(item, offset);
+            move_in_vec(&mut expected, item, offset);
+        }
+
+        assert_eq!(list.iter().collect::<Vec<_>>(), expected);
+        assert!((0..n).all(|i| list.get(list.index_of(i)) == Some(i)));
+    }
+}
@@ -1,4 +1,8 @@
-use std::{str::FromStr, collections::HashMap};
+use std::{str::FromStr, collections::HashMap, fmt::{Display, Formatter}};
+
+use symbolic::{Equation, Solution};
+
+pub mod symbolic;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel  {
@@ -9,6 +13,24 @@ pub struct InputModel  {
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("Unknown monkey {0}")]
+    UnknownMonkey(MonkeyId),
+    #[error("Monkey {0} compares inside an expression")]
+    NestedRoot(MonkeyId),
+    #[error("Division by zero")]
+    DivisionByZero,
+    #[error("Cannot solve an equation of degree {0}")]
+    Unsolvable(usize),
+    #[error("No number solves {0}")]
+    NoSolution(String),
+    #[error("The solution {0} is not a whole number")]
+    NotInteger(String),
+    #[error("The solution {0} does not fit in an i128")]
+    Overflow(String),
+    #[error("Several numbers solve it: {0:?}")]
+    MultipleSolutions(Vec<i128>),
+    #[error("Any number solves it")]
+    AnySolution,
 }
         
 impl FromStr for InputModel {
@@ -43,6 +65,12 @@ impl MonkeyId {
 }
 
 
+impl Display for MonkeyId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl FromStr for MonkeyId {
     type Err = AocError;
 
@@ -117,75 +145,15 @@ pub  fn prepare_part2(expressions: &Expressions) -> Expressions {
     expressions
 }
 
-pub fn find_hole(monkey: &MonkeyId, target: i128, expressions: &Expressions) -> Option<i128> {
-    let expression = expressions.get(monkey).unwrap();
-    println!("find_hole: {expression:?} -> {target}");
-
-    match expression {
-        Expression::Add(a, b) => find_hole_add(a, b, target, expressions),
-        Expression::Subtract(a, b) => find_hole_subtract(a, b, target, expressions),
-        Expression::Multiply(a, b) => find_hole_multiply(a, b, target, expressions),
-        Expression::Divide(a, b) => find_hole_divide(a, b, target, expressions),
-        Expression::Hole => Some(target),
-        Expression::Root(a, b) => find_hole_root(a, b, expressions),
-        Expression::Literal(_) => None,  // there in no hole to determine
-    }
-    
-}
-
-fn find_hole_add(a: &MonkeyId, b: &MonkeyId, target: i128, expressions: &HashMap<MonkeyId, Expression>) -> Option<i128> {
-    let a_value = evaluate(a, expressions);
-    let b_value = evaluate(b, expressions);
-    match (a_value, b_value) {
-        (None, Some(b)) => find_hole(a, target - b, expressions),
-        (Some(a), None) => find_hole(b, target - a, expressions),
-        _ => None,  // either both are known or neither
+// the number to yell for the monkey to yell the target, or for the root monkey to see equal numbers
+pub fn find_hole(monkey: &MonkeyId, target: i128, expressions: &Expressions) -> Result<i128, AocError> {
+    match Equation::new(monkey, target, expressions)?.solve()? {
+        Solution::Unique(n) => Ok(n),
+        Solution::Multiple(solutions) => Err(AocError::MultipleSolutions(solutions)),
+        Solution::Any => Err(AocError::AnySolution),
     }
 }
 
-fn find_hole_subtract(a: &MonkeyId, b: &MonkeyId, target: i128, expressions: &HashMap<MonkeyId, Expression>) -> Option<i128> {
-    let a_value = evaluate(a, expressions);
-    let b_value = evaluate(b, expressions);
-    println!("subtract a: {a_value:?}, b: {b_value:?}, target: {target}");
-    match (a_value, b_value) {
-        (None, Some(b)) => find_hole(a, target + b, expressions),
-        (Some(a), None) => find_hole(b, a - target, expressions),
-        _ => None,
-    }
-}
-
-fn find_hole_multiply(a: &MonkeyId, b: &MonkeyId, target: i128, expressions: &HashMap<MonkeyId, Expression>) -> Option<i128> {
-    let a_value = evaluate(a, expressions);
-    let b_value = evaluate(b, expressions);
-    match (a_value, b_value) {
-        (None, Some(b)) => find_hole(a, target / b, expressions),
-        (Some(a), None) => find_hole(b, target / a, expressions),
-        _ => None,
-    }
-}
-
-fn find_hole_divide(a: &MonkeyId, b: &MonkeyId, target: i128, expressions: &HashMap<MonkeyId, Expression>) -> Option<i128> {
-    let a_value = evaluate(a, expressions);
-    let b_value = evaluate(b, expressions);
-    println!("divide a: {a_value:?}, b: {b_value:?}, target: {target}");
-    match (a_value, b_value) {
-        (None, Some(b)) => find_hole(a, target * b, expressions),
-        (Some(a), None) => find_hole(b, a / target, expressions),
-        _ => None,
-    }
-}
-
-fn find_hole_root(a: &MonkeyId, b: &MonkeyId, expressions: &HashMap<MonkeyId, Expression>) -> Option<i128> {
-    let a_value = evaluate(a, expressions);
-    let b_value = evaluate(b, expressions);
-    match (a_value, b_value) {
-        (None, Some(b)) => find_hole(a, b, expressions),
-        (Some(a), None) => find_hole(b, a, expressions),
-        _ => None,
-    }
-}
-
-
 #[cfg(test)]
 mod tests {
 
@@ -238,13 +206,35 @@ mod tests {
         ];
 
         for (expected, input) in test_data {
-            println!("test fine hole in : {input}");
             let model: InputModel = input.parse().unwrap();
             let expressions = prepare_part2(&model.expressions);
             let hole = find_hole(&MonkeyId::new("root"), 0,  &expressions);
-            println!("Hole: {hole:?}, expected {expected}");
-            assert_eq!(Some(*expected), hole);
+            assert_eq!(*expected, hole.unwrap());
         }
+
+        let hole = |input: &str| {
+            let model: InputModel = input.parse().unwrap();
+            find_hole(&MonkeyId::new("root"), 0, &prepare_part2(&model.expressions))
+        };
+        assert!(matches!(
+            hole("root: a + b\na: twelve / humn\nb: humn - one\ntwelve: 12\none: 1\nhumn: 0"),
+            Err(AocError::MultipleSolutions(s)) if s == vec![-3, 4]
+        ));
+        assert!(matches!(hole("root: a + b\na: humn - humn\nb: 0\nhumn: 0"), Err(AocError::AnySolution)));
+        assert!(matches!(
+            hole("root: a + b\na: humn / two\nb: three / four\ntwo: 2\nthree: 3\nfour: 4\nhumn: 0"),
+            Err(AocError::NotInteger(_))
+        ));
+        // humn * big * big is far beyond i128 before the divisions bring it back
+        let big = "big: 1000000000000000000000000000000";
+        assert_eq!(
+            hole(&format!("root: a + b\na: c / big\nc: d / big\nd: e * big\ne: humn * big\nb: 7\nhumn: 0\n{}", big)).unwrap(),
+            7
+        );
+        assert!(matches!(
+            hole(&format!("root: a + b\na: humn / big\nb: c * big\nc: d * big\nd: 1\nhumn: 0\n{}", big)),
+            Err(AocError::Overflow(_))
+        ));
     }
 
 }
@@ -14,9 +14,7 @@ fn part1(input: &InputModel) -> Result<String,AocError> {
 fn part2(input: &InputModel) -> Result<String, AocError> {
     let expressions = prepare_part2(&input.expressions);
     let monkey = MonkeyId::new("root");
-    Ok(find_hole(&monkey, 0, &expressions)
-       .expect("expression to be valid")
-       .to_string())
+    Ok(find_hole(&monkey, 0, &expressions)?.to_string())
 }
 
 fn main() -> Result<(), AocError> {
@@ -0,0 +1,515 @@
+use std::{
+    fmt::{Display, Formatter},
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use num::{BigInt, BigRational, One, Signed, ToPrimitive, Zero};
+
+use crate::{AocError, Expression, Expressions, MonkeyId};
+
+// the square root when the number is a perfect square
+fn exact_sqrt(n: &BigInt) -> Option<BigInt> {
+    if n.is_negative() {
+        return None;
+    }
+    let root = n.sqrt();
+    (&root * &root == *n).then_some(root)
+}
+
+/// An exact fraction of any size, kept in lowest terms with a positive denominator.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Rational(BigRational);
+
+impl Rational {
+    pub fn zero() -> Rational {
+        Rational(BigRational::zero())
+    }
+
+    pub fn one() -> Rational {
+        Rational(BigRational::one())
+    }
+
+    pub fn new(num: i128, den: i128) -> Rational {
+        assert!(den != 0, "zero denominator");
+        Rational(BigRational::new(num.into(), den.into()))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    pub fn to_integer(&self) -> Option<BigInt> {
+        self.0.is_integer().then(|| self.0.to_integer())
+    }
+
+    fn sqrt(&self) -> Option<Rational> {
+        let (num, den) = (exact_sqrt(self.0.numer())?, exact_sqrt(self.0.denom())?);
+        Some(Rational(BigRational::new(num, den)))
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Rational(BigRational::from_integer(n.into()))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.to_integer() {
+            Some(n) => write!(f, "{}", n),
+            None => write!(f, "{}/{}", self.0.numer(), self.0.denom()),
+        }
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, other: Rational) -> Rational {
+        Rational(self.0 + other.0)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, other: Rational) -> Rational {
+        Rational(self.0 - other.0)
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, other: Rational) -> Rational {
+        Rational(self.0 * other.0)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, other: Rational) -> Rational {
+        Rational(self.0 / other.0)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational(-self.0)
+    }
+}
+
+// the monkeys shout i128s, a hole beyond that cannot be shouted
+fn to_i128(n: &BigInt) -> Result<i128, AocError> {
+    n.to_i128().ok_or_else(|| AocError::Overflow(n.to_string()))
+}
+
+/// A polynomial in the unknown `x` with the coefficient of `x^i` at index `i`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Polynomial(Vec<Rational>);
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<Rational>) -> Polynomial {
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+        Polynomial(coefficients)
+    }
+
+    pub fn constant(c: Rational) -> Polynomial {
+        Polynomial::new(vec![c])
+    }
+
+    pub fn x() -> Polynomial {
+        Polynomial::new(vec![Rational::zero(), Rational::one()])
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The highest power of `x`, the zero polynomial has none.
+    pub fn degree(&self) -> Option<usize> {
+        self.0.len().checked_sub(1)
+    }
+
+    pub fn coefficient(&self, power: usize) -> Rational {
+        self.0.get(power).cloned().unwrap_or_else(Rational::zero)
+    }
+
+    fn leading(&self) -> Rational {
+        self.0.last().cloned().unwrap_or_else(Rational::zero)
+    }
+
+    pub fn evaluate(&self, x: &Rational) -> Rational {
+        self.0.iter().rev().fold(Rational::zero(), |acc, c| acc * x.clone() + c.clone())
+    }
+
+    fn scale(&self, factor: Rational) -> Polynomial {
+        Polynomial::new(self.0.iter().map(|c| c.clone() * factor.clone()).collect())
+    }
+
+    // long division, the divisor must not be zero
+    fn div_rem(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        let d = divisor.degree().expect("division by the zero polynomial");
+        let mut remainder = self.0.clone();
+        let mut quotient = vec![Rational::zero(); self.0.len().saturating_sub(d)];
+        for power in (d..self.0.len()).rev() {
+            let factor = remainder[power].clone() / divisor.leading();
+            for (i, c) in divisor.0.iter().enumerate() {
+                remainder[power - d + i] = remainder[power - d + i].clone() - factor.clone() * c.clone();
+            }
+            quotient[power - d] = factor;
+        }
+        (Polynomial::new(quotient), Polynomial::new(remainder))
+    }
+
+    // the monic greatest common divisor
+    fn gcd(&self, other: &Polynomial) -> Polynomial {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let (_, r) = a.div_rem(&b);
+            a = b;
+            b = r;
+        }
+        let leading = a.leading();
+        if leading.is_zero() {
+            a
+        } else {
+            a.scale(Rational::one() / leading)
+        }
+    }
+
+    // the roots which are fractions, as far as they can be found exactly
+    fn rational_roots(&self) -> Result<Vec<Rational>, AocError> {
+        match self.degree() {
+            None | Some(0) => Ok(vec![]),
+            Some(1) => Ok(vec![-self.coefficient(0) / self.coefficient(1)]),
+            Some(2) => {
+                let (a, b, c) = (self.coefficient(2), self.coefficient(1), self.coefficient(0));
+                let discriminant = b.clone() * b.clone() - Rational::from(4) * a.clone() * c;
+                let Some(root) = discriminant.sqrt() else {
+                    return Ok(vec![]);
+                };
+                let two_a = Rational::from(2) * a;
+                let mut roots = vec![
+                    (-b.clone() - root.clone()) / two_a.clone(),
+                    (-b + root) / two_a,
+                ];
+                roots.dedup();
+                Ok(roots)
+            }
+            Some(degree) => Err(AocError::Unsolvable(degree)),
+        }
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, other: &Polynomial) -> Polynomial {
+        let n = self.0.len().max(other.0.len());
+        Polynomial::new(
+            (0..n)
+                .map(|i| self.coefficient(i) + other.coefficient(i))
+                .collect(),
+        )
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, other: &Polynomial) -> Polynomial {
+        self + &other.scale(-Rational::one())
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, other: &Polynomial) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::new(vec![]);
+        }
+        let mut coefficients = vec![Rational::zero(); self.0.len() + other.0.len() - 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                coefficients[i + j] = coefficients[i + j].clone() + a.clone() * b.clone();
+            }
+        }
+        Polynomial::new(coefficients)
+    }
+}
+
+impl Display for Polynomial {
+    // highest power first, like `3x^2 - (1/2)x + 4`
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (power, c) in self.0.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
+            }
+            let magnitude = Rational(c.0.abs());
+            match (first, c.is_negative()) {
+                (true, true) => write!(f, "-")?,
+                (true, false) => {}
+                (false, true) => write!(f, " - ")?,
+                (false, false) => write!(f, " + ")?,
+            }
+            first = false;
+            let coefficient = match (power, magnitude.to_integer()) {
+                (0, _) => magnitude.to_string(),
+                (_, Some(n)) if n.is_one() => String::new(),
+                (_, Some(n)) => n.to_string(),
+                (_, None) => format!("({})", magnitude),
+            };
+            match power {
+                0 => write!(f, "{}", coefficient)?,
+                1 => write!(f, "{}x", coefficient)?,
+                _ => write!(f, "{}x^{}", coefficient, power)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A quotient of polynomials in lowest terms, the denominator has a leading coefficient of 1.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct RationalFunction {
+    num: Polynomial,
+    den: Polynomial,
+}
+
+impl RationalFunction {
+    fn new(num: Polynomial, den: Polynomial) -> Result<RationalFunction, AocError> {
+        if den.is_zero() {
+            return Err(AocError::DivisionByZero);
+        }
+        let common = num.gcd(&den);
+        let (num, den) = if common.degree() > Some(0) {
+            (num.div_rem(&common).0, den.div_rem(&common).0)
+        } else {
+            (num, den)
+        };
+        let leading = Rational::one() / den.leading();
+        Ok(RationalFunction {
+            num: num.scale(leading.clone()),
+            den: den.scale(leading),
+        })
+    }
+
+    pub fn constant(c: Rational) -> RationalFunction {
+        RationalFunction {
+            num: Polynomial::constant(c),
+            den: Polynomial::constant(Rational::one()),
+        }
+    }
+
+    pub fn x() -> RationalFunction {
+        RationalFunction {
+            num: Polynomial::x(),
+            den: Polynomial::constant(Rational::one()),
+        }
+    }
+
+    pub fn numerator(&self) -> &Polynomial {
+        &self.num
+    }
+
+    pub fn denominator(&self) -> &Polynomial {
+        &self.den
+    }
+
+    fn add(&self, other: &RationalFunction) -> Result<RationalFunction, AocError> {
+        RationalFunction::new(
+            &(&self.num * &other.den) + &(&other.num * &self.den),
+            &self.den * &other.den,
+        )
+    }
+
+    fn sub(&self, other: &RationalFunction) -> Result<RationalFunction, AocError> {
+        RationalFunction::new(
+            &(&self.num * &other.den) - &(&other.num * &self.den),
+            &self.den * &other.den,
+        )
+    }
+
+    fn mul(&self, other: &RationalFunction) -> Result<RationalFunction, AocError> {
+        RationalFunction::new(&self.num * &other.num, &self.den * &other.den)
+    }
+
+    fn div(&self, other: &RationalFunction) -> Result<RationalFunction, AocError> {
+        RationalFunction::new(&self.num * &other.den, &self.den * &other.num)
+    }
+}
+
+impl Display for RationalFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.den.degree() == Some(0) {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "({}) / ({})", self.num, self.den)
+        }
+    }
+}
+
+/// The simplified expression a monkey yells, with the unknown as `x`.
+pub fn simplify(monkey: &MonkeyId, expressions: &Expressions) -> Result<RationalFunction, AocError> {
+    let expression = expressions
+        .get(monkey)
+        .ok_or_else(|| AocError::UnknownMonkey(monkey.clone()))?;
+    let operands = |a, b| Ok::<_, AocError>((simplify(a, expressions)?, simplify(b, expressions)?));
+    match expression {
+        Expression::Add(a, b) => operands(a, b).and_then(|(a, b)| a.add(&b)),
+        Expression::Subtract(a, b) => operands(a, b).and_then(|(a, b)| a.sub(&b)),
+        Expression::Multiply(a, b) => operands(a, b).and_then(|(a, b)| a.mul(&b)),
+        Expression::Divide(a, b) => operands(a, b).and_then(|(a, b)| a.div(&b)),
+        Expression::Literal(n) => Ok(RationalFunction::constant(Rational::from(*n))),
+        Expression::Hole => Ok(RationalFunction::x()),
+        Expression::Root(_, _) => Err(AocError::NestedRoot(monkey.clone())),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Solution {
+    Unique(i128),
+    Multiple(Vec<i128>),
+    // the equation holds whatever is yelled
+    Any,
+}
+
+/// Two simplified expressions which have to be equal.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Equation {
+    pub left: RationalFunction,
+    pub right: RationalFunction,
+}
+
+impl Equation {
+    /// The equation of the `root` monkey, or of another monkey yelling the target.
+    pub fn new(monkey: &MonkeyId, target: i128, expressions: &Expressions) -> Result<Equation, AocError> {
+        match expressions.get(monkey) {
+            Some(Expression::Root(a, b)) => Ok(Equation {
+                left: simplify(a, expressions)?,
+                right: simplify(b, expressions)?,
+            }),
+            _ => Ok(Equation {
+                left: simplify(monkey, expressions)?,
+                right: RationalFunction::constant(Rational::from(target)),
+            }),
+        }
+    }
+
+    /// The equation with everything moved to the left, as a single fraction.
+    pub fn normalized(&self) -> Result<RationalFunction, AocError> {
+        self.left.sub(&self.right)
+    }
+
+    pub fn solve(&self) -> Result<Solution, AocError> {
+        let difference = self.normalized()?;
+        if difference.num.is_zero() {
+            return Ok(Solution::Any);
+        }
+        let roots = difference
+            .num
+            .rational_roots()?
+            .into_iter()
+            .filter(|r| !difference.den.evaluate(r).is_zero())
+            .collect::<Vec<_>>();
+        let mut integers = roots.iter().filter_map(|r| r.to_integer()).collect::<Vec<_>>();
+        integers.sort();
+        match (integers.as_slice(), roots.first()) {
+            ([], None) => Err(AocError::NoSolution(self.to_string())),
+            ([], Some(root)) => Err(AocError::NotInteger(root.to_string())),
+            ([n], _) => Ok(Solution::Unique(to_i128(n)?)),
+            _ => Ok(Solution::Multiple(
+                integers.iter().map(to_i128).collect::<Result<_, _>>()?,
+            )),
+        }
+    }
+}
+
+impl Display for Equation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", self.left, self.right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{prepare_part2, InputModel};
+
+    fn equation(input: &str) -> Equation {
+        let model: InputModel = input.parse().unwrap();
+        let expressions = prepare_part2(&model.expressions);
+        Equation::new(&MonkeyId::new("root"), 0, &expressions).unwrap()
+    }
+
+    #[test]
+    fn test_rational() {
+        let half = Rational::new(2, -4);
+
+        assert_eq!(half, Rational::new(-1, 2));
+        assert_eq!((half.clone() + Rational::from(2)).to_string(), "3/2");
+        assert_eq!((half.clone() / half).to_integer(), Some(1.into()));
+    }
+
+    #[test]
+    fn test_polynomial_display() {
+        let p = Polynomial::new(vec![Rational::from(4), Rational::new(-1, 2), Rational::from(3)]);
+
+        assert_eq!(p.to_string(), "3x^2 - (1/2)x + 4");
+        assert_eq!((&p - &p).to_string(), "0");
+        assert_eq!(Polynomial::x().scale(-Rational::one()).to_string(), "-x");
+    }
+
+    #[test]
+    fn test_hole_on_both_sides() {
+        // 2x + 1 = x + 5
+        let eq = equation("root: a + b\na: c + one\nc: humn * two\nb: humn + five\none: 1\ntwo: 2\nfive: 5\nhumn: 0");
+
+        assert_eq!(eq.to_string(), "2x + 1 = x + 5");
+        assert_eq!(eq.solve().unwrap(), Solution::Unique(4));
+    }
+
+    #[test]
+    fn test_division_by_the_hole() {
+        // 12 / x = x - 1 has the roots 4 and -3
+        let eq = equation("root: a + b\na: twelve / humn\nb: humn - one\ntwelve: 12\none: 1\nhumn: 0");
+
+        assert_eq!(eq.to_string(), "(12) / (x) = x - 1");
+        assert_eq!(eq.normalized().unwrap().to_string(), "(-x^2 + x + 12) / (x)");
+        assert_eq!(eq.solve().unwrap(), Solution::Multiple(vec![-3, 4]));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        // x / 2 = 3 / 4 has no whole solution
+        let eq = equation("root: a + b\na: humn / two\nb: three / four\ntwo: 2\nthree: 3\nfour: 4\nhumn: 0");
+        assert!(matches!(eq.solve(), Err(AocError::NotInteger(s)) if s == "3/2"));
+
+        // x - x = 1 never holds, x - x = 0 always does
+        let eq = equation("root: a + b\na: humn - humn\nb: 1\nhumn: 0");
+        assert!(matches!(eq.solve(), Err(AocError::NoSolution(_))));
+        let eq = equation("root: a + b\na: humn - humn\nb: 0\nhumn: 0");
+        assert_eq!(eq.solve().unwrap(), Solution::Any);
+
+        // common factors cancel
+        let eq = equation("root: a + b\na: c / c\nc: humn + one\nb: 1\none: 1\nhumn: 0");
+        assert_eq!(eq.to_string(), "1 = 1");
+    }
+}
@@ -1,18 +1,14 @@
-use core::fmt;
-use itertools::Itertools;
 use std::{
-    collections::HashMap,
-    fmt::{Display, Formatter},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    fmt::{self, Display, Formatter},
 };
 
 use aoc_common::position::Position;
-use ndarray::{array, Array2};
 
 use crate::{
     grid::{Direction, Grid, Move, Tile},
     AocError, InputModel,
 };
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CubePosition {
     face_id: usize,
@@ -61,80 +57,83 @@ impl Display for CubeSide {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
-enum Rotation {
-    Straight,
-    Flip,
-    Clockwise,
-    CounterClockwise,
+// a direction in space, the folded faces are told apart by their outward normals
+type Axis = [i32; 3];
+
+fn neg(a: Axis) -> Axis {
+    [-a[0], -a[1], -a[2]]
 }
 
-impl From<Rotation> for Array2<i32> {
-    fn from(rotation: Rotation) -> Self {
-        match rotation {
-            Rotation::Straight => array![[1, 0], [0, 1]],
-            Rotation::Flip => array![[-1, 0], [0, -1]],
-            Rotation::Clockwise => array![[0, 1], [-1, 0]],
-            Rotation::CounterClockwise => array![[0, -1], [1, 0]],
-        }
-    }
+fn dot(a: Axis, b: Axis) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
 }
 
-impl Rotation {
-    fn apply_dir(&self, dir: Direction) -> Direction {
-        match self {
-            Rotation::Straight => dir,
-            Rotation::Flip => dir.turn(Move::TurnLeft).turn(Move::TurnLeft),
-            Rotation::Clockwise => dir.turn(Move::TurnRight),
-            Rotation::CounterClockwise => dir.turn(Move::TurnLeft),
-        }
-    }
+/// Where a face of the net ends up once folded: the way it faces and the directions its
+/// columns and rows run in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Orientation {
+    normal: Axis,
+    right: Axis,
+    down: Axis,
+}
 
-    fn apply_rot(&self, rot: Rotation) -> Rotation {
-        match (self, rot) {
-            (Rotation::Straight, Rotation::Straight) => Rotation::Straight,
-            (Rotation::Straight, Rotation::Flip) => Rotation::Flip,
-            (Rotation::Straight, Rotation::Clockwise) => Rotation::Clockwise,
-            (Rotation::Straight, Rotation::CounterClockwise) => Rotation::CounterClockwise,
-            (Rotation::Flip, Rotation::Straight) => Rotation::Flip,
-            (Rotation::Flip, Rotation::Flip) => Rotation::Straight,
-            (Rotation::Flip, Rotation::Clockwise) => Rotation::CounterClockwise,
-            (Rotation::Flip, Rotation::CounterClockwise) => Rotation::Clockwise,
-            (Rotation::Clockwise, Rotation::Straight) => Rotation::Clockwise,
-            (Rotation::Clockwise, Rotation::Flip) => Rotation::CounterClockwise,
-            (Rotation::Clockwise, Rotation::Clockwise) => Rotation::Flip,
-            (Rotation::Clockwise, Rotation::CounterClockwise) => Rotation::Straight,
-            (Rotation::CounterClockwise, Rotation::Straight) => Rotation::CounterClockwise,
-            (Rotation::CounterClockwise, Rotation::Flip) => Rotation::Clockwise,
-            (Rotation::CounterClockwise, Rotation::Clockwise) => Rotation::Straight,
-            (Rotation::CounterClockwise, Rotation::CounterClockwise) => Rotation::Flip,
+impl Orientation {
+    // the first face of the net, looked at from the outside
+    const FRONT: Orientation = Orientation {
+        normal: [0, 0, -1],
+        right: [1, 0, 0],
+        down: [0, 1, 0],
+    };
+
+    // the orientation of the face folded down over the edge on the given side
+    fn fold(&self, direction: Direction) -> Orientation {
+        match direction {
+            Direction::Right => Orientation { normal: self.right, right: neg(self.normal), down: self.down },
+            Direction::Left => Orientation { normal: neg(self.right), right: self.normal, down: self.down },
+            Direction::Down => Orientation { normal: self.down, right: self.right, down: neg(self.normal) },
+            Direction::Up => Orientation { normal: neg(self.down), right: self.right, down: self.normal },
         }
     }
 
-    fn inverse(&self) -> Rotation {
-        match self {
-            Rotation::Straight => Rotation::Straight,
-            Rotation::Flip => Rotation::Flip,
-            Rotation::Clockwise => Rotation::CounterClockwise,
-            Rotation::CounterClockwise => Rotation::Clockwise,
+    // the way the edge on the given side points
+    fn edge(&self, direction: Direction) -> Axis {
+        match direction {
+            Direction::Right => self.right,
+            Direction::Left => neg(self.right),
+            Direction::Down => self.down,
+            Direction::Up => neg(self.down),
         }
     }
 }
 
+/// Crossing an edge leads to face `to`, entering it through its `side` edge.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CubeLink {
     to: usize,
-    rotation: Rotation,
+    side: Direction,
 }
 
 impl CubeLink {
-    fn new(to: usize, rotation: Rotation) -> Self {
-        Self { to, rotation }
+    fn new(to: usize, side: Direction) -> Self {
+        Self { to, side }
     }
 }
 
 type CubeLinks = HashMap<usize, HashMap<Direction, CubeLink>>;
 
+// faces are square, so the size follows from the number of tiles
+pub fn face_size(grid: &Grid) -> Result<usize, AocError> {
+    let tiles = grid.iter()
+        .flat_map(|row| row.iter())
+        .filter(|t| **t != Tile::Void)
+        .count();
+    let size = ((tiles / 6) as f64).sqrt().round() as usize;
+    if size == 0 || size * size * 6 != tiles {
+        return Err(AocError::InvalidNet(format!("{} tiles do not make six square faces", tiles)));
+    }
+    Ok(size)
+}
+
 pub fn find_cube_sides(grid: &Grid, size: usize) -> Vec<CubeSide> {
     let faces_h = grid.len() / size;
     (0..grid.len())
@@ -159,166 +158,207 @@ pub fn find_cube_sides(grid: &Grid, size: usize) -> Vec<CubeSide> {
         .collect::<Vec<CubeSide>>()
 }
 
+// the faces which touch in the net
 pub fn find_straight_links(sides: &[CubeSide]) -> CubeLinks {
     let side_map: HashMap<Position, &CubeSide> =
         sides.iter().map(|s| (s.face_position, s)).collect();
-    let straight_links = sides
+    sides
         .iter()
         .map(|side| {
             let links = Direction::iter()
                 .filter_map(|dir| {
                     let face_pos = dir.step(side.face_position);
-                    if let Some(other) = side_map.get(&face_pos) {
-                        let link = CubeLink::new(other.id, Rotation::Straight);
-
-                        Some((dir, link))
-                    } else {
-                        None
-                    }
+                    side_map
+                        .get(&face_pos)
+                        .map(|other| (dir, CubeLink::new(other.id, dir.inverse())))
                 })
                 .collect::<HashMap<Direction, CubeLink>>();
             (side.id, links)
         })
-        .collect::<CubeLinks>();
-
-    straight_links
+        .collect::<CubeLinks>()
 }
 
-fn find_rotation(dir1: Direction, dir2: Direction) -> Rotation {
-    match (dir1, dir2) {
-        (Direction::Up, Direction::Right) => Rotation::Clockwise,
-        (Direction::Right, Direction::Down) => Rotation::Clockwise,
-        (Direction::Down, Direction::Left) => Rotation::Clockwise,
-        (Direction::Left, Direction::Up) => Rotation::Clockwise,
-        (Direction::Up, Direction::Left) => Rotation::CounterClockwise,
-        (Direction::Left, Direction::Down) => Rotation::CounterClockwise,
-        (Direction::Down, Direction::Right) => Rotation::CounterClockwise,
-        (Direction::Right, Direction::Up) => Rotation::CounterClockwise,
-        (Direction::Up, Direction::Down) => Rotation::Straight,
-        (Direction::Down, Direction::Up) => Rotation::Straight,
-        (Direction::Left, Direction::Right) => Rotation::Straight,
-        (Direction::Right, Direction::Left) => Rotation::Straight,
-        (Direction::Down, Direction::Down) => Rotation::Flip,
-        (Direction::Up, Direction::Up) => Rotation::Flip,
-        (Direction::Left, Direction::Left) => Rotation::Flip,
-        (Direction::Right, Direction::Right) => Rotation::Flip,
+// fold the net up around the first face by walking over the edges the faces share in the net
+fn fold_net(sides: &[CubeSide]) -> Result<HashMap<usize, Orientation>, AocError> {
+    if sides.len() != 6 {
+        return Err(AocError::InvalidNet(format!("{} faces instead of 6", sides.len())));
+    }
+    let straight = find_straight_links(sides);
+    let mut orientations = HashMap::from([(sides[0].id, Orientation::FRONT)]);
+    let mut open = VecDeque::from([sides[0].id]);
+    while let Some(id) = open.pop_front() {
+        let orientation = orientations[&id];
+        for (direction, link) in &straight[&id] {
+            if let Entry::Vacant(entry) = orientations.entry(link.to) {
+                entry.insert(orientation.fold(*direction));
+                open.push_back(link.to);
+            }
+        }
     }
-}
 
-fn neighbor_directions(dir1: Direction, dir2: Direction) -> bool {
-    matches!(
-        (dir1, dir2),
-        (Direction::Up, Direction::Right)
-            | (Direction::Right, Direction::Down)
-            | (Direction::Down, Direction::Left)
-            | (Direction::Left, Direction::Up)
-            | (Direction::Up, Direction::Left)
-            | (Direction::Left, Direction::Down)
-            | (Direction::Down, Direction::Right)
-            | (Direction::Right, Direction::Up)
-    )
+    let normals = orientations.values().map(|o| o.normal).collect::<HashSet<_>>();
+    if orientations.len() != 6 || normals.len() != 6 {
+        return Err(AocError::InvalidNet("faces overlap when folded".to_string()));
+    }
+    Ok(orientations)
 }
 
-fn add_links(sides: &[CubeSide], links: &CubeLinks, _cube_size: usize) -> CubeLinks {
-    let mut links = links.clone();
-    let additional: Vec<(usize, Direction, Rotation, usize)> = sides
-        .iter()
-        .flat_map(|side| {
-            let start_links = links.get(&side.id).unwrap();
-            start_links
-                .iter()
-                .permutations(2)
-                .filter(|edges| neighbor_directions(*edges[0].0, *edges[1].0))
-                .map(|links| {
-                    let (dir1, link1) = links[0];
-                    let (dir2, link2) = links[1];
-                    let rotation = find_rotation(*dir2, *dir1);
-                    // reverse rotation on link 1 and add rotation of link2
-                    let link_rot = link2.rotation.apply_rot(link1.rotation.inverse());
-                    // apply on the corner rotation
-                    let back_rotation = link_rot.apply_rot(rotation);
-                    let dir = link1.rotation.inverse().apply_dir(*dir2);
-                    
-                    (link1.to, dir, back_rotation, link2.to)
+/// Link every edge of every face to the edge it is glued to on the folded cube.
+pub fn find_cube_links(sides: &[CubeSide]) -> Result<CubeLinks, AocError> {
+    let orientations = fold_net(sides)?;
+    let by_normal = orientations.iter()
+        .map(|(id, o)| (o.normal, (*id, o)))
+        .collect::<HashMap<_, _>>();
+
+    let links = orientations.iter()
+        .map(|(id, from)| {
+            let links = Direction::iter()
+                .map(|direction| {
+                    // the face over the edge faces the way the edge points, and is entered
+                    // over its edge pointing back the way this face faces
+                    let (to, onto) = by_normal[&from.edge(direction)];
+                    let side = Direction::iter()
+                        .find(|side| onto.edge(*side) == from.normal)
+                        .unwrap();
+                    (direction, CubeLink::new(to, side))
                 })
-                .collect::<Vec<(usize, Direction, Rotation, usize)>>()
+                .collect();
+            (*id, links)
         })
         .collect();
+    validate_links(&links)?;
+    Ok(links)
+}
 
-    additional
-        .iter()
-        .for_each(|(from, direction, rotation, to)| {
-            let link = CubeLink::new(*to, *rotation);
-            let side = links.entry(*from).or_default();
-
-            side.entry(*direction)
-                .and_modify(|e| {
-                    if *e != link {
-                        println!("inconsisten links {e:?} <> {link:?}");
-                        panic!("invalid link");
-                    }
-                })
-                .or_insert(link);
-        });
-
-    links
+// every edge is entered from exactly one other edge, and leads back there
+fn validate_links(links: &CubeLinks) -> Result<(), AocError> {
+    let mut entered = HashSet::new();
+    for (from, edges) in links {
+        for (direction, link) in edges {
+            if link.to == *from || !entered.insert((link.to, link.side)) {
+                return Err(AocError::InvalidNet(format!("edge {:?} of face {} is glued twice", link.side, link.to)));
+            }
+            if links[&link.to][&link.side] != CubeLink::new(*from, *direction) {
+                return Err(AocError::InvalidNet(format!("edge {:?} of face {} does not lead back", direction, from)));
+            }
+        }
+    }
+    if entered.len() != 24 {
+        return Err(AocError::InvalidNet(format!("{} edges linked instead of 24", entered.len())));
+    }
+    Ok(())
 }
 
-pub fn find_cube_links(sides: &[CubeSide], cube_size: usize) -> CubeLinks {
-    let links = find_straight_links(sides);
+// marks drawn over the tiles, by face and position on the face
+type Marks = HashMap<(usize, Position), char>;
 
-    let links = add_links(sides, &links, cube_size);
-    let links = add_links(sides, &links, cube_size);
-    let links = add_links(sides, &links, cube_size);
-    add_links(sides, &links, cube_size)
-}
+// the orientation a face is drawn in and the one it has
+type Turn = (Orientation, Orientation);
+
+// the faces of the folded cube laid out as a cross, with the folds leading there from the front
+const CROSS: [(i32, i32, &[Direction]); 6] = [
+    (1, 0, &[Direction::Up]),
+    (0, 1, &[Direction::Left]),
+    (1, 1, &[]),
+    (2, 1, &[Direction::Right]),
+    (3, 1, &[Direction::Right, Direction::Right]),
+    (1, 2, &[Direction::Down]),
+];
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Cube {
     size: usize,
     sides: Vec<CubeSide>,
     links: CubeLinks,
+    orientations: HashMap<usize, Orientation>,
 }
 
 impl Cube {
-    pub fn new(grid: &Grid, size: usize) -> Self {
+    pub fn new(grid: &Grid, size: usize) -> Result<Self, AocError> {
         let sides = find_cube_sides(grid, size);
-        let links = find_cube_links(&sides, size);
-        Cube { size, sides, links }
+        let links = find_cube_links(&sides)?;
+        let orientations = fold_net(&sides)?;
+        Ok(Cube { size, sides, links, orientations })
     }
-}
 
-impl fmt::Display for Cube {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let faces: HashMap<Position, Vec<String>> = self
-            .sides
-            .iter()
-            .map(|side| -> (Position, Vec<String>) {
-                (
-                    side.face_position,
-                    format!("{side}").lines().map(|s| s.to_string()).collect(),
-                )
-            })
-            .collect();
+    fn side(&self, id: usize) -> &CubeSide {
+        self.sides.iter().find(|f| f.id == id).unwrap()
+    }
 
-        for y in 0..4 {
-            for row in 0..self.size {
+    fn tile_char(&self, side: &CubeSide, position: Position, marks: &Marks) -> char {
+        marks.get(&(side.id, position))
+            .copied()
+            .unwrap_or_else(|| side.get_tile(position).to_string().chars().next().unwrap())
+    }
+
+    // the net as it was given
+    fn render(&self, marks: &Marks) -> String {
+        let faces: HashMap<Position, &CubeSide> =
+            self.sides.iter().map(|s| (s.face_position, s)).collect();
+        let width = self.sides.iter().map(|s| s.face_position.x + 1).max().unwrap_or(0);
+        let height = self.sides.iter().map(|s| s.face_position.y + 1).max().unwrap_or(0);
+        self.render_layout(width, height, marks, |x, y| {
+            faces.get(&Position::new(x, y)).map(|side| (*side, None))
+        })
+    }
+
+    /// The folded cube unwrapped as a cross around the first face of the net, with every
+    /// face turned the way it sits on the cube.
+    fn render_folded(&self, marks: &Marks) -> String {
+        self.render_layout(4, 3, marks, |x, y| {
+            let (_, _, folds) = CROSS.iter().find(|(cx, cy, _)| (*cx, *cy) == (x, y))?;
+            let view = folds.iter().fold(Orientation::FRONT, |o, d| o.fold(*d));
+            let (id, face) = self.orientations.iter().find(|(_, o)| o.normal == view.normal)?;
+            Some((self.side(*id), Some((view, *face))))
+        })
+    }
+
+    // draw a layout of faces, optionally turning a face from its own orientation to a view
+    fn render_layout<'a>(
+        &'a self,
+        width: i32,
+        height: i32,
+        marks: &Marks,
+        face_at: impl Fn(i32, i32) -> Option<(&'a CubeSide, Option<Turn>)>,
+    ) -> String {
+        let n = self.size as i32 - 1;
+        let mut text = String::new();
+        for y in 0..height {
+            for row in 0..self.size as i32 {
                 let mut line = String::new();
-                for x in 0..=3 {
-                    let pos = Position::new(x, y);
-                    if let Some(side) = faces.get(&pos) {
-                        line.push_str(&side[row]);
-                    } else {
-                        line.push_str(&" ".repeat(self.size));
+                for x in 0..width {
+                    match face_at(x, y) {
+                        Some((side, turn)) => {
+                            for column in 0..self.size as i32 {
+                                let position = match turn {
+                                    None => Position::new(column, row),
+                                    Some((view, face)) => {
+                                        // from the view's coordinates through space to the face's
+                                        let (u, v) = (2 * column - n, 2 * row - n);
+                                        let w = [0, 1, 2].map(|i| view.right[i] * u + view.down[i] * v);
+                                        Position::new((dot(w, face.right) + n) / 2, (dot(w, face.down) + n) / 2)
+                                    }
+                                };
+                                line.push(self.tile_char(side, position, marks));
+                            }
+                        }
+                        None => line.push_str(&" ".repeat(self.size)),
                     }
                 }
                 if line.trim().is_empty() {
                     continue;
                 }
-                writeln!(f, "{}", line.trim_end())?;
+                text.push_str(line.trim_end());
+                text.push('\n');
             }
         }
-        Ok(())
+        text
+    }
+}
+
+impl fmt::Display for Cube {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(&Marks::new()))
     }
 }
 
@@ -328,6 +368,8 @@ struct CubeWalker {
     face: usize,
     position: Position,
     direction: Direction,
+    // every tile visited by `do_walk` with the way the walker was facing
+    path: Vec<(usize, Position, Direction)>,
 }
 
 impl CubeWalker {
@@ -337,30 +379,24 @@ impl CubeWalker {
             face,
             position,
             direction,
+            path: Vec::new(),
         }
     }
 
     pub fn walk(&mut self, no_walls: bool) {
         let mut pos = self.direction.step(self.position);
         let mut face = self.face;
-        let mut dir =  self.direction;
+        let mut dir = self.direction;
         if self.out_of_bounds(pos) {
-            pos = self
-                .direction
-                .inverse()
-                .steps(pos, self.cube.size);
-            let link = self
-                .cube
-                .links
-                .get(&self.face)
-                .unwrap()
-                .get(&self.direction)
-                .unwrap();
+            // the place along an edge counted clockwise runs the other way on the edge it is glued to
+            let link = &self.cube.links[&self.face][&self.direction];
+            let n = self.cube.size as i32 - 1;
+            let index = n - self.edge_index(self.position, self.direction);
             face = link.to;
-            dir = link.rotation.inverse().apply_dir(self.direction);
-            pos = self.rotate_position(pos, link.rotation);
+            pos = self.edge_position(index, link.side);
+            dir = link.side.inverse();
         }
-        let tile = self.cube.sides.iter().find(|f| f.id == face).unwrap().get_tile(pos);
+        let tile = self.cube.side(face).get_tile(pos);
         if no_walls || tile == Tile::Empty {
             self.face = face;
             self.position = pos;
@@ -372,88 +408,96 @@ impl CubeWalker {
         pos.x < 0 || pos.x >= self.cube.size as i32 || pos.y < 0 || pos.y >= self.cube.size as i32
     }
 
-    fn rotate_position(&self, pos: Position, rotation: Rotation) -> Position {
+    // position along the edge on the given side, clockwise around the face
+    fn edge_index(&self, pos: Position, side: Direction) -> i32 {
+        let n = self.cube.size as i32 - 1;
+        match side {
+            Direction::Up => pos.x,
+            Direction::Right => pos.y,
+            Direction::Down => n - pos.x,
+            Direction::Left => n - pos.y,
+        }
+    }
+
+    fn edge_position(&self, index: i32, side: Direction) -> Position {
         let n = self.cube.size as i32 - 1;
-        match rotation {
-            Rotation::Straight => pos,
-            Rotation::Clockwise => Position::new(pos.y, n - pos.x),
-            Rotation::CounterClockwise => Position::new(n - pos.y, pos.x),
-            Rotation::Flip => Position::new(n - pos.x, n - pos.y),
+        match side {
+            Direction::Up => Position::new(index, 0),
+            Direction::Right => Position::new(n, index),
+            Direction::Down => Position::new(n - index, n),
+            Direction::Left => Position::new(0, n - index),
         }
     }
 
-    fn do_walk(&mut self, moves: &[Move], no_walls: bool, display: bool) {
+    fn do_walk(&mut self, moves: &[Move], no_walls: bool) {
+        self.path.push((self.face, self.position, self.direction));
         for m in moves {
             self.direction = self.direction.turn(*m);
+            self.path.push((self.face, self.position, self.direction));
             if let Move::Forward(n) = m {
-                self.walk(no_walls);
-                for _ in 1..*n {
-                    if display {
-                    println!("{self}");
-                    }
+                for _ in 0..*n {
                     self.walk(no_walls);
+                    self.path.push((self.face, self.position, self.direction));
                 }
             }
-            if display {
-                println!("{self}");
-            }
         }
     }
 
     fn current_face(&self) -> CubeSide {
-        self.cube.sides.iter().find(|f| f.id == self.face).unwrap().clone()
+        self.cube.side(self.face).clone()
+    }
+
+    // the last facing on every tile of the path, like the puzzle draws it
+    fn marks(&self) -> Marks {
+        self.path.iter()
+            .chain(std::iter::once(&(self.face, self.position, self.direction)))
+            .map(|(face, position, direction)| {
+                let arrow = match direction {
+                    Direction::Up => '^',
+                    Direction::Down => 'v',
+                    Direction::Left => '<',
+                    Direction::Right => '>',
+                };
+                ((*face, *position), arrow)
+            })
+            .collect()
+    }
+
+    pub fn render_folded(&self) -> String {
+        self.cube.render_folded(&self.marks())
     }
 }
 
 impl Display for CubeWalker {
-    // place a marker for the walker position in the current face
-    // of the formatted cube
+    // the net with the path walked so far
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let display = format!("{}", self.cube);
-        if let Some(current_face) = self.cube.sides.iter().find(|f| f.id == self.face) {
-            let (c, r) = current_face.face_position.into();
-
-            let walker_line = (r * self.cube.size as i32 + self.position.y) as usize;
-            for (i, line) in display.lines().enumerate() {
-                let mut line = line.to_string();
-                if i == walker_line {
-                    let p: usize = (c * self.cube.size as i32 + self.position.x) as usize;
-                    line.replace_range(
-                        p..p + 1,
-                        &format!(
-                            "{}",
-                            match self.direction {
-                                Direction::Up => '^',
-                                Direction::Down => 'v',
-                                Direction::Left => '<',
-                                Direction::Right => '>',
-                            }
-                        ),
-                    );
-                };
-                writeln!(f, "{line}")?;
-            }
-        } else {
-            writeln!(f, "No such face : {}", self.face)?;
-        }
-        Ok(())
+        write!(f, "{}", self.cube.render(&self.marks()))
     }
 }
 
-pub fn cube_password(input: &InputModel, size: usize) -> Result<i32, AocError> {
-    let cube = Cube::new(&input.grid, size);
+fn walk_cube(input: &InputModel, size: usize) -> Result<CubeWalker, AocError> {
+    let cube = Cube::new(&input.grid, size)?;
     let face_id = cube.sides[0].id;
     let mut walker = CubeWalker::new(cube, face_id, Position::new(0, 0), Direction::Right);
-    walker.do_walk(&input.moves, false, false);
+    walker.do_walk(&input.moves, false);
+    Ok(walker)
+}
+
+pub fn cube_password(input: &InputModel, size: usize) -> Result<i32, AocError> {
+    let walker = walk_cube(input, size)?;
     let (x, y) = walker.position.into();
     let (c, r) = walker.current_face().face_position.into();
 
     let x = x + c * walker.cube.size as i32 + 1;
     let y = y + r * walker.cube.size as i32 + 1;
-    let _d: i32 = walker.direction.try_into().unwrap();
     walker.direction.try_into()
         .map(|d: i32| y * 1000 + x * 4 + d)
+}
 
+/// The path walked on the cube, drawn on the net and on the folded cube.
+pub fn render_path(input: &InputModel, size: usize) -> Result<(String, String), AocError> {
+    let walker = walk_cube(input, size)?;
+    Ok((walker.to_string(), walker.render_folded()))
 }
 
 #[cfg(test)]
@@ -486,7 +530,7 @@ mod tests {
         let input = TEST_INPUT.parse::<InputModel>().unwrap();
         let cubesides = find_cube_sides(&input.grid, 4);
 
-        let links = find_cube_links(&cubesides, 4);
+        let links = find_cube_links(&cubesides).unwrap();
         assert_eq!(links.len(), 6);
         assert_eq!(links.iter().flat_map(|(_, dir)| dir.keys()).count(), 24);
     }
@@ -494,7 +538,7 @@ mod tests {
     #[test]
     fn test_cube_display() {
         let input = TEST_INPUT.parse::<InputModel>().unwrap();
-        let cube = Cube::new(&input.grid, 4);
+        let cube = Cube::new(&input.grid, 4).unwrap();
         let cube_display = format!("{}", cube);
 
         // trim last 2 lines of TEST_INPUT
@@ -511,7 +555,7 @@ mod tests {
     fn test_cube_walk_straight() {
         let input = TEST_INPUT.parse::<InputModel>().unwrap();
         for direction in Direction::iter() {
-            let cube = Cube::new(&input.grid, 4);
+            let cube = Cube::new(&input.grid, 4).unwrap();
             let face_id = cube.sides[0].id;
             let mut walker = CubeWalker::new(cube, face_id, Position::new(1, 1), direction);
             let start_walker = walker.clone();
@@ -532,4 +576,101 @@ mod tests {
         let password = cube_password(&input, 4).unwrap();
         assert_eq!(password, 5031);
     }
+
+    // the eleven nets of a cube, one `#` per face
+    const NETS: [&str; 11] = [
+        "#...\n####\n#...",
+        "#...\n####\n.#..",
+        "#...\n####\n..#.",
+        "#...\n####\n...#",
+        ".#..\n####\n.#..",
+        ".#..\n####\n..#.",
+        "##..\n.###\n.#..",
+        "##..\n.###\n..#.",
+        "##..\n.###\n...#",
+        "##..\n.##.\n..##",
+        "###..\n..###",
+    ];
+
+    // blow every face of the layout up to an empty face of the given size
+    fn net(layout: &str, size: usize) -> Grid {
+        let lines = layout.lines()
+            .flat_map(|row| {
+                let line = row.chars()
+                    .map(|c| if c == '#' { ".".repeat(size) } else { " ".repeat(size) })
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string();
+                std::iter::repeat_n(line, size)
+            })
+            .collect::<Vec<_>>();
+        Grid::new(&lines.iter().map(|l| l.as_str()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_fold_all_nets() {
+        for layout in NETS {
+            for size in 1..=3 {
+                let grid = net(layout, size);
+                assert_eq!(face_size(&grid), Ok(size));
+
+                let sides = find_cube_sides(&grid, size);
+                let links = find_cube_links(&sides).unwrap();
+                assert_eq!(links.values().map(|l| l.len()).sum::<usize>(), 24);
+
+                // walking straight on returns after going round the cube
+                let cube = Cube::new(&grid, size).unwrap();
+                for direction in Direction::iter() {
+                    for start in [Position::new(0, 0), Position::new(size as i32 - 1, 0)] {
+                        let mut walker = CubeWalker::new(cube.clone(), cube.sides[0].id, start, direction);
+                        let start_walker = walker.clone();
+                        for _ in 0..4 * size {
+                            walker.walk(true);
+                        }
+                        assert_eq!(walker, start_walker, "{}", layout);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_nets() {
+        for layout in ["###\n###", "####\n##..", "#...\n####\n....\n#..."] {
+            let grid = net(layout, 2);
+            let sides = find_cube_sides(&grid, 2);
+
+            assert!(find_cube_links(&sides).is_err(), "{}", layout);
+        }
+        assert!(face_size(&net("##", 2)).is_err());
+    }
+
+    #[test]
+    fn test_render_folded() {
+        let input = TEST_INPUT.parse::<InputModel>().unwrap();
+        let cube = Cube::new(&input.grid, 4).unwrap();
+        let folded = cube.render_folded(&Marks::new());
+        let lines = folded.lines().collect::<Vec<_>>();
+        let net = TEST_INPUT.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 12);
+        assert_eq!(folded.matches('#').count(), TEST_INPUT.matches('#').count());
+        // the first face is the front, and the face below it in the net is below it on the cube
+        for row in 0..4 {
+            assert_eq!(&lines[4 + row][4..8], &net[row][8..12]);
+            assert_eq!(&lines[8 + row][4..8], &net[4 + row][8..12]);
+        }
+    }
+
+    #[test]
+    fn test_render_path() {
+        let input = TEST_INPUT.parse::<InputModel>().unwrap();
+        let (path, folded) = render_path(&input, 4).unwrap();
+        let lines = path.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "        >>v#");
+        // the password 5031 is row 5, column 7, facing up
+        assert_eq!(lines[4].chars().nth(6), Some('^'));
+        assert_eq!(folded.matches(['>', 'v', '<', '^']).count(), path.matches(['>', 'v', '<', '^']).count());
+    }
 }
@@ -86,15 +86,12 @@ impl Direction {
 
     pub fn steps(&self, pos: Position,  steps: usize) -> Position {
         let steps = steps as i32;
-        let new_pos = match self {
+        match self {
             Direction::Up => pos + Position::new(0, -steps),
             Direction::Down => pos + Position::new(0, steps),
             Direction::Left => pos + Position::new(-steps, 0),
             Direction::Right => pos + Position::new(steps, 0),
-        };
-        println!("{:?} {} -> {:?}", pos, steps, new_pos);
-        new_pos
-
+        }
     }
 
     pub(crate) fn inverse(&self) -> Direction {
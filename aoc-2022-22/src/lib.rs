@@ -11,6 +11,8 @@ pub enum AocError {
     ParseError,
     #[error("Invalid Direction")]
     DirectionError,
+    #[error("Not a cube net: {0}")]
+    InvalidNet(String),
 }
       
 
@@ -20,6 +22,13 @@ pub struct InputModel  {
     moves: Vec<Move>,
 }
 
+impl InputModel {
+    // the edge length of the faces of the cube the map folds into
+    pub fn face_size(&self) -> Result<usize, AocError> {
+        cube::face_size(&self.grid)
+    }
+}
+
 impl FromStr for InputModel {
     type Err = AocError;
 
@@ -19,7 +19,7 @@ fn main() -> Result<(), AocError> {
     let part1_result = part1(&input)?;
     println!("Part1: {part1_result}");
     println!("--------------");
-    let part2_result = part2(&input, 50)?;
+    let part2_result = part2(&input, input.face_size()?)?;
     println!("Part2: {part2_result}");
     Ok(())
 }
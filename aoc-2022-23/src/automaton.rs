@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use aoc_common::{bitgrid::BitGrid, position::Position};
+
+// empty cells added around the grid whenever the cells come close to its edge
+const GROWTH: usize = 8;
+
+/// A step a cell proposes when none of the scanned offsets hold a live cell.
+///
+/// The scan should include the step itself, otherwise a cell may move onto another one.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Rule {
+    pub step: Position,
+    pub scan: Vec<Position>,
+}
+
+impl Rule {
+    // how far from a cell the rule looks or moves
+    fn reach(&self) -> usize {
+        self.scan
+            .iter()
+            .chain([&self.step])
+            .map(|p| p.x.unsigned_abs().max(p.y.unsigned_abs()) as usize)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RoundStats {
+    pub moved: usize,
+    pub bounds: Option<(Position, Position)>,
+}
+
+/// Cells on a dense bit grid that move by a list of rules.
+///
+/// A cell with nothing around it stays put, any other cell proposes the step of the first rule
+/// whose scan is clear, starting one rule further down the list each round. Cells only move
+/// when no other cell proposed the same destination.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Automaton {
+    cells: BitGrid,
+    // the position of the top left of the grid
+    origin: Position,
+    rules: Vec<Rule>,
+    reach: usize,
+    // in grid coordinates
+    bounds: Option<(Position, Position)>,
+    history: Vec<RoundStats>,
+}
+
+impl Automaton {
+    pub fn new(cells: impl IntoIterator<Item = Position>, rules: Vec<Rule>) -> Automaton {
+        let cells = cells.into_iter().collect::<Vec<_>>();
+        let reach = rules.iter().map(Rule::reach).max().unwrap_or(0);
+        let margin = (GROWTH + reach) as i32;
+        let (min, max) = cells.iter().fold(
+            (Position::new(i32::MAX, i32::MAX), Position::new(i32::MIN, i32::MIN)),
+            |(min, max), p| {
+                (
+                    Position::new(min.x.min(p.x), min.y.min(p.y)),
+                    Position::new(max.x.max(p.x), max.y.max(p.y)),
+                )
+            },
+        );
+        let (origin, width, height) = if cells.is_empty() {
+            (Position::new(0, 0), 0, 0)
+        } else {
+            (
+                min - Position::new(margin, margin),
+                (max.x - min.x + 1 + 2 * margin) as usize,
+                (max.y - min.y + 1 + 2 * margin) as usize,
+            )
+        };
+        let mut grid = BitGrid::new(width, height);
+        for p in cells {
+            grid.set(p - origin, true);
+        }
+        Automaton {
+            bounds: grid.bounds(),
+            cells: grid,
+            origin,
+            rules,
+            reach,
+            history: vec![],
+        }
+    }
+
+    // grow the grid when a cell could look or move past its edge
+    fn make_room(&mut self) {
+        let Some((min, max)) = self.bounds else {
+            return;
+        };
+        let reach = self.reach as i32;
+        if min.x >= reach
+            && min.y >= reach
+            && max.x + reach < self.cells.width() as i32
+            && max.y + reach < self.cells.height() as i32
+        {
+            return;
+        }
+        let margin = GROWTH + self.reach;
+        let offset = Position::new(margin as i32, margin as i32);
+        self.cells = self.cells.pad(margin);
+        self.origin = self.origin - offset;
+        self.bounds = Some((min + offset, max + offset));
+    }
+
+    pub fn step(&mut self) -> RoundStats {
+        self.make_room();
+
+        // one grid per scanned offset, marking the cells with a live cell at that offset
+        let mut neighbours: HashMap<Position, BitGrid> = HashMap::new();
+        let blocked = self
+            .rules
+            .iter()
+            .map(|rule| {
+                rule.scan.iter().fold(self.empty(), |acc, offset| {
+                    let shifted = neighbours
+                        .entry(*offset)
+                        .or_insert_with(|| self.cells.translate(Position::new(0, 0) - *offset));
+                    &acc | shifted
+                })
+            })
+            .collect::<Vec<_>>();
+        let crowded = blocked.iter().fold(self.empty(), |acc, b| &acc | b);
+
+        let mut remaining = &self.cells & &crowded;
+        let first = self.history.len() % self.rules.len().max(1);
+        let proposals = (0..self.rules.len())
+            .map(|k| {
+                let rule = (first + k) % self.rules.len();
+                let proposers = &remaining - &blocked[rule];
+                remaining = &remaining - &proposers;
+                (rule, proposers)
+            })
+            .collect::<Vec<_>>();
+
+        // destinations proposed once, and the ones proposed more than once
+        let (_, contested) = proposals.iter().fold(
+            (self.empty(), self.empty()),
+            |(once, twice), (rule, proposers)| {
+                let targets = proposers.translate(self.rules[*rule].step);
+                (&once | &targets, &twice | &(&once & &targets))
+            },
+        );
+
+        let (left, arrived) = proposals.iter().fold(
+            (self.empty(), self.empty()),
+            |(left, arrived), (rule, proposers)| {
+                let step = self.rules[*rule].step;
+                let movers = proposers - &contested.translate(Position::new(0, 0) - step);
+                (&left | &movers, &arrived | &movers.translate(step))
+            },
+        );
+
+        self.cells = &(&self.cells - &left) | &arrived;
+        self.bounds = self.cells.bounds();
+        let stats = RoundStats {
+            moved: left.count(),
+            bounds: self.bounds(),
+        };
+        self.history.push(stats);
+        stats
+    }
+
+    fn empty(&self) -> BitGrid {
+        BitGrid::new(self.cells.width(), self.cells.height())
+    }
+
+    // the stats of the rounds just played
+    pub fn run(&mut self, rounds: usize) -> &[RoundStats] {
+        for _ in 0..rounds {
+            self.step();
+        }
+        &self.history[self.history.len() - rounds..]
+    }
+
+    // play until nothing moves, returning the number of that round
+    pub fn run_until_stable(&mut self) -> usize {
+        while self.step().moved > 0 {}
+        self.round()
+    }
+
+    pub fn round(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn history(&self) -> &[RoundStats] {
+        &self.history
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_none()
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.cells.get(position - self.origin)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = Position> + '_ {
+        self.cells.iter().map(|p| p + self.origin)
+    }
+
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        self.bounds.map(|(min, max)| (min + self.origin, max + self.origin))
+    }
+
+    // the empty cells in the box around the live ones
+    pub fn empty_ground(&self) -> usize {
+        self.bounds.map_or(0, |(min, max)| {
+            ((max.x - min.x + 1) * (max.y - min.y + 1)) as usize - self.len()
+        })
+    }
+}
+
+impl Display for Automaton {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some((min, max)) = self.bounds else {
+            return Ok(());
+        };
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let c = if self.cells.get(Position::new(x, y)) { '#' } else { '.' };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
-use aoc_common::position::Position;
-use std::{collections::{HashMap, HashSet}, str::FromStr};
+use aoc_common::{direction::Direction, position::Position};
+use automaton::{Automaton, Rule};
+use std::{collections::HashSet, str::FromStr};
+
+pub mod automaton;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel {
@@ -36,157 +39,29 @@ impl FromStr for InputModel {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum Direction {
-    North,
-    South,
-    West,
-    East,
-}
-
-impl Direction {
-    pub fn next_direction(&self) -> Direction {
-        match self {
-            Direction::North => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::East,
-            Direction::East => Direction::North,
-        }
-    }
-
-    fn next_position(&self, position: &Position) -> Position {
-        match self {
-            Direction::North => Position::new(position.x, position.y - 1),
-            Direction::South => Position::new(position.x, position.y + 1),
-            Direction::West => Position::new(position.x - 1, position.y),
-            Direction::East => Position::new(position.x + 1, position.y),
-        }
-    }
-    // retrunt the position of the next cell in the given direction
-    // and the 2 neighbours of the next cell
-    fn scan_position(&self, position: &Position) -> Vec<Position> {
-        let next_position = self.next_position(position);
-        
-        match self {
-            Direction::North => vec![
-                next_position,
-                Direction::East.next_position(&next_position),
-                Direction::West.next_position(&next_position),
-            ],
-            Direction::South => vec![
-                next_position,
-                Direction::East.next_position(&next_position),
-                Direction::West.next_position(&next_position),
-            ],
-            Direction::West => vec![
-                next_position,
-                Direction::North.next_position(&next_position),
-                Direction::South.next_position(&next_position),
-            ],
-            Direction::East => vec![
-                next_position,
-                Direction::North.next_position(&next_position),
-                Direction::South.next_position(&next_position),
-            ],
-        }
-    }
-}
-
 pub type Elves = HashSet<Position>;
 
-fn is_direction_clear(pos: &Position, direction: &Direction, elves: &Elves) -> bool {
-    direction
-        .scan_position(pos)
-        .iter()
-        .all(|pos| !elves.contains(pos))
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Proposal {
-    Ok(Position),
-    Conflict,
-}
-
-fn has_neighbours(pos: &Position, elves: &HashSet<Position>) -> bool {
-    (-1..=1).any(|dx| 
-        (-1..=1).any(|dy|  {
-            let n = *pos + Position::new(dx, dy);
-            !(dx == 0 && dy == 0) && elves.contains(&n)
-        }))
-}
-
-pub fn do_round(elves: &Elves, first_dir: &Direction) -> Elves {
-    let mut to_be: HashMap<Position, Proposal> = HashMap::with_capacity(elves.len());
-
-    // create a proposal for each elf to move in the direction
-    // that is clear starting with the first direction
-    elves
+// an elf moves in a direction when the three cells on that side are free, trying north, south,
+// west and east in turn
+pub fn elf_rules() -> Vec<Rule> {
+    [Direction::North, Direction::South, Direction::West, Direction::East]
         .iter()
-        .filter(|pos| has_neighbours(pos, elves))
-        .filter_map(|pos| {
-            let mut dir = *first_dir;
-            (0..=3).find_map(|_| {
-                if is_direction_clear(pos, &dir, elves) {
-                    Some((dir.next_position(pos), *pos))
-                } else {
-                    dir = dir.next_direction();
-                    None
-                }
-            })
+        .map(|direction| {
+            let step = Position::from(direction);
+            Rule {
+                step,
+                scan: vec![
+                    step,
+                    step + Position::from(direction.turn_left()),
+                    step + Position::from(direction.turn_right()),
+                ],
+            }
         })
-        .for_each(|(proposal, pos)| {
-            to_be
-                .entry(proposal)
-                .and_modify(|p| {
-                    *p = Proposal::Conflict;
-                })
-                .or_insert(Proposal::Ok(pos));
-        });
-
-    let mut elves = elves.clone();
-    to_be.into_iter().for_each(|(new_pos, proposal)| {
-        if let Proposal::Ok(pos) = proposal {
-            elves.remove(&pos);
-            elves.insert(new_pos);
-        }
-    });
-    elves
+        .collect()
 }
 
-fn total_area(elves: &Elves) -> i32 {
-    let min_x = i32::MAX;
-    let max_x = i32::MIN;
-    let min_y = i32::MAX;
-    let max_y = i32::MIN;
-
-    let (min_x, max_x, min_y, max_y) = elves.iter().fold(
-        (min_x, max_x, min_y, max_y),
-        |(min_x, max_x, min_y, max_y), pos| {
-            (
-                min_x.min(pos.x),
-                max_x.max(pos.x),
-                min_y.min(pos.y),
-                max_y.max(pos.y),
-            )
-        },
-    );
-
-    (max_x - min_x + 1) * (max_y - min_y + 1)
-}
-
-pub fn count_free_space(elves: &Elves) -> usize {
-    total_area(elves) as usize - elves.len()
-}
-
-pub fn count_rounds_till_stable(elves: &Elves) -> usize {
-    std::iter::successors(Some((elves.clone(), Direction::North)), |(elves, dir)| {
-        let next_elves = do_round(elves, dir);
-        if next_elves == *elves {
-            None
-        } else {
-            Some((next_elves, dir.next_direction()))
-        }
-    }).count()
+pub fn diffusion(elves: &Elves) -> Automaton {
+    Automaton::new(elves.iter().copied(), elf_rules())
 }
 
 #[cfg(test)]
@@ -250,48 +125,65 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
-    #[test]
-    fn test_rounds() {
-        let sequence = TEST_SEQUENCE
+    // every picture should follow from the one before it in a single round
+    fn check_sequence(pictures: &[&str]) {
+        let sequence = pictures
             .iter()
-            .map(|s| s.parse::<InputModel>().unwrap())
-            .map(|m| m.elves);
-        let pairs = sequence.clone().zip(sequence.skip(1));
+            .map(|s| s.parse::<InputModel>().unwrap().elves)
+            .collect::<Vec<_>>();
 
-        let mut direction = Direction::North;
-        for (actual, expected) in pairs {
-            let actual = do_round(&actual, &direction);
-            assert_eq!(actual, expected);
+        let mut automaton = diffusion(&sequence[0]);
+        for (before, expected) in sequence.iter().zip(&sequence[1..]) {
+            let stats = automaton.step();
 
-            direction = direction.next_direction();
+            assert_eq!(automaton.cells().collect::<Elves>(), *expected);
+            assert_eq!(stats.moved, before.difference(expected).count());
+            assert_eq!(stats.bounds, diffusion(expected).bounds());
         }
     }
 
     #[test]
-    fn test_large_rounds() {
-        let sequence = LARGE_TEST_SEQUENCE
-            .iter()
-            .map(|s| s.parse::<InputModel>().unwrap())
-            .map(|m| m.elves);
-        let pairs = sequence.clone().zip(sequence.skip(1));
+    fn test_rounds() {
+        check_sequence(TEST_SEQUENCE);
+    }
 
-        let mut direction = Direction::North;
-        for (actual, expected) in pairs {
-            let actual = do_round(&actual, &direction);
-            let mut actual = actual.iter().cloned().collect::<Vec<_>>();
-            actual.sort();
-            let mut expected = expected.iter().cloned().collect::<Vec<_>>();
-            expected.sort();
-            assert_eq!(actual, expected);
+    #[test]
+    fn test_large_rounds() {
+        check_sequence(LARGE_TEST_SEQUENCE);
+    }
 
-            direction = direction.next_direction();
-        }
+    #[test]
+    fn test_run_until_stable() {
+        let mut automaton = diffusion(&input_data().elves);
+
+        assert_eq!(automaton.run_until_stable(), 4);
+        assert_eq!(
+            automaton.history().iter().map(|stats| stats.moved).collect::<Vec<_>>(),
+            vec![3, 5, 3, 0]
+        );
+        assert_eq!(automaton.to_string(), "..#..\n....#\n#....\n....#\n.....\n..#..\n");
+        assert_eq!(automaton.empty_ground(), 25);
     }
 
     #[test]
-    fn test_count_rounds_till_stable() {
-        let actual = count_rounds_till_stable(&input_data().elves);
-        assert_eq!(actual, 4);
+    fn test_grid_grows() {
+        // a pair that keeps each other moving east
+        let rules = vec![
+            Rule {
+                step: Position::new(1, 0),
+                scan: [(1, 0), (1, -1), (1, 1), (0, -1)].map(Position::from).to_vec(),
+            },
+            Rule {
+                step: Position::new(1, 0),
+                scan: [(1, 0), (1, -1), (1, 1), (0, 1)].map(Position::from).to_vec(),
+            },
+        ];
+        let mut automaton = Automaton::new([Position::new(0, 0), Position::new(0, 1)], rules);
+
+        assert!(automaton.run(50).iter().all(|stats| stats.moved == 2));
+        assert_eq!(automaton.bounds(), Some((Position::new(50, 0), Position::new(50, 1))));
+        assert!(automaton.contains(Position::new(50, 1)));
+        assert_eq!(automaton.len(), 2);
     }
 
     const LARGE_TEST_SEQUENCE: &[&str] = &[
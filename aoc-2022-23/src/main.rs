@@ -1,24 +1,17 @@
 #![feature(test)]
-use std::iter::successors;
-
 use aoc_2022_23::*;
 
 const INPUT: &str = include_str!("../data/input.txt");
 
 
-fn part1(_input: &InputModel) -> Result<String,AocError> {
-    let directions = successors(Some(Direction::North), 
-                                |d| Some(d.next_direction()));
-
-    let elves = directions
-        .take(10)
-        .fold(_input.elves.clone(), |elves, direction| do_round(&elves, &direction));
-
-    Ok(count_free_space(&elves).to_string())
+fn part1(input: &InputModel) -> Result<String,AocError> {
+    let mut elves = diffusion(&input.elves);
+    elves.run(10);
+    Ok(elves.empty_ground().to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
-    Ok(count_rounds_till_stable(&input.elves).to_string())
+    Ok(diffusion(&input.elves).run_until_stable().to_string())
 }
 
 fn main() -> Result<(), AocError> {
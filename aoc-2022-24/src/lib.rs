@@ -1,10 +1,20 @@
-use aoc_common::position::Position;
+use aoc_common::{bitgrid::BitGrid, direction, position::Position};
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::HashSet,
     fmt::{Display, Formatter},
     str::FromStr,
 };
 
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The valley with the blizzards of every minute of their period precomputed, so it can be
+/// shared between searches running on several threads.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Valley {
     pub start: Position,
@@ -12,49 +22,126 @@ pub struct Valley {
     width: i32,
     height: i32,
     blizzards: Vec<Blizzard>,
-    blizzard_cache: HashMap<i32, HashSet<Position>>,
+    // the cells which are not walls
+    open: BitGrid,
+    // the blizzards of each minute, they are back where they started after the period
+    occupancy: Vec<BitGrid>,
 }
 
 impl Valley {
-    pub(crate) fn blizzards_positions(&mut self, i: i32) -> HashSet<Position> {
-        let blizzards = self.blizzard_cache.get(&i);
-        if let Some(positions) = blizzards {
-            return positions.clone();
+    fn new(
+        start: Position,
+        finish: Position,
+        width: i32,
+        height: i32,
+        blizzards: Vec<Blizzard>,
+    ) -> Valley {
+        let mut open = BitGrid::new(width as usize, height as usize);
+        (1..height - 1)
+            .flat_map(|y| (1..width - 1).map(move |x| Position::new(x, y)))
+            .chain([start, finish])
+            .for_each(|p| {
+                open.set(p, true);
+            });
+
+        // rotate the blizzards of each direction on the floor, then put the floor back between the walls
+        let (floor_width, floor_height) = ((width - 2) as usize, (height - 2) as usize);
+        let period = floor_width / gcd(floor_width, floor_height) * floor_height;
+        let lanes = [Direction::Up, Direction::Right, Direction::Down, Direction::Left].map(|d| {
+            let mut lane = BitGrid::new(floor_width, floor_height);
+            blizzards
+                .iter()
+                .filter(|b| b.direction == d)
+                .for_each(|b| {
+                    lane.set(Position::new(b.start.x - 1, b.start.y - 1), true);
+                });
+            (d.into(), lane)
+        });
+        let occupancy = (0..period)
+            .map(|time| {
+                let floor = lanes
+                    .iter()
+                    .map(|(d, lane)| lane.rotate(*d, time))
+                    .fold(BitGrid::new(floor_width, floor_height), |acc, lane| &acc | &lane);
+                let mut grid = BitGrid::new(width as usize, height as usize);
+                floor.iter().for_each(|p| {
+                    grid.set(Position::new(p.x + 1, p.y + 1), true);
+                });
+                grid
+            })
+            .collect();
+
+        Valley {
+            start,
+            finish,
+            width,
+            height,
+            blizzards,
+            open,
+            occupancy,
         }
-        let blizzards = self._blizzards_positions(i);
-        self.blizzard_cache.insert(i, blizzards.clone());
-        blizzards
     }
 
-    fn _blizzards_positions(&self, i: i32) -> HashSet<Position> {
-        self.blizzards
-            .iter()
-            .map(|b| self.blizzard_position(b, i))
-            .collect()
-    }
-
-    fn blizzard_position(&self, blizzard: &Blizzard, time: i32) -> Position {
-        let (mut x, mut y) = (blizzard.start.x, blizzard.start.y);
-        let floor_height = self.height - 2;
-        let floor_width = self.width - 2;
-        match blizzard.direction {
-            Direction::Up => y = (y - time - 1).rem_euclid(floor_height) + 1,
-            Direction::Down => y = (y + time - 1).rem_euclid(floor_height) + 1,
-            Direction::Left => x = (x - time - 1).rem_euclid(floor_width) + 1,
-            Direction::Right => x = (x + time - 1).rem_euclid(floor_width) + 1,
-        }
-        (x, y).into()
+    pub fn period(&self) -> usize {
+        self.occupancy.len()
     }
 
-    fn is_free(&mut self, pos: Position, time: i32) -> bool {
-        // check for walls
-        (pos.x > 0 && pos.x < self.width - 1
-         && pos.y > 0 && pos.y < self.height - 1
-         // check for blizzards
-         && !self.blizzards_positions(time).contains(&pos))
-        // except start and finish
-        || pos == self.start
-        || pos == self.finish
+    pub fn blizzards_at(&self, time: i32) -> &BitGrid {
+        &self.occupancy[time.rem_euclid(self.period() as i32) as usize]
+    }
+
+    // not a wall and no blizzard there at the time, the start and finish are never hit
+    pub fn is_free(&self, pos: Position, time: i32) -> bool {
+        self.open.get(pos) && !self.blizzards_at(time).get(pos)
+    }
+
+    /// The fastest route from the start at minute 0 through the waypoints in order.
+    pub fn plan(&self, waypoints: &[Position]) -> Result<Route, AocError> {
+        Walker::new(self).plan(self, waypoints)
+    }
+
+    // every cell the expedition can be in a minute after being somewhere in the frontier
+    fn expand(&self, frontier: &BitGrid, time: i32) -> BitGrid {
+        let reachable = [
+            direction::Direction::North,
+            direction::Direction::East,
+            direction::Direction::South,
+            direction::Direction::West,
+        ]
+        .iter()
+        .fold(frontier.clone(), |acc, d| &acc | &frontier.shift(*d, 1));
+        &(&reachable & &self.open) - self.blizzards_at(time)
+    }
+
+    // spread out over the valley minute by minute until the target is reached, then walk back
+    fn leg(&self, from: Walker, to: Position) -> Result<Vec<Walker>, AocError> {
+        let mut start = BitGrid::new(self.width as usize, self.height as usize);
+        start.set(from.position, true);
+        let mut frontiers = vec![start];
+        let mut seen = HashSet::new();
+        let mut time = from.time;
+        while !frontiers[frontiers.len() - 1].get(to) {
+            let next = self.expand(&frontiers[frontiers.len() - 1], time + 1);
+            time += 1;
+            // the blizzards repeat, so a frontier seen before in the same phase never gets further
+            let phase = time.rem_euclid(self.period() as i32);
+            if next.is_empty() || !seen.insert((phase, next.clone())) {
+                return Err(AocError::Unreachable(to));
+            }
+            frontiers.push(next);
+        }
+
+        let mut position = to;
+        let mut steps = vec![Walker { time, position }];
+        for frontier in frontiers.iter().rev().skip(1) {
+            time -= 1;
+            position = Walker::moves(position)
+                .find(|p| frontier.get(*p))
+                .expect("a frontier cell is reached from the one before");
+            steps.push(Walker { time, position });
+        }
+        steps.reverse();
+        Ok(steps)
     }
 }
 
@@ -88,10 +175,14 @@ impl Display for Valley {
     }
 }
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("Waypoint {0} is not in the valley")]
+    InvalidWaypoint(Position),
+    #[error("There is no way through the blizzards to {0}")]
+    Unreachable(Position),
 }
 
 impl FromStr for Valley {
@@ -100,7 +191,10 @@ impl FromStr for Valley {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let lines = s.trim().lines().collect::<Vec<_>>();
         let height = lines.len() as i32;
-        let width = lines[0].len() as i32;
+        let width = lines.first().ok_or(AocError::ParseError)?.len() as i32;
+        if width < 3 || height < 3 {
+            return Err(AocError::ParseError);
+        }
 
         let start_x = lines[0].find('.');
         let start_x = start_x.or_else(|| lines[0].find('E'));
@@ -124,19 +218,23 @@ impl FromStr for Valley {
                 })
             })
             .collect();
-        let blizzard_cache = HashMap::with_capacity(1000);
-        Ok(Valley {
-            start: Position::new(start_x as i32, 0),
-            finish: Position::new(finish_x as i32, height - 1),
+        // blizzards can only blow over the floor
+        if blizzards.iter().any(|b| {
+            b.start.x < 1 || b.start.x > width - 2 || b.start.y < 1 || b.start.y > height - 2
+        }) {
+            return Err(AocError::ParseError);
+        }
+        Ok(Valley::new(
+            Position::new(start_x as i32, 0),
+            Position::new(finish_x as i32, height - 1),
             width,
             height,
             blizzards,
-            blizzard_cache,
-        })
+        ))
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Direction {
     Up,
     Right,
@@ -144,6 +242,17 @@ enum Direction {
     Left,
 }
 
+impl From<Direction> for direction::Direction {
+    fn from(d: Direction) -> Self {
+        match d {
+            Direction::Up => direction::Direction::North,
+            Direction::Right => direction::Direction::East,
+            Direction::Down => direction::Direction::South,
+            Direction::Left => direction::Direction::West,
+        }
+    }
+}
+
 impl FromStr for Direction {
     type Err = AocError;
 
@@ -158,6 +267,18 @@ impl FromStr for Direction {
     }
 }
 
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Direction::Up => "up",
+            Direction::Right => "right",
+            Direction::Down => "down",
+            Direction::Left => "left",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct Blizzard {
     start: Position,
@@ -183,42 +304,84 @@ impl Walker {
         }
     }
 
-    fn next_moves(&self, valley: &mut Valley) -> Vec<Position> {
-        let moves = &[(0, -1), (1, 0), (0, 1), (-1, 0), (0, 0)];
-        moves
-            .iter()
-            .map(|(dx, dy)| Position::new(self.position.x + dx, self.position.y + dy))
-            .filter(|p| valley.is_free(*p, self.time + 1))
-            .collect()
+    pub fn position(&self) -> Position {
+        self.position
     }
 
-    pub fn best_path(&self, valley: &mut Valley, finish: &Position) -> Option<Walker> {
-        let mut open = VecDeque::new();
-        let mut seen = HashSet::new();
+    // the neighbours and the position itself, for waiting
+    fn moves(position: Position) -> impl Iterator<Item = Position> {
+        [(0, -1), (1, 0), (0, 1), (-1, 0), (0, 0)]
+            .into_iter()
+            .map(move |(dx, dy)| Position::new(position.x + dx, position.y + dy))
+    }
 
-        open.push_back(*self);
-        while let Some(walker) = open.pop_front() {
-            if walker.position == *finish {
-                return Some(walker);
-            }
-            if walker.time > 20000 {
-                return None;
-            }
+    /// The fastest route from here through the waypoints in order.
+    pub fn plan(&self, valley: &Valley, waypoints: &[Position]) -> Result<Route, AocError> {
+        if let Some(waypoint) = waypoints.iter().find(|w| !valley.open.get(**w)) {
+            return Err(AocError::InvalidWaypoint(*waypoint));
+        }
+        let mut steps = vec![*self];
+        let mut arrivals = vec![];
+        for waypoint in waypoints {
+            let leg = valley.leg(steps[steps.len() - 1], *waypoint)?;
+            steps.extend_from_slice(&leg[1..]);
+            arrivals.push(steps.len() - 1);
+        }
+        Ok(Route { steps, arrivals })
+    }
 
-            for next_position in walker.next_moves(valley) {
-                let next = Walker {
-                    time: walker.time + 1,
-                    position: next_position,
-                };
-                if seen.contains(&next) {
-                    continue;
-                } else {
-                    open.push_back(next);
-                    seen.insert(next);
-                }
+    pub fn best_path(&self, valley: &Valley, finish: &Position) -> Option<Walker> {
+        self.plan(valley, &[*finish])
+            .ok()
+            .map(|route| route.end())
+    }
+}
+
+/// A timed route through the valley, one step per minute.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Route {
+    steps: Vec<Walker>,
+    // the index of the step each waypoint is reached in
+    arrivals: Vec<usize>,
+}
+
+impl Route {
+    pub fn steps(&self) -> &[Walker] {
+        &self.steps
+    }
+
+    pub fn end(&self) -> Walker {
+        self.steps[self.steps.len() - 1]
+    }
+
+    // the minute the route ends in
+    pub fn time(&self) -> i32 {
+        self.end().time
+    }
+
+    pub fn arrivals(&self) -> impl Iterator<Item = &Walker> + '_ {
+        self.arrivals.iter().map(|i| &self.steps[*i])
+    }
+}
+
+impl Display for Route {
+    // the moves the way the puzzle describes them
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for step in self.steps.windows(2) {
+            let (from, to) = (step[0].position, step[1].position);
+            let direction = match (to.x - from.x, to.y - from.y) {
+                (0, -1) => Some(Direction::Up),
+                (1, 0) => Some(Direction::Right),
+                (0, 1) => Some(Direction::Down),
+                (-1, 0) => Some(Direction::Left),
+                _ => None,
+            };
+            match direction {
+                Some(d) => writeln!(f, "Minute {}, move {}", step[1].time, d)?,
+                None => writeln!(f, "Minute {}, wait", step[1].time)?,
             }
         }
-        None
+        Ok(())
     }
 }
 
@@ -235,82 +398,38 @@ mod tests {
 #.....#
 #####.#";
 
-    impl Valley {
-        fn print_at_time(&self, time: i32) {
-            let mut lines = vec![vec!['.'; self.width as usize]; self.height as usize];
-            (0..self.width as usize).for_each(|x| {
-                lines[0][x] = '#';
-                lines[self.height as usize - 1][x] = '#';
-            });
-            (0..self.height as usize).for_each(|y| {
-                lines[y][0] = '#';
-                lines[y][self.width as usize - 1] = '#';
-            });
-            lines[self.start.y as usize][self.start.x as usize] = '.';
-            lines[self.finish.y as usize][self.finish.x as usize] = '.';
-            self.blizzards.iter().for_each(|b| {
-                let symbol = match b.direction {
-                    Direction::Up => '^',
-                    Direction::Down => 'v',
-                    Direction::Right => '>',
-                    Direction::Left => '<',
-                };
-                let pos = self.blizzard_position(b, time);
-                lines[pos.y as usize][pos.x as usize] = symbol;
-            });
+    // the cells the walker can be in a minute later, as the search spreads out
+    fn next_moves(valley: &Valley, walker: &Walker) -> Vec<Position> {
+        let mut frontier = BitGrid::new(valley.width as usize, valley.height as usize);
+        frontier.set(walker.position, true);
+        let mut moves = valley.expand(&frontier, walker.time + 1).iter().collect::<Vec<_>>();
+        moves.sort();
+        moves
+    }
 
-            for line in lines {
-                println!("{}", line.iter().collect::<String>());
-            }
-        }
+    // the blizzards of the minute as `*`, whatever their direction or number, and the walker
+    fn frame(valley: &Valley, walker: &Walker) -> String {
+        (0..valley.height)
+            .map(|y| {
+                (0..valley.width)
+                    .map(|x| {
+                        let pos = Position::new(x, y);
+                        if pos == walker.position {
+                            'E'
+                        } else if valley.blizzards_at(walker.time).get(pos) {
+                            '*'
+                        } else if valley.open.get(pos) {
+                            '.'
+                        } else {
+                            '#'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .fold("".to_string(), |acc, s| acc + "\n" + &s)
     }
 
     impl Walker {
-        fn format_valley(&self, valley: &Valley) -> String {
-            let mut lines = vec![vec!['.'; valley.width as usize]; valley.height as usize];
-            (0..valley.width as usize).for_each(|x| {
-                lines[0][x] = '#';
-                lines[valley.height as usize - 1][x] = '#';
-            });
-            (0..valley.height as usize).for_each(|y| {
-                lines[y][0] = '#';
-                lines[y][valley.width as usize - 1] = '#';
-            });
-            lines[valley.start.y as usize][valley.start.x as usize] = '.';
-            lines[valley.finish.y as usize][valley.finish.x as usize] = '.';
-
-            valley.blizzards.iter().for_each(|b| {
-                let symbol = match b.direction {
-                    Direction::Up => '^',
-                    Direction::Down => 'v',
-                    Direction::Right => '>',
-                    Direction::Left => '<',
-                };
-                let pos = valley.blizzard_position(b, self.time);
-                let (x, y) = pos.into();
-                let (x, y) = (x as usize, y as usize);
-
-                lines[y][x] = match lines[y][x] {
-                    '.' => symbol,
-                    '2' => '3',
-                    '3' => '4',
-                    '^' => '2',
-                    'v' => '2',
-                    '>' => '2',
-                    '<' => '2',
-                    _ => lines[y][x],
-                }
-            });
-
-            // add the walker
-            lines[self.position.y as usize][self.position.x as usize] = 'E';
-
-            lines
-                .iter()
-                .map(|line| line.iter().collect::<String>())
-                .fold("".to_string(), |acc, s| acc + "\n" + &s)
-        }
-
         pub(crate) fn walk(&self, dir: &Direction) -> Walker {
             let (dx, dy) = match dir {
                 Direction::Up => (0, -1),
@@ -336,18 +455,18 @@ mod tests {
     fn test_parse() {
         let input = SIMPLE_INPUT;
         let model = input.parse::<Valley>().unwrap();
-        let expected = Valley {
-            start: Position::new(1, 0),
-            finish: Position::new(5, 6),
-            width: 7,
-            height: 7,
-            blizzards: vec![
+        let expected = Valley::new(
+            Position::new(1, 0),
+            Position::new(5, 6),
+            7,
+            7,
+            vec![
                 Blizzard::new(Position::new(1, 2), Direction::Right),
                 Blizzard::new(Position::new(4, 4), Direction::Down),
             ],
-            blizzard_cache: HashMap::new(),
-        };
+        );
         assert_eq!(model, expected);
+        assert_eq!(model.period(), 5);
     }
 
     #[test]
@@ -361,7 +480,7 @@ mod tests {
     #[test]
     fn test_blizzard_position() {
         let input = SIMPLE_INPUT;
-        let mut model = input.parse::<Valley>().unwrap();
+        let model = input.parse::<Valley>().unwrap();
         let expected = vec![
             vec![Position::new(1, 2), Position::new(4, 4)],
             vec![Position::new(2, 2), Position::new(4, 5)],
@@ -379,14 +498,14 @@ mod tests {
             .map(|v| v.into_iter().collect::<HashSet<_>>())
             .collect::<Vec<_>>();
         let actual = (0..10)
-            .map(|i| model.blizzards_positions(i))
+            .map(|i| model.blizzards_at(i).iter().collect::<HashSet<_>>())
             .collect::<Vec<_>>();
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn test_has_blizzard() {
-        let mut model = SIMPLE_INPUT.parse::<Valley>().unwrap();
+        let model = SIMPLE_INPUT.parse::<Valley>().unwrap();
         assert!(!model.is_free(Position::new(1, 2), 0));
         assert!(model.is_free(Position::new(1, 2), 1));
         assert!(!model.is_free(Position::new(2, 2), 1));
@@ -395,29 +514,28 @@ mod tests {
 
     #[test]
     fn test_walker_moves() {
-        let mut model = SIMPLE_INPUT.parse::<Valley>().unwrap();
+        let model = SIMPLE_INPUT.parse::<Valley>().unwrap();
         let expected = vec![Position::new(1, 0), Position::new(1, 1)];
         let walker = Walker::new(&model);
-        let mut actual = walker.next_moves(&mut model);
-        actual.sort();
+        let actual = next_moves(&model, &walker);
         assert_eq!(actual, expected);
         let walker = walker.walk(&Direction::Down);
-        let actual = walker.next_moves(&mut model);
+        let actual = next_moves(&model, &walker);
         let expected = vec![
             Position::new(1, 0),
-            Position::new(2, 1),
-            Position::new(1, 2),
             Position::new(1, 1),
+            Position::new(1, 2),
+            Position::new(2, 1),
         ];
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn test_shortest_path() {
-        let mut model = SIMPLE_INPUT.parse::<Valley>().unwrap();
+        let model = SIMPLE_INPUT.parse::<Valley>().unwrap();
         let walker = Walker::new(&model);
         let finish = model.finish;
-        let actual = walker.best_path(&mut model, &finish);
+        let actual = walker.best_path(&model, &finish);
         assert_eq!(actual.unwrap().time, 10);
     }
 
@@ -430,25 +548,21 @@ mod tests {
 
     #[test]
     fn test_complex_shortest_path() {
-        let mut model = COMPLEX_INPUT.parse::<Valley>().unwrap();
+        let model = COMPLEX_INPUT.parse::<Valley>().unwrap();
         let walker = Walker::new(&model);
         let finish = model.finish;
-        let actual = walker.best_path(&mut model, &finish).unwrap().time;
+        let actual = walker.best_path(&model, &finish).unwrap().time;
         assert_eq!(actual, 18);
     }
 
     #[test]
     fn test_complex_next_moves() {
-        let mut model = COMPLEX_INPUT.parse::<Valley>().unwrap();
+        let model = COMPLEX_INPUT.parse::<Valley>().unwrap();
         let walker = Walker {
             time: 1,
             position: Position::new(1, 0),
         };
-        model.print_at_time(0);
-        model.print_at_time(1);
-        model.print_at_time(2);
-        let mut actual = walker.next_moves(&mut model);
-        actual.sort();
+        let actual = next_moves(&model, &walker);
         let expected = vec![Position::new(1, 0), Position::new(1, 1)];
         assert_eq!(actual, expected);
     }
@@ -630,35 +744,110 @@ Minute 18, move down:
             .collect();
 
         let initial = expected[0].2.clone();
-        let mut model = initial.parse::<Valley>().unwrap();
+        let model = initial.parse::<Valley>().unwrap();
         let mut walker = Walker {
             time: 0,
             position: model.start,
         };
-        let mut next_positions = walker.next_moves(&mut model);
+        let mut next_positions = next_moves(&model, &walker);
         expected
             .iter()
             .enumerate()
-            .for_each(|(i, (_, direction, grid))| {
+            .for_each(|(i, (time, direction, grid))| {
                 if i > 0 {
-                    if let Some(dir) = direction {
-                        walker = walker.walk(dir);
-                        assert!(
-                            next_positions.contains(&walker.position),
-                            "walker should be in next positions"
-                        );
-                        next_positions = walker.next_moves(&mut model);
-                    } else {
-                        walker = walker.wait();
-                        assert!(
-                            next_positions.contains(&walker.position),
-                            "walker should be in next positions"
-                        );
-                        next_positions = walker.next_moves(&mut model);
-                    }
+                    walker = match direction {
+                        Some(dir) => walker.walk(dir),
+                        None => walker.wait(),
+                    };
+                    assert!(
+                        next_positions.contains(&walker.position),
+                        "walker should be in next positions"
+                    );
+                    next_positions = next_moves(&model, &walker);
                 }
-                let actual = walker.format_valley(&model);
-                assert_eq!(&actual, grid);
+                assert_eq!(walker.time, *time);
+                let expected = grid
+                    .chars()
+                    .map(|c| match c {
+                        '#' | '.' | 'E' | '\n' => c,
+                        _ => '*',
+                    })
+                    .collect::<String>();
+                assert_eq!(frame(&model, &walker), expected, "minute {time}");
             });
     }
+
+    #[test]
+    fn test_period() {
+        let model = COMPLEX_INPUT.parse::<Valley>().unwrap();
+
+        assert_eq!(model.period(), 12);
+        assert_eq!(model.blizzards_at(12), model.blizzards_at(0));
+        assert_eq!(model.blizzards_at(-1), model.blizzards_at(11));
+        // two blizzards can share a cell
+        assert_eq!(model.blizzards_at(0).count(), 19);
+        assert_eq!(model.blizzards_at(1).count(), 14);
+    }
+
+    #[test]
+    fn test_plan_waypoints() {
+        let model = COMPLEX_INPUT.parse::<Valley>().unwrap();
+        let route = model.plan(&[model.finish, model.start, model.finish]).unwrap();
+        let arrivals = route.arrivals().map(|w| (w.time, w.position())).collect::<Vec<_>>();
+
+        assert_eq!(arrivals, vec![(18, model.finish), (41, model.start), (54, model.finish)]);
+        assert_eq!(route.steps().len(), 55);
+        // every step is a move or a wait onto a free cell
+        for (time, step) in route.steps().iter().enumerate() {
+            assert_eq!(step.time, time as i32);
+            assert!(model.is_free(step.position(), step.time));
+        }
+        for step in route.steps().windows(2) {
+            assert!(step[0].position().manhattan(&step[1].position()) <= 1);
+        }
+    }
+
+    #[test]
+    fn test_route_display() {
+        let model = COMPLEX_INPUT.parse::<Valley>().unwrap();
+        let route = model.plan(&[model.finish]).unwrap();
+        let actual = route.to_string();
+
+        assert_eq!(actual.lines().count(), 18);
+        for (minute, line) in actual.lines().enumerate() {
+            let (head, action) = line.split_once(", ").unwrap();
+            assert_eq!(head, format!("Minute {}", minute + 1));
+            assert!(action == "wait" || action.strip_prefix("move ").unwrap().parse::<Direction>().is_ok());
+        }
+        // the finish can only be entered from above
+        assert!(actual.ends_with("Minute 18, move down\n"));
+    }
+
+    #[test]
+    fn test_invalid_plans() {
+        let model = SIMPLE_INPUT.parse::<Valley>().unwrap();
+
+        assert_eq!(model.plan(&[Position::new(0, 3)]), Err(AocError::InvalidWaypoint(Position::new(0, 3))));
+
+        // the only way on is blocked by a blizzard blowing up and down all the time
+        let blocked = "#.#\n#^#\n#.#".parse::<Valley>().unwrap();
+        assert_eq!(blocked.plan(&[blocked.finish]), Err(AocError::Unreachable(blocked.finish)));
+    }
+
+    #[test]
+    fn test_shared_valley() {
+        let model = COMPLEX_INPUT.parse::<Valley>().unwrap();
+        let trips = [vec![model.finish], vec![model.finish, model.start, model.finish]];
+        let times = std::thread::scope(|s| {
+            trips
+                .iter()
+                .map(|trip| s.spawn(|| model.plan(trip).map(|r| r.time())))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|h| h.join().unwrap().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(times, vec![18, 54]);
+    }
 }
@@ -1,5 +1,9 @@
 use std::str::FromStr;
 
+use numeral::SnafuNumber;
+
+pub mod numeral;
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel  {
     pub lines: Vec<String>,
@@ -9,6 +13,10 @@ pub struct InputModel  {
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("Invalid digit {0:?}")]
+    InvalidDigit(char),
+    #[error("{0} does not fit in 64 bits")]
+    Overflow(String),
 }
         
 impl FromStr for InputModel {
@@ -22,42 +30,20 @@ impl FromStr for InputModel {
 }
 
 
-pub fn snafu_decimal(snafu: &str) -> i64  {
-   snafu.chars()
-       .map(|c| match c {
-           '=' => -2,
-           '-' => -1,
-           '0' => 0,
-           '1' => 1,
-           '2' => 2,
-           _ => panic!("Invalid character"),
-       })
-       .fold(0, |acc, x| acc * 5 + x)
+pub fn snafu_decimal(snafu: &str) -> Result<i64, AocError> {
+    i64::try_from(&snafu.parse::<SnafuNumber>()?)
 }
 
 pub fn decimal_snafu(decimal: i64) -> String {
-    match decimal {
-        -2 => "=".to_string(),
-        -1 => "-".to_string(),
-        0 => "0".to_string(),
-        1 => "1".to_string(),
-        2 => "2".to_string(),
-        _ => {
-            let div = decimal.div_euclid(5);
-            let rem = decimal.rem_euclid(5);
-            if rem > 2  {
-                decimal_snafu(div + 1) + &decimal_snafu(rem - 5)
-            } else {
-                decimal_snafu(div) + &decimal_snafu(rem)
-            }
-        }
-    }
+    SnafuNumber::from(decimal).to_string()
 }
 
-pub fn sum_snafu(snafus: &[String]) -> i64 {
-    snafus.iter()
-        .map(|s| snafu_decimal(s))
-        .sum::<i64>()
+// add up without going through i64, so the sum can be any length
+pub fn sum_snafu(snafus: &[String]) -> Result<SnafuNumber, AocError> {
+    snafus
+        .iter()
+        .map(|s| s.parse::<SnafuNumber>())
+        .sum()
 }
 
 #[cfg(test)]
@@ -86,7 +72,7 @@ mod tests {
     #[test]
     fn test_snafu_decimal() {
         for (decimal, snafu) in TEST_CASES {
-            assert_eq!(snafu_decimal(snafu), decimal.parse::<i64>().unwrap());
+            assert_eq!(snafu_decimal(snafu).unwrap(), decimal.parse::<i64>().unwrap());
         }
     }
 
@@ -94,7 +80,6 @@ mod tests {
     fn test_decimal_snafu() {
         for (decimal, snafu) in TEST_CASES {
             let decimal = decimal.parse::<i64>().unwrap();
-            println!("{} {}", decimal, snafu);
             assert_eq!(decimal_snafu(decimal), *snafu);
         }
     }
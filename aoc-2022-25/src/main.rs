@@ -5,8 +5,8 @@ const INPUT: &str = include_str!("../data/input.txt");
 
 
 fn part1(input: &InputModel) -> Result<String,AocError> {
-    let sum = sum_snafu(&input.lines);
-    Ok(decimal_snafu(sum))
+    let sum = sum_snafu(&input.lines)?;
+    Ok(sum.to_string())
 }
 
 fn part2(_input: &InputModel) -> Result<String, AocError> {
@@ -0,0 +1,342 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+    iter::Sum,
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use crate::AocError;
+
+/// The digits of a balanced numeral system, lowest value first.
+///
+/// The base is the number of digits, which has to be odd so the digits are centred on zero.
+pub trait Alphabet {
+    const DIGITS: &'static [char];
+
+    // fails the build for an alphabet whose digits are not centred on zero or do not fit an `i8`
+    const BALANCED: () = assert!(
+        Self::DIGITS.len() % 2 == 1 && Self::DIGITS.len() <= 255,
+        "a balanced alphabet needs an odd number of digits, at most 255"
+    );
+
+    fn base() -> i64 {
+        let () = Self::BALANCED;
+        Self::DIGITS.len() as i64
+    }
+
+    // the value of the lowest digit is minus this
+    fn half() -> i64 {
+        Self::base() / 2
+    }
+
+    fn value(c: char) -> Option<i8> {
+        Self::DIGITS
+            .iter()
+            .position(|d| *d == c)
+            .map(|i| (i as i64 - Self::half()) as i8)
+    }
+
+    fn symbol(value: i8) -> char {
+        Self::DIGITS[(value as i64 + Self::half()) as usize]
+    }
+}
+
+/// Balanced base 5 with `=` and `-` for minus two and minus one.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Snafu;
+
+impl Alphabet for Snafu {
+    const DIGITS: &'static [char] = &['=', '-', '0', '1', '2'];
+}
+
+/// Balanced base 3 with the digits `-`, `0` and `+`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct BalancedTernary;
+
+impl Alphabet for BalancedTernary {
+    const DIGITS: &'static [char] = &['-', '0', '+'];
+}
+
+/// A number of any length written in a balanced numeral system.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Numeral<A: Alphabet> {
+    // least significant first, without leading zeros so zero has no digits
+    digits: Vec<i8>,
+    alphabet: PhantomData<A>,
+}
+
+pub type SnafuNumber = Numeral<Snafu>;
+
+impl<A: Alphabet> Numeral<A> {
+    pub fn zero() -> Self {
+        Numeral {
+            digits: vec![],
+            alphabet: PhantomData,
+        }
+    }
+
+    // bring column sums back into digit range by carrying into the next column
+    fn normalize(columns: Vec<i64>) -> Self {
+        let (base, half) = (A::base(), A::half());
+        let mut digits = Vec::with_capacity(columns.len() + 1);
+        let mut carry = 0;
+        let mut columns = columns.into_iter();
+        loop {
+            let value = match columns.next() {
+                Some(column) => column + carry,
+                None if carry != 0 => carry,
+                None => break,
+            };
+            let digit = (value + half).rem_euclid(base) - half;
+            carry = (value - digit) / base;
+            digits.push(digit as i8);
+        }
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Numeral {
+            digits,
+            alphabet: PhantomData,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    // the sign is the sign of the most significant digit
+    pub fn signum(&self) -> i8 {
+        self.digits.last().map_or(0, |d| d.signum())
+    }
+
+    fn zip_columns(&self, other: &Self, op: impl Fn(i64, i64) -> i64) -> Self {
+        let n = self.digits.len().max(other.digits.len());
+        let digit = |digits: &[i8], i: usize| digits.get(i).copied().unwrap_or(0) as i64;
+        Self::normalize(
+            (0..n)
+                .map(|i| op(digit(&self.digits, i), digit(&other.digits, i)))
+                .collect(),
+        )
+    }
+}
+
+impl<A: Alphabet> Default for Numeral<A> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<A: Alphabet> From<i64> for Numeral<A> {
+    fn from(value: i64) -> Self {
+        let (base, half) = (A::base() as i128, A::half() as i128);
+        let mut value = value as i128;
+        let mut digits = vec![];
+        while value != 0 {
+            let digit = (value + half).rem_euclid(base) - half;
+            digits.push(digit as i8);
+            value = (value - digit) / base;
+        }
+        Numeral {
+            digits,
+            alphabet: PhantomData,
+        }
+    }
+}
+
+impl<A: Alphabet> TryFrom<&Numeral<A>> for i64 {
+    type Error = AocError;
+
+    fn try_from(numeral: &Numeral<A>) -> Result<Self, Self::Error> {
+        numeral.digits.iter().rev().try_fold(0i64, |acc, d| {
+            acc.checked_mul(A::base())
+                .and_then(|acc| acc.checked_add(*d as i64))
+                .ok_or_else(|| AocError::Overflow(numeral.to_string()))
+        })
+    }
+}
+
+impl<A: Alphabet> FromStr for Numeral<A> {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(AocError::ParseError);
+        }
+        let digits = s
+            .chars()
+            .rev()
+            .map(|c| A::value(c).ok_or(AocError::InvalidDigit(c)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::normalize(digits.into_iter().map(i64::from).collect()))
+    }
+}
+
+impl<A: Alphabet> Display for Numeral<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "{}", A::symbol(0));
+        }
+        let s = self.digits.iter().rev().map(|d| A::symbol(*d)).collect::<String>();
+        write!(f, "{}", s)
+    }
+}
+
+impl<A: Alphabet> Add for &Numeral<A> {
+    type Output = Numeral<A>;
+
+    fn add(self, other: Self) -> Numeral<A> {
+        self.zip_columns(other, |a, b| a + b)
+    }
+}
+
+impl<A: Alphabet> Sub for &Numeral<A> {
+    type Output = Numeral<A>;
+
+    fn sub(self, other: Self) -> Numeral<A> {
+        self.zip_columns(other, |a, b| a - b)
+    }
+}
+
+impl<A: Alphabet> Mul for &Numeral<A> {
+    type Output = Numeral<A>;
+
+    // long multiplication, the column sums are small enough to carry at the end
+    fn mul(self, other: Self) -> Numeral<A> {
+        if self.is_zero() || other.is_zero() {
+            return Numeral::zero();
+        }
+        let mut columns = vec![0i64; self.digits.len() + other.digits.len()];
+        for (i, a) in self.digits.iter().enumerate() {
+            for (j, b) in other.digits.iter().enumerate() {
+                columns[i + j] += *a as i64 * *b as i64;
+            }
+        }
+        Numeral::normalize(columns)
+    }
+}
+
+impl<A: Alphabet> Neg for &Numeral<A> {
+    type Output = Numeral<A>;
+
+    // every digit flips, there is no sign to keep
+    fn neg(self) -> Numeral<A> {
+        Numeral {
+            digits: self.digits.iter().map(|d| -d).collect(),
+            alphabet: PhantomData,
+        }
+    }
+}
+
+macro_rules! by_value {
+    ($op:ident, $method:ident) => {
+        impl<A: Alphabet> $op for Numeral<A> {
+            type Output = Numeral<A>;
+
+            fn $method(self, other: Self) -> Numeral<A> {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+by_value!(Add, add);
+by_value!(Sub, sub);
+by_value!(Mul, mul);
+
+impl<A: Alphabet> Neg for Numeral<A> {
+    type Output = Numeral<A>;
+
+    fn neg(self) -> Numeral<A> {
+        -&self
+    }
+}
+
+impl<'a, A: Alphabet + 'a> Sum<&'a Numeral<A>> for Numeral<A> {
+    fn sum<I: Iterator<Item = &'a Numeral<A>>>(iter: I) -> Self {
+        iter.fold(Numeral::zero(), |acc, n| &acc + n)
+    }
+}
+
+impl<A: Alphabet> Sum for Numeral<A> {
+    fn sum<I: Iterator<Item = Numeral<A>>>(iter: I) -> Self {
+        iter.fold(Numeral::zero(), |acc, n| &acc + &n)
+    }
+}
+
+impl<A: Alphabet + Eq> PartialOrd for Numeral<A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A: Alphabet + Eq> Ord for Numeral<A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self - other).signum().cmp(&0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    type Ternary = Numeral<BalancedTernary>;
+
+    #[test]
+    fn test_round_trip() {
+        for value in -1000..=1000 {
+            let snafu = SnafuNumber::from(value);
+            let ternary = Ternary::from(value);
+
+            assert_eq!(snafu.to_string().parse::<SnafuNumber>().unwrap(), snafu);
+            assert_eq!(i64::try_from(&snafu).unwrap(), value);
+            assert_eq!(i64::try_from(&ternary).unwrap(), value);
+        }
+        assert_eq!(Ternary::from(8).to_string(), "+0-");
+        assert_eq!(Ternary::from(-8).to_string(), "-0+");
+        assert_eq!(SnafuNumber::zero().to_string(), "0");
+        // leading zeros are dropped
+        assert_eq!("002=".parse::<SnafuNumber>().unwrap().to_string(), "2=");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let values = [-130, -25, -7, -1, 0, 1, 3, 12, 99, 2022];
+        for a in values {
+            for b in values {
+                let (x, y) = (SnafuNumber::from(a), SnafuNumber::from(b));
+
+                assert_eq!(&x + &y, SnafuNumber::from(a + b));
+                assert_eq!(&x - &y, SnafuNumber::from(a - b));
+                assert_eq!(&x * &y, SnafuNumber::from(a * b));
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+        }
+        assert_eq!(-Ternary::from(5), Ternary::from(-5));
+    }
+
+    #[test]
+    fn test_beyond_i64() {
+        let big = SnafuNumber::from(i64::MAX);
+        let square = &big * &big;
+        let back = &(&square - &big) + &big;
+
+        assert_eq!(back, square);
+        assert!(i64::try_from(&square).is_err());
+        assert_eq!(i64::try_from(&(&square - &square)).unwrap(), 0);
+        // (5^30)^2 is a one followed by sixty zeros
+        let power = SnafuNumber::from(5i64.pow(27)) * SnafuNumber::from(125);
+        assert_eq!((&power * &power).to_string(), format!("1{}", "0".repeat(60)));
+    }
+
+    #[test]
+    fn test_invalid_digits() {
+        assert!(matches!(
+            "1=3".parse::<SnafuNumber>(),
+            Err(AocError::InvalidDigit('3'))
+        ));
+        assert!(matches!("".parse::<Ternary>(), Err(AocError::ParseError)));
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{AocError, Crate, Move, Moves, Stacks};
+
+/// A crane model which knows how to carry out a rearrangement procedure step.
+pub trait Crane {
+    fn name(&self) -> &str;
+
+    // the stacks are validated before this is called
+    fn apply(&self, stacks: &mut Stacks, m: &Move) -> Result<(), AocError>;
+
+    // a move which puts the crates back where they came from
+    fn inverse(&self, m: &Move) -> Move {
+        Move {
+            amount: m.amount,
+            from: m.to,
+            to: m.from,
+        }
+    }
+}
+
+impl<C: Crane + ?Sized> Crane for &C {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn apply(&self, stacks: &mut Stacks, m: &Move) -> Result<(), AocError> {
+        (**self).apply(stacks, m)
+    }
+
+    fn inverse(&self, m: &Move) -> Move {
+        (**self).inverse(m)
+    }
+}
+
+/// Moves crates one at a time, reversing their order.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> &str {
+        "CrateMover 9000"
+    }
+
+    fn apply(&self, stacks: &mut Stacks, m: &Move) -> Result<(), AocError> {
+        for _ in 0..m.amount {
+            let c = stacks.pop(m.from)?;
+            stacks.push(m.to, c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Moves all crates at once, keeping their order.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> &str {
+        "CrateMover 9001"
+    }
+
+    fn apply(&self, stacks: &mut Stacks, m: &Move) -> Result<(), AocError> {
+        let taken = (0..m.amount)
+            .map(|_| stacks.pop(m.from))
+            .collect::<Result<Vec<Crate>, AocError>>()?;
+        for c in taken.into_iter().rev() {
+            stacks.push(m.to, c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stacks operated by a crane, keeping the history of moves for undo and redo.
+pub struct Operation<C: Crane> {
+    crane: C,
+    stacks: Stacks,
+    done: Vec<Move>,
+    undone: Vec<Move>,
+}
+
+impl<C: Crane> Operation<C> {
+    pub fn new(crane: C, stacks: Stacks) -> Self {
+        Operation {
+            crane,
+            stacks,
+            done: vec![],
+            undone: vec![],
+        }
+    }
+
+    pub fn stacks(&self) -> &Stacks {
+        &self.stacks
+    }
+
+    pub fn crane(&self) -> &C {
+        &self.crane
+    }
+
+    pub fn history(&self) -> &[Move] {
+        &self.done
+    }
+
+    // apply a move, the stacks are left untouched when it is invalid
+    pub fn apply(&mut self, m: Move) -> Result<(), AocError> {
+        self.execute(&m)?;
+        self.done.push(m);
+        self.undone.clear();
+        Ok(())
+    }
+
+    pub fn apply_all(&mut self, moves: Moves) -> Result<(), AocError> {
+        for m in moves {
+            let index = self.done.len();
+            self.apply(m.clone()).map_err(|reason| AocError::InvalidMove {
+                index,
+                m,
+                reason: Box::new(reason),
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Result<Option<&Move>, AocError> {
+        let Some(m) = self.done.pop() else {
+            return Ok(None);
+        };
+        let inverse = self.crane.inverse(&m);
+        self.execute(&inverse)?;
+        self.undone.push(m);
+        Ok(self.undone.last())
+    }
+
+    pub fn redo(&mut self) -> Result<Option<&Move>, AocError> {
+        let Some(m) = self.undone.pop() else {
+            return Ok(None);
+        };
+        self.execute(&m)?;
+        self.done.push(m);
+        Ok(self.done.last())
+    }
+
+    fn execute(&mut self, m: &Move) -> Result<(), AocError> {
+        self.stacks.validate(m)?;
+        self.crane.apply(&mut self.stacks, m)
+    }
+}
+
+impl<C: Crane> Display for Operation<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} after {} moves", self.crane.name(), self.done.len())?;
+        write!(f, "{}", self.stacks)
+    }
+}
@@ -1,7 +1,10 @@
-use std::str::FromStr;
+use std::{fmt::{Display, Formatter}, str::FromStr};
 
+use crane::{Crane, CrateMover9000, CrateMover9001};
 use nom::{IResult, Parser, bytes::complete::tag, sequence::tuple, combinator::map_res, character::complete::{digit1, line_ending, anychar}, branch::alt, multi::{separated_list1, many_till}};
 
+pub mod crane;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct InputModel  {
     pub stacks: Stacks,
@@ -18,6 +21,10 @@ pub enum AocError {
     EmptyStackError,
     #[error("No solution found")]
     NoSolution,
+    #[error("Stack {stack} holds {available} crates, {needed} needed")]
+    NotEnoughCrates { stack: usize, needed: usize, available: usize },
+    #[error("Move {index} ({m}) failed: {reason}")]
+    InvalidMove { index: usize, m: Move, reason: Box<AocError> },
 }
         
 impl FromStr for InputModel {
@@ -167,27 +174,31 @@ impl Stacks {
         Ok(())
     }
 
-    pub fn apply_move_9000(&mut self, m: Move) -> Result<(), AocError> {
-        let Move { amount, from, to } = m;
-        
-        for _ in 0..amount {
-            let c = self.pop(from)?;
-            self.push(to, c)?;
+    fn stack(&self, i: usize) -> Result<&Stack, AocError> {
+        if i == 0 {
+            return Err(AocError::IllegalStackError);
+        }
+        self.0.get(i - 1).ok_or(AocError::IllegalStackError)
+    }
+
+    // check a move can be carried out without touching the stacks
+    pub fn validate(&self, m: &Move) -> Result<(), AocError> {
+        let available = self.stack(m.from)?.len();
+        self.stack(m.to)?;
+        if available < m.amount {
+            return Err(AocError::NotEnoughCrates { stack: m.from, needed: m.amount, available });
         }
         Ok(())
     }
 
+    pub fn apply_move_9000(&mut self, m: Move) -> Result<(), AocError> {
+        self.validate(&m)?;
+        CrateMover9000.apply(self, &m)
+    }
+
     pub fn apply_move_9001(&mut self, m: Move) -> Result<(), AocError> {
-        let Move { amount, from, to } = m;
-        
-        let taken = (0..amount)
-            .map(|_| self.pop(from))
-            .collect::<Result<Vec<Crate>, AocError>>()?;
-        taken.into_iter()
-            .rev()
-            .map(|c| self.push(to, c))
-            .collect::<Result<Vec<()>, AocError>>()?;
-        Ok(())
+        self.validate(&m)?;
+        CrateMover9001.apply(self, &m)
     }
 
     pub fn heads(&self) -> Vec<Option<&Crate>> {
@@ -195,6 +206,26 @@ impl Stacks {
     }
 }
 
+// draw the stacks the way the puzzle input does
+impl Display for Stacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let height = self.0.iter().map(|s| s.len()).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self.0.iter()
+                .map(|s| match s.0.get(level) {
+                    Some(c) => format!("[{}]", c.to_char()),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        let labels = (1..=self.0.len())
+            .map(|i| format!(" {} ", i))
+            .collect::<Vec<_>>();
+        writeln!(f, "{}", labels.join(" "))
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Move {
     pub amount: usize,
@@ -218,6 +249,12 @@ impl Move {
     }
 }
 
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "move {} from {} to {}", self.amount, self.from, self.to)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Moves(Vec<Move>);
 impl Moves {
@@ -446,6 +483,87 @@ move 2 from 1 to 2").unwrap();
         let expected = vec![Crate('N'), Crate('D'),Crate('P')];
         assert_eq!(actual, expected);
     }
+
+    const DIAGRAM: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+";
+
+    fn sample_moves() -> Moves {
+        Moves(vec![
+            Move{amount: 1, from: 2, to: 1},
+            Move{amount: 3, from: 1, to: 3},
+            Move{amount: 2, from: 2, to: 1},
+            Move{amount: 1, from: 1, to: 2},
+        ])
+    }
+
+    #[test]
+    fn test_render_stacks() {
+        let (_, stacks) = Stacks::parse(DIAGRAM).unwrap();
+
+        assert_eq!(stacks.to_string(), DIAGRAM);
+    }
+
+    #[test]
+    fn test_render_round_trip_after_moves() {
+        let (_, stacks) = Stacks::parse(DIAGRAM).unwrap();
+        let mut operation = crane::Operation::new(crane::CrateMover9000, stacks);
+        operation.apply_all(sample_moves()).unwrap();
+
+        let rendered = operation.stacks().to_string();
+        let (_, reparsed) = Stacks::parse(&rendered).unwrap();
+
+        assert_eq!(&reparsed, operation.stacks());
+        assert_eq!(rendered, "        [Z]
+        [N]
+        [D]
+[C] [M] [P]
+ 1   2   3 
+");
+    }
+
+    #[test]
+    fn test_validate_reports_failing_move() {
+        let (_, stacks) = Stacks::parse(DIAGRAM).unwrap();
+        let mut operation = crane::Operation::new(crane::CrateMover9001, stacks.clone());
+        let moves = Moves(vec![
+            Move{amount: 1, from: 2, to: 1},
+            Move{amount: 5, from: 1, to: 3},
+        ]);
+
+        let actual = operation.apply_all(moves).unwrap_err();
+        let expected = AocError::InvalidMove {
+            index: 1,
+            m: Move{amount: 5, from: 1, to: 3},
+            reason: Box::new(AocError::NotEnoughCrates { stack: 1, needed: 5, available: 3 }),
+        };
+
+        assert_eq!(actual, expected);
+        assert_eq!(operation.history().len(), 1);
+        assert_eq!(stacks.validate(&Move{amount: 1, from: 4, to: 1}), Err(AocError::IllegalStackError));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        for crane in [&crane::CrateMover9000 as &dyn Crane, &crane::CrateMover9001] {
+            let (_, stacks) = Stacks::parse(DIAGRAM).unwrap();
+            let mut operation = crane::Operation::new(crane, stacks.clone());
+            operation.apply_all(sample_moves()).unwrap();
+            let finished = operation.stacks().clone();
+
+            while operation.undo().unwrap().is_some() {}
+            assert_eq!(operation.stacks(), &stacks);
+
+            operation.redo().unwrap();
+            operation.redo().unwrap();
+            operation.redo().unwrap();
+            operation.redo().unwrap();
+            assert_eq!(operation.redo().unwrap(), None);
+            assert_eq!(operation.stacks(), &finished);
+        }
+    }
 }
     #[test]
     fn test_stacks_apply_move_9001() {
@@ -1,30 +1,24 @@
 #![feature(test)]
-use aoc_2022_5::{AocError, InputModel, Crate};
+use aoc_2022_5::{AocError, InputModel, Crate, crane::{Crane, CrateMover9000, CrateMover9001, Operation}};
 
 const INPUT: &str = include_str!("../data/input.txt");
 
 
-fn part1(input: &InputModel) -> Result<String,AocError> {
-    let mut stacks = input.stacks.clone();
-    let moves = input.moves.clone();
-    moves.into_iter()
-        .map(|m| stacks.apply_move_9000(m))
-        .collect::<Result<Vec<_>,_>>()?;
-    stacks.heads().iter()
+fn operate<C: Crane>(crane: C, input: &InputModel) -> Result<String, AocError> {
+    let mut operation = Operation::new(crane, input.stacks.clone());
+    operation.apply_all(input.moves.clone())?;
+    operation.stacks().heads().iter()
         .map(|c| c.map(Crate::to_char).ok_or(AocError::NoSolution))
         .collect()
 }
+
+fn part1(input: &InputModel) -> Result<String,AocError> {
+    operate(CrateMover9000, input)
+}
     
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
-    let mut stacks = input.stacks.clone();
-    let moves = input.moves.clone();
-    moves.into_iter()
-        .map(|m| stacks.apply_move_9001(m))
-        .collect::<Result<Vec<_>,_>>()?;
-    stacks.heads().iter()
-        .map(|c| c.map(Crate::to_char).ok_or(AocError::NoSolution))
-        .collect()
+    operate(CrateMover9001, input)
 }
 
 fn main() -> Result<(), AocError> {
@@ -0,0 +1,37 @@
+use std::io::{BufRead, Write};
+
+use aoc_2022_7::*;
+
+const INPUT: &str = include_str!("../../data/input.txt");
+
+// explore the tree of a transcript, the puzzle input unless a file is given
+fn main() -> Result<(), AocError> {
+    let transcript = match std::env::args().nth(1) {
+        Some(path) => std::fs::read_to_string(path).map_err(|_| AocError::ParseError)?,
+        None => INPUT.to_string(),
+    };
+    let input = transcript.parse::<InputModel>()?;
+    let mut shell = Shell::new();
+    shell.execute(input.commands)?;
+    shell.change_dir("/")?;
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    loop {
+        print!("{}$ ", shell.pwd());
+        stdout.flush().map_err(|_| AocError::IllegalState)?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).map_err(|_| AocError::IllegalState)? == 0 {
+            break;
+        }
+        match line.trim() {
+            "exit" | "quit" => break,
+            line => match shell.interpret(line) {
+                Ok(output) if output.is_empty() => {}
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("error: {}", e),
+            },
+        }
+    }
+    Ok(())
+}
@@ -138,6 +138,72 @@ impl FileSystemNode {
 
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn contents(&self) -> Result<&Vec<FileSystemNode>, AocError> {
+        match self.node_type {
+            NodeType::Directory(ref contents) => Ok(contents),
+            NodeType::File(_) => Err(AocError::NotADirectory(self.name.clone())),
+        }
+    }
+
+    fn contents_mut(&mut self) -> Result<&mut Vec<FileSystemNode>, AocError> {
+        match self.node_type {
+            NodeType::Directory(ref mut contents) => Ok(contents),
+            NodeType::File(_) => Err(AocError::NotADirectory(self.name.clone())),
+        }
+    }
+
+    fn entries(&self, path: &str, entries: &mut Vec<Entry>) {
+        entries.push(Entry {
+            path: path.to_string(),
+            size: self.total_size(),
+            is_dir: self.is_dir(),
+        });
+        if let NodeType::Directory(ref contents) = self.node_type {
+            for node in contents {
+                node.entries(&join_path(path, &node.name), entries);
+            }
+        }
+    }
+
+    // replay as `ls` output followed by a visit to every non-empty subdirectory
+    fn transcript(&self, lines: &mut Vec<String>) {
+        let contents = match self.node_type {
+            NodeType::Directory(ref contents) if !contents.is_empty() => contents,
+            _ => return,
+        };
+        lines.push("$ ls".to_string());
+        for node in contents {
+            lines.push(match node.node_type {
+                NodeType::File(size) => format!("{} {}", size, node.name),
+                NodeType::Directory(_) => format!("dir {}", node.name),
+            });
+        }
+        for node in contents.iter().filter(|node| node.is_dir()) {
+            let mut sub = vec![];
+            node.transcript(&mut sub);
+            if !sub.is_empty() {
+                lines.push(format!("$ cd {}", node.name));
+                lines.extend(sub);
+                lines.push("$ cd ..".to_string());
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        match self.node_type {
+            NodeType::File(size) => format!("{{\"name\":{},\"size\":{}}}", json_string(&self.name), size),
+            NodeType::Directory(ref contents) => format!(
+                "{{\"name\":{},\"contents\":[{}]}}",
+                json_string(&self.name),
+                contents.iter().map(|node| node.to_json()).collect::<Vec<_>>().join(",")
+            ),
+        }
+    }
+
     fn flat_list(&self) -> Vec<FileSystemNode> {
         match self.node_type {
             NodeType::File(_) => vec![self.clone()],
@@ -176,6 +242,84 @@ pub enum AocError {
     IllegalState,
     #[error("No such directory {0}")]
     NoSuchDirectory(String), 
+    #[error("No such file or directory {0}")]
+    NoSuchFile(String),
+    #[error("Not a directory {0}")]
+    NotADirectory(String),
+    #[error("{0} already exists")]
+    AlreadyExists(String),
+    #[error("Unknown command {0}")]
+    UnknownCommand(String),
+}
+
+/// A node in the tree with its absolute path, as listed by `find`, `du` and `sort`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Entry {
+    pub path: String,
+    pub size: usize,
+    pub is_dir: bool,
+}
+
+fn join_path(dir: &str, name: &str) -> String {
+    if dir == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn path_string(path: &[String]) -> String {
+    path.iter().skip(1).fold("/".to_string(), |dir, name| join_path(&dir, name))
+}
+
+fn json_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// match a name against a pattern with `*` and `?` wildcards
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    // matches[j]: pattern prefix so far matches name[..j]
+    let mut matches = vec![false; name.len() + 1];
+    matches[0] = true;
+    for p in pattern {
+        let previous = matches.clone();
+        matches[0] = p == '*' && previous[0];
+        for j in 1..=name.len() {
+            matches[j] = match p {
+                '*' => previous[j] || matches[j - 1],
+                '?' => previous[j - 1],
+                c => previous[j - 1] && name[j - 1] == c,
+            };
+        }
+    }
+    matches[name.len()]
+}
+
+// sizes the way `du -h` prints them
+pub fn human_size(size: usize) -> String {
+    let units = ["K", "M", "G", "T"];
+    let mut value = size as f64;
+    let mut unit = "";
+    for u in units {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = u;
+    }
+    if unit.is_empty() {
+        size.to_string()
+    } else if value < 10.0 {
+        format!("{:.1}{}", value, unit)
+    } else {
+        format!("{:.0}{}", value, unit)
+    }
+}
+
+pub fn sort_by_size(entries: &mut [Entry]) {
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
 }
         
 impl FromStr for InputModel {
@@ -227,6 +371,9 @@ fn command(input: &str) -> IResult<&str, Command> {
     .parse(input)
 }
 
+const REPL_HELP: &str = "pwd | cd PATH | ls [PATH] | tree [PATH] | du [-h] [PATH] | find [PATH] GLOB
+sort [PATH] | rm PATH | mv FROM TO | json | transcript | help | exit";
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Shell {
     root: FileSystemNode,
@@ -287,6 +434,175 @@ impl Shell {
         Ok(())
     }
 
+    pub fn pwd(&self) -> String {
+        path_string(&self.path)
+    }
+
+    // resolve an absolute or relative path with `..` into path components
+    fn resolve(&self, path: &str) -> Vec<String> {
+        let mut components = if path.starts_with('/') {
+            vec![self.root.name.clone()]
+        } else {
+            self.path.clone()
+        };
+        for part in path.split('/').filter(|p| !p.is_empty() && *p != ".") {
+            if part == ".." {
+                if components.len() > 1 {
+                    components.pop();
+                }
+            } else {
+                components.push(part.to_string());
+            }
+        }
+        components
+    }
+
+    fn lookup(&self, path: &str) -> Result<(Vec<String>, &FileSystemNode), AocError> {
+        let components = self.resolve(path);
+        let node = self.get_path(&components)
+            .map_err(|_| AocError::NoSuchFile(path.to_string()))?;
+        Ok((components, node))
+    }
+
+    pub fn change_dir(&mut self, path: &str) -> Result<(), AocError> {
+        let (components, node) = self.lookup(path)?;
+        if !node.is_dir() {
+            return Err(AocError::NotADirectory(path.to_string()));
+        }
+        self.path = components;
+        Ok(())
+    }
+
+    pub fn rm(&mut self, path: &str) -> Result<FileSystemNode, AocError> {
+        let (components, _) = self.lookup(path)?;
+        let (name, parent) = components.split_last().unwrap();
+        if parent.is_empty() {
+            return Err(AocError::IllegalState);
+        }
+        let contents = self.get_path_mut(parent)?.contents_mut()?;
+        let index = contents.iter().position(|node| &node.name == name).unwrap();
+        let removed = contents.remove(index);
+        if self.path.starts_with(&components) {
+            self.path = parent.to_vec();
+        }
+        Ok(removed)
+    }
+
+    // move into an existing directory, or rename when the target does not exist yet
+    pub fn mv(&mut self, from: &str, to: &str) -> Result<(), AocError> {
+        let (source, _) = self.lookup(from)?;
+        let (target_dir, name) = match self.lookup(to) {
+            Ok((components, node)) if node.is_dir() => (components, source.last().unwrap().clone()),
+            Ok(_) => return Err(AocError::AlreadyExists(to.to_string())),
+            Err(_) => {
+                let mut components = self.resolve(to);
+                let name = components.pop().ok_or(AocError::IllegalState)?;
+                (components, name)
+            }
+        };
+        if target_dir.starts_with(&source) {
+            return Err(AocError::IllegalState);
+        }
+        let target = self.get_path(&target_dir)
+            .map_err(|_| AocError::NoSuchDirectory(path_string(&target_dir)))?;
+        if target.contents()?.iter().any(|node| node.name == name) {
+            return Err(AocError::AlreadyExists(join_path(&path_string(&target_dir), &name)));
+        }
+
+        let mut node = self.rm(from)?;
+        node.name = name;
+        self.get_path_mut(&target_dir)?.contents_mut()?.push(node);
+        Ok(())
+    }
+
+    /// Every file and directory below the path, the path itself first.
+    pub fn walk(&self, path: &str) -> Result<Vec<Entry>, AocError> {
+        let (components, node) = self.lookup(path)?;
+        let mut entries = vec![];
+        node.entries(&path_string(&components), &mut entries);
+        Ok(entries)
+    }
+
+    pub fn du(&self, path: &str) -> Result<Vec<Entry>, AocError> {
+        Ok(self.walk(path)?.into_iter().filter(|entry| entry.is_dir).collect())
+    }
+
+    // patterns with a `/` match the whole path, others only the name
+    pub fn find(&self, path: &str, pattern: &str) -> Result<Vec<Entry>, AocError> {
+        Ok(self.walk(path)?
+            .into_iter()
+            .filter(|entry| {
+                let subject = if pattern.contains('/') {
+                    entry.path.as_str()
+                } else {
+                    entry.path.rsplit('/').next().unwrap_or("")
+                };
+                glob_match(pattern, subject)
+            })
+            .collect())
+    }
+
+    pub fn to_transcript(&self) -> String {
+        let mut lines = vec!["$ cd /".to_string()];
+        self.root.transcript(&mut lines);
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self) -> String {
+        self.root.to_json()
+    }
+
+    /// Run one line typed at the REPL and return what it prints.
+    pub fn interpret(&mut self, line: &str) -> Result<String, AocError> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let format_entries = |entries: Vec<Entry>, human: bool| {
+            entries.iter()
+                .map(|entry| {
+                    let size = if human { human_size(entry.size) } else { entry.size.to_string() };
+                    format!("{}\t{}", size, entry.path)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        match words[..] {
+            [] => Ok(String::new()),
+            ["pwd"] => Ok(self.pwd()),
+            ["cd"] => self.change_dir("/").map(|_| String::new()),
+            ["cd", path] => self.change_dir(path).map(|_| String::new()),
+            ["ls"] | ["ls", _] => {
+                let (_, node) = self.lookup(words.get(1).unwrap_or(&"."))?;
+                Ok(node.contents()?.iter()
+                    .map(|node| match node.node_type {
+                        NodeType::File(size) => format!("{} {}", size, node.name),
+                        NodeType::Directory(_) => format!("dir {}", node.name),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            ["tree"] | ["tree", _] => {
+                let (_, node) = self.lookup(words.get(1).unwrap_or(&"."))?;
+                Ok(node.format_with_indent(""))
+            }
+            ["du"] => Ok(format_entries(self.du(".")?, false)),
+            ["du", "-h"] => Ok(format_entries(self.du(".")?, true)),
+            ["du", "-h", path] => Ok(format_entries(self.du(path)?, true)),
+            ["du", path] => Ok(format_entries(self.du(path)?, false)),
+            ["find", pattern] => Ok(format_entries(self.find(".", pattern)?, false)),
+            ["find", path, pattern] => Ok(format_entries(self.find(path, pattern)?, false)),
+            ["sort"] | ["sort", _] => {
+                let mut entries = self.walk(words.get(1).unwrap_or(&"."))?;
+                sort_by_size(&mut entries);
+                Ok(format_entries(entries, true))
+            }
+            ["rm", path] => self.rm(path).map(|_| String::new()),
+            ["mv", from, to] => self.mv(from, to).map(|_| String::new()),
+            ["json"] => Ok(self.to_json()),
+            ["transcript"] => Ok(self.to_transcript()),
+            ["help"] => Ok(REPL_HELP.to_string()),
+            _ => Err(AocError::UnknownCommand(line.to_string())),
+        }
+    }
+
     fn cdup(&mut self) -> Result<(), AocError> {
         self.path.truncate(self.path.len() - 1);
         Ok(())
@@ -536,4 +852,134 @@ $ ls";
     }
 
 
+
+    const SAMPLE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    fn sample_shell() -> Shell {
+        let mut shell = Shell::new();
+        shell.execute(SAMPLE.parse::<InputModel>().unwrap().commands).unwrap();
+        shell
+    }
+
+    fn paths(entries: Vec<Entry>) -> Vec<String> {
+        entries.into_iter().map(|entry| entry.path).collect()
+    }
+
+    #[test]
+    fn test_pwd_and_change_dir() {
+        let mut shell = sample_shell();
+
+        assert_eq!(shell.pwd(), "/d");
+        shell.change_dir("../a/e").unwrap();
+        assert_eq!(shell.pwd(), "/a/e");
+        shell.change_dir("/").unwrap();
+        assert_eq!(shell.pwd(), "/");
+        assert!(shell.change_dir("b.txt").is_err());
+    }
+
+    #[test]
+    fn test_du() {
+        let shell = sample_shell();
+
+        let actual = shell.du("/").unwrap()
+            .into_iter()
+            .map(|entry| (entry.path, entry.size))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            ("/".to_string(), 48381165),
+            ("/a".to_string(), 94853),
+            ("/a/e".to_string(), 584),
+            ("/d".to_string(), 24933642),
+        ];
+
+        assert_eq!(actual, expected);
+        assert_eq!(human_size(48381165), "46M");
+        assert_eq!(human_size(94853), "93K");
+        assert_eq!(human_size(8504156), "8.1M");
+        assert_eq!(human_size(584), "584");
+    }
+
+    #[test]
+    fn test_find_and_sort() {
+        let shell = sample_shell();
+
+        assert_eq!(paths(shell.find("/", "*.*").unwrap()), vec!["/a/h.lst", "/b.txt", "/c.dat", "/d/d.log", "/d/d.ext"]);
+        assert_eq!(paths(shell.find("/", "/a/?").unwrap()), vec!["/a/e", "/a/f", "/a/g"]);
+
+        let mut entries = shell.walk("/a").unwrap();
+        sort_by_size(&mut entries);
+        assert_eq!(paths(entries), vec!["/a", "/a/h.lst", "/a/f", "/a/g", "/a/e", "/a/e/i"]);
+    }
+
+    #[test]
+    fn test_rm_and_mv() {
+        let mut shell = sample_shell();
+
+        shell.rm("/a/e").unwrap();
+        shell.mv("/b.txt", "/d").unwrap();
+        shell.mv("k", "kk").unwrap();
+
+        assert_eq!(shell.get_root().total_size(), 48381165 - 584);
+        assert_eq!(paths(shell.find("/", "*k*").unwrap()), vec!["/d/kk"]);
+        assert_eq!(paths(shell.find("/", "b.txt").unwrap()), vec!["/d/b.txt"]);
+        assert!(shell.mv("/a", "/a/f").is_err());
+        assert!(shell.rm("/nope").is_err());
+    }
+
+    #[test]
+    fn test_transcript_round_trip() {
+        let shell = sample_shell();
+        let mut replayed = Shell::new();
+
+        replayed.execute(shell.to_transcript().parse::<InputModel>().unwrap().commands).unwrap();
+
+        assert_eq!(replayed.get_root(), shell.get_root());
+    }
+
+    #[test]
+    fn test_json() {
+        let mut shell = Shell::new();
+        shell.execute(vec![Command::LS(vec![
+            FileSystemNode::new_dir("a", vec![]),
+            FileSystemNode::new_file("b.txt", 14848514),
+        ])]).unwrap();
+
+        assert_eq!(
+            shell.to_json(),
+            r#"{"name":"/","contents":[{"name":"a","contents":[]},{"name":"b.txt","size":14848514}]}"#
+        );
+    }
+
+    #[test]
+    fn test_interpret() {
+        let mut shell = sample_shell();
+
+        assert_eq!(shell.interpret("cd /a").unwrap(), "");
+        assert_eq!(shell.interpret("ls").unwrap(), "dir e\n29116 f\n2557 g\n62596 h.lst");
+        assert_eq!(shell.interpret("du -h").unwrap(), "93K\t/a\n584\t/a/e");
+        assert!(shell.interpret("format c:").is_err());
+    }
+
 }
@@ -1,8 +1,10 @@
-use std::{str::FromStr, cmp::max, collections::HashSet};
+use std::{str::FromStr, cmp::max, collections::HashSet, fmt::{Display, Formatter}, ops::Range};
 use nom::{
     IResult,Parser,
+    branch::alt,
+    bytes::complete::tag,
     character::complete::{digit1, newline, space1},
-    combinator::map_res,
+    combinator::{map, map_res},
     multi::separated_list1,
     sequence::separated_pair,
     error::ErrorKind,
@@ -13,12 +15,31 @@ pub struct InputModel  {
     pub motions: Vec<Motion>
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Direction {
     Up,
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, 1),
+            Direction::UpRight => (1, 1),
+            Direction::DownLeft => (-1, -1),
+            Direction::DownRight => (1, -1),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -27,13 +48,26 @@ pub struct Motion {
     pub distance: usize,
 }
 
+impl Motion {
+    // the motion one step at a time
+    pub fn steps(&self) -> impl Iterator<Item = Direction> {
+        std::iter::repeat_n(self.direction, self.distance)
+    }
+}
+
+pub fn motion_steps(motions: &[Motion]) -> impl Iterator<Item = Direction> + '_ {
+    motions.iter().flat_map(Motion::steps)
+}
+
 
 #[derive(thiserror::Error, Debug)]
 pub enum AocError {
     #[error("Error parsing the input")]
     ParseError,
+    #[error("The rope has no knot {0}")]
+    InvalidKnot(usize),
 }
-        
+
 impl FromStr for InputModel {
     type Err = AocError;
 
@@ -44,21 +78,26 @@ impl FromStr for InputModel {
     }
 }
 
+fn direction(s: &str) -> IResult<&str, Direction> {
+    // the diagonals go first so `U` does not cut `UL` short
+    alt((
+        map(tag("UL"), |_| Direction::UpLeft),
+        map(tag("UR"), |_| Direction::UpRight),
+        map(tag("DL"), |_| Direction::DownLeft),
+        map(tag("DR"), |_| Direction::DownRight),
+        map(tag("U"), |_| Direction::Up),
+        map(tag("D"), |_| Direction::Down),
+        map(tag("L"), |_| Direction::Left),
+        map(tag("R"), |_| Direction::Right),
+    ))(s)
+}
+
 fn parser(s: &str) -> IResult<&str, Vec<Motion>> {
     separated_list1(
         newline,
         map_res(
             separated_pair(
-                map_res(
-                    nom::character::complete::one_of("UDLR"),
-                    |c| match c {
-                        'U' => Ok(Direction::Up),
-                        'D' => Ok(Direction::Down),
-                        'L' => Ok(Direction::Left),
-                        'R' => Ok(Direction::Right),
-                        _ => Err(()),
-                    },
-                ),
+                direction,
                 space1,
                 map_res(digit1, |s: &str| s.parse::<usize>()),
             ),
@@ -67,21 +106,20 @@ fn parser(s: &str) -> IResult<&str, Vec<Motion>> {
     ).parse(s)
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Position {
     x: isize,
     y: isize,
 }
 
 impl Position {
+    pub fn new(x: isize, y: isize) -> Position {
+        Position { x, y }
+    }
+
     pub fn step_to(&self, direction: &Direction) -> Position {
-        let Position{x, y} = self;
-        match direction {
-            Direction::Up => Position { x: *x, y: *y + 1 },
-            Direction::Down => Position { x: *x, y: *y - 1 },
-            Direction::Left => Position { x: *x - 1, y: *y },
-            Direction::Right => Position { x: *x + 1, y: *y },
-        }
+        let (dx, dy) = direction.delta();
+        Position { x: self.x + dx, y: self.y + dy }
     }
 
     pub fn distance(&self, other: &Self) -> usize {
@@ -92,23 +130,15 @@ impl Position {
         let dx = head.x - self.x;
         let dy = head.y - self.y;
 
-        if max(dx.abs(), dy.abs()) <= 1 { 
-            self.clone()
+        if max(dx.abs(), dy.abs()) <= 1 {
+            *self
         } else {
             Position {
-                x: self.x + sign(dx),
-                y: self.y + sign(dy),
+                x: self.x + dx.signum(),
+                y: self.y + dy.signum(),
             }
         }
-            
-    }
-}
 
-fn sign(x: isize) -> isize {
-    match x.cmp(&0) {
-        std::cmp::Ordering::Greater => 1,
-        std::cmp::Ordering::Less => -1,
-        std::cmp::Ordering::Equal => 0,
     }
 }
 
@@ -124,36 +154,140 @@ impl Rope {
         Rope { knots: vec![Position { x: 0, y: 0 }; n] }
     }
 
-    pub fn step(&self, direction: &Direction) -> Self {
-        let mut new_knots = self.knots.clone();
-        new_knots[0] = self.knots[0].step_to(direction);
-        for i in 1..new_knots.len() {
-            new_knots[i] = new_knots[i].catch_up(&new_knots[i-1]);
+    // move the head and let every knot catch up with the one in front of it
+    pub fn step(&mut self, direction: &Direction) {
+        let Some(head) = self.knots.first_mut() else {
+            return;
+        };
+        *head = head.step_to(direction);
+        for i in 1..self.knots.len() {
+            let lead = self.knots[i - 1];
+            self.knots[i] = self.knots[i].catch_up(&lead);
         }
-        Rope { knots: new_knots }
     }
 
-    pub fn tail(&self) -> Position {
-        self.knots.last().unwrap().clone()
+    // a rope without knots has no tail
+    pub fn tail(&self) -> Option<Position> {
+        self.knots.last().copied()
     }
 
-}
+    // the head is `H`, a rope of two has a `T` for the tail and longer ones number their knots
+    fn label(&self, knot: usize) -> char {
+        match knot {
+            0 => 'H',
+            1 if self.knots.len() == 2 => 'T',
+            1..=9 => char::from_digit(knot as u32, 10).unwrap(),
+            _ => '#',
+        }
+    }
 
+    /// Draw the rope between the corners like the puzzle does, with `s` marking the start and
+    /// knots nearer the head covering the ones behind.
+    pub fn render(&self, min: &Position, max: &Position) -> String {
+        (min.y..=max.y).rev()
+            .map(|y| {
+                (min.x..=max.x)
+                    .map(|x| {
+                        let position = Position { x, y };
+                        match self.knots.iter().position(|knot| *knot == position) {
+                            Some(knot) => self.label(knot),
+                            None if position == (Position { x: 0, y: 0 }) => 's',
+                            None => '.',
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
 
-pub fn tail_coverage(rope: &Rope, motions: &Vec<Motion>) -> HashSet<Position> {  
 
-    let mut tail_set = HashSet::new();
+/// Every position each of the chosen knots visits while the rope follows the steps, starting
+/// positions included.
+pub fn coverage(
+    rope: &Rope,
+    steps: impl IntoIterator<Item = Direction>,
+    knots: &[usize],
+) -> Result<Vec<HashSet<Position>>, AocError> {
+    if let Some(knot) = knots.iter().find(|knot| **knot >= rope.knots.len()) {
+        return Err(AocError::InvalidKnot(*knot));
+    }
 
     let mut rope = rope.clone();
+    let mut visited = knots.iter()
+        .map(|knot| HashSet::from([rope.knots[*knot]]))
+        .collect::<Vec<_>>();
+
+    for direction in steps {
+        rope.step(&direction);
+        for (set, knot) in visited.iter_mut().zip(knots) {
+            set.insert(rope.knots[*knot]);
+        }
+    }
+
+    Ok(visited)
+}
+
+pub fn tail_coverage(rope: &Rope, motions: &[Motion]) -> Result<HashSet<Position>, AocError> {
+    let tail = rope.knots.len().checked_sub(1).ok_or(AocError::InvalidKnot(0))?;
+    let mut visited = coverage(rope, motion_steps(motions), &[tail])?;
+    Ok(visited.remove(0))
+}
+
+/// The rope after each step in a range, to be played back frame by frame.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Animation {
+    start: usize,
+    frames: Vec<Rope>,
+}
 
-    for motion in motions {
-        for _ in 0..motion.distance {
-            rope = rope.step(&motion.direction);
-            tail_set.insert(rope.tail().clone());
+impl Animation {
+    // frame `n` is the rope after `n` steps, so the range `0..1` is just the initial state
+    pub fn record(rope: &Rope, steps: impl IntoIterator<Item = Direction>, range: Range<usize>) -> Animation {
+        let mut rope = rope.clone();
+        let mut steps = steps.into_iter();
+        let mut frames = vec![];
+        for n in 0..range.end {
+            if n >= range.start {
+                frames.push(rope.clone());
+            }
+            match steps.next() {
+                Some(direction) => rope.step(&direction),
+                None => break,
+            }
         }
+        Animation { start: range.start, frames }
+    }
+
+    pub fn frames(&self) -> &[Rope] {
+        &self.frames
+    }
+
+    // the corners of a box holding the start and every knot of every frame
+    pub fn bounds(&self) -> (Position, Position) {
+        let origin = Position { x: 0, y: 0 };
+        self.frames.iter()
+            .flat_map(|rope| rope.knots.iter())
+            .fold((origin, origin), |(min, max), knot| (
+                Position { x: min.x.min(knot.x), y: min.y.min(knot.y) },
+                Position { x: max.x.max(knot.x), y: max.y.max(knot.y) },
+            ))
     }
+}
 
-    tail_set
+impl Display for Animation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (min, max) = self.bounds();
+        for (n, rope) in self.frames.iter().enumerate() {
+            if n > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "== Step {} ==", self.start + n)?;
+            writeln!(f, "{}", rope.render(&min, &max))?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -194,47 +328,47 @@ R 2";
 
     #[test]
     fn test_step() {
-        let rope = Rope {
+        let mut rope = Rope {
             knots: vec![ Position { x: 0, y: 0 }, Position { x: 0, y: 0 },]
         };
         let expected = Rope {
             knots: vec![ Position { x: 1, y: 0 }, Position { x: 0, y: 0 },]
         };
-        let actual = rope.step(&Direction::Right);
-        assert_eq!(actual, expected);
+        rope.step(&Direction::Right);
+        assert_eq!(rope, expected);
     }
 
     #[test]
     fn test_2_r_step() {
-        let rope = Rope {
+        let mut rope = Rope {
             knots: vec![ Position { x: 0, y: 0 }, Position { x: 0, y: 0 },]
         };
         let expected = Rope {
             knots: vec![ Position { x: 2, y: 0 }, Position { x: 1, y: 0 },]
         };
-        let rope = rope.step(&Direction::Right);
-        let actual = rope.step(&Direction::Right);
-        assert_eq!(actual, expected);
+        rope.step(&Direction::Right);
+        rope.step(&Direction::Right);
+        assert_eq!(rope, expected);
     }
 
     #[test]
     fn test_r_u_step() {
-        let rope = Rope {
+        let mut rope = Rope {
             knots: vec![ Position { x: 0, y: 0 }, Position { x: 0, y: 0 },]
         };
         let expected = Rope {
             knots: vec![ Position { x: 1, y: 1 }, Position { x: 0, y: 0 },]
         };
-        let rope = rope.step(&Direction::Right);
-        let actual = rope.step(&Direction::Up);
-        assert_eq!(actual, expected);
+        rope.step(&Direction::Right);
+        rope.step(&Direction::Up);
+        assert_eq!(rope, expected);
     }
 
     #[test]
     fn test_tail_coverage() {
         let rope = Rope::new(2);
         let motions = input_data().motions;
-        let actual = tail_coverage(&rope, &motions).len();
+        let actual = tail_coverage(&rope, &motions).unwrap().len();
         let expected = 13;
         assert_eq!(actual, expected);
     }
@@ -253,5 +387,70 @@ R 2";
             assert_eq!(actual, expected);
         }
     }
-}
 
+    #[test]
+    fn test_diagonal_motions() {
+        let input = "UR 2\nDL 1\nR 1\nDR 3\nUL 1".parse::<InputModel>().unwrap();
+        assert_eq!(input.motions[0], Motion{direction: Direction::UpRight, distance: 2});
+        assert_eq!(input.motions[4], Motion{direction: Direction::UpLeft, distance: 1});
+
+        let mut rope = Rope::new(3);
+        for direction in motion_steps(&input.motions).take(2) {
+            rope.step(&direction);
+        }
+        // a diagonal pull drags each knot diagonally behind it
+        assert_eq!(rope.knots, vec![Position { x: 2, y: 2 }, Position { x: 1, y: 1 }, Position { x: 0, y: 0 }]);
+        assert_eq!(tail_coverage(&Rope::new(2), &input.motions).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_rope_from_positions() {
+        let rope = Rope { knots: vec![Position::new(1, 1), Position::new(0, 0)] };
+
+        assert_eq!(rope.tail(), Some(Position::new(0, 0)));
+        assert_eq!(Rope::new(0).tail(), None);
+        assert_eq!(rope.render(&Position::new(0, 0), &Position::new(2, 1)), ".H.\nT..");
+    }
+
+    #[test]
+    fn test_knot_coverage() {
+        let motions = input_data().motions;
+        let visited = coverage(&Rope::new(10), motion_steps(&motions), &[1, 9, 0]).unwrap();
+
+        // the first knot behind the head moves like the tail of a short rope
+        assert_eq!(visited[0].len(), 13);
+        assert_eq!(visited[1].len(), 1);
+        assert_eq!(visited[2].len(), 21);
+        assert!(matches!(
+            coverage(&Rope::new(2), motion_steps(&motions), &[2]),
+            Err(AocError::InvalidKnot(2))
+        ));
+    }
+
+    #[test]
+    fn test_animation() {
+        let motions = input_data().motions;
+        let animation = Animation::record(&Rope::new(2), motion_steps(&motions), 0..100);
+
+        assert_eq!(animation.frames().len(), 25);
+        assert_eq!(animation.bounds(), (Position { x: 0, y: 0 }, Position { x: 5, y: 4 }));
+        let (min, max) = animation.bounds();
+        assert_eq!(animation.frames()[24].render(&min, &max), "\
+......
+......
+.TH...
+......
+s.....");
+
+        let animation = Animation::record(&Rope::new(10), motion_steps(&motions), 4..6);
+        assert_eq!(animation.to_string(), "\
+== Step 4 ==
+.....
+4321H
+
+== Step 5 ==
+....H
+4321.
+");
+    }
+}
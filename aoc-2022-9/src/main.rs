@@ -5,11 +5,11 @@ const INPUT: &str = include_str!("../data/input.txt");
 
 
 fn part1(input: &InputModel) -> Result<String,AocError> {
-    Ok(tail_coverage(&Rope::new(2), &input.motions).len().to_string())
+    Ok(tail_coverage(&Rope::new(2), &input.motions)?.len().to_string())
 }
 
 fn part2(input: &InputModel) -> Result<String, AocError> {
-    Ok(tail_coverage(&Rope::new(10), &input.motions).len().to_string())
+    Ok(tail_coverage(&Rope::new(10), &input.motions)?.len().to_string())
 }
 
 fn main() -> Result<(), AocError> {
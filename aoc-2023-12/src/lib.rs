@@ -1,5 +1,9 @@
 use std::{str::FromStr, collections::HashMap};
 
+use aoc_common::memo::Memo;
+
+type SpringMemo<'a> = Memo<(&'a str, &'a [u64]), u64>;
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct InputModel {
     pub lines: Vec<(String, Vec<u64>)>,
@@ -32,13 +36,13 @@ impl FromStr for InputModel {
 
 
 // assume we are at the start of a possible block
-fn step(s: &str, blocks: &[u64], memo: &mut HashMap<(String, Vec<u64>), u64>) -> u64 {
+fn step<'a>(s: &'a str, blocks: &'a [u64], memo: &mut SpringMemo<'a>) -> u64 {
 
-    fn operational_spring(s: &str, blocks: &[u64], memo: &mut HashMap<(String, Vec<u64>), u64>) -> u64 {
+    fn operational_spring<'a>(s: &'a str, blocks: &'a [u64], memo: &mut SpringMemo<'a>) -> u64 {
         step(skip_operational(&s[1..]), blocks, memo)
     }
 
-    fn broken_spring(s: &str, blocks: &[u64], memo: &mut HashMap<(String, Vec<u64>), u64>) -> u64 {
+    fn broken_spring<'a>(s: &'a str, blocks: &'a [u64], memo: &mut SpringMemo<'a>) -> u64 {
         let (block, rest) = s.split_at(blocks[0] as usize);
         if block.chars().all(|c| c == '#' || c == '?') && !rest.starts_with('#') {
             let next_blocks = &blocks[1..];
@@ -60,7 +64,7 @@ fn step(s: &str, blocks: &[u64], memo: &mut HashMap<(String, Vec<u64>), u64>) ->
         return 0;
     }
 
-    if let Some(&arrangements) = memo.get(&(s.to_string(), blocks.to_vec())) {
+    if let Some(arrangements) = memo.get(&(s, blocks)) {
         return arrangements;
     }
     
@@ -74,9 +78,7 @@ fn step(s: &str, blocks: &[u64], memo: &mut HashMap<(String, Vec<u64>), u64>) ->
         arrangements += broken_spring(s, blocks, memo);
     }
 
-    memo.insert((s.to_string(), blocks.to_vec()), arrangements);
-    
-    arrangements
+    memo.insert((s, blocks), arrangements)
 }
 
 fn skip_operational(s: &str) -> &str {
@@ -139,7 +141,7 @@ pub fn count_arrangements_nfa(s: &str, blocks: &[u64]) -> u64 {
     pos
 }
 pub fn count_arrangements(s: &str, blocks: &[u64]) -> u64 {
-    let mut memo = HashMap::new();
+    let mut memo = Memo::new();
     step(skip_operational(s), blocks, &mut memo)
 }
 
@@ -158,7 +160,7 @@ mod tests {
     #[test]
     fn test_no_blocks() {
         let blocks = vec![];
-        let mut memo = HashMap::new();
+        let mut memo = Memo::new();
 
         let s = "";
         assert_eq!(step(s, &blocks, &mut memo), 1);
@@ -174,7 +176,7 @@ mod tests {
     fn test_one_block() {
         let blocks = vec![1];
         let blocks_3 = vec![3];
-        let mut memo = HashMap::new();
+        let mut memo = Memo::new();
 
         let s = "#";
         assert_eq!(step(s, &blocks, &mut memo), 1);
@@ -199,7 +201,7 @@ mod tests {
     fn test_more_blocks() {
         let s = "???";
         let blocks = vec![1, 1];
-        let mut memo = HashMap::new();
+        let mut memo = Memo::new();
         assert_eq!(step(s, &blocks, &mut memo), 1);
     }
 
@@ -3,8 +3,9 @@ use std::{
     str::FromStr,
 };
 
-use argmin::core::{CostFunction, Executor, Gradient};
+use aoc_common::linear::{solve, LinearError};
 use nalgebra::{vector, Const, Matrix, Vector2, Vector3};
+use num::{BigRational, ToPrimitive};
 
 #[derive(thiserror::Error, Debug)]
 pub enum AocError {
@@ -12,6 +13,10 @@ pub enum AocError {
     ParseError,
     #[error("No solution found")]
     NoSolution,
+    #[error("Rock trajectory is not integral")]
+    NonIntegerSolution,
+    #[error("Hailstone coordinate {0} is not a whole number")]
+    NonIntegerInput(FloatScalar),
 }
 
 impl From<ParseIntError> for AocError {
@@ -88,19 +93,6 @@ impl HailStone {
 
         intercept
     }
-
-    fn distance_path(&self, other: &HailStone) -> Option<FloatScalar> {
-        let n = self.velocity.cross(&other.velocity);
-        let diff = other.position - self.position;
-        let d = n.dot(&diff).abs();
-        let l = n.norm();
-
-        if l == 0.0 {
-            None
-        } else {
-            Some(d / l)
-        }
-    }
 }
 
 impl FromStr for HailStone {
@@ -158,70 +150,91 @@ pub fn pairs_in_range(stones: &[HailStone], range: impl Fn(&Vec2D) -> bool) -> u
         .count()
 }
 
-struct Part2Problem {
-    stones: Vec<HailStone>,
-}
+type Exact3D = [BigRational; 3];
 
-impl CostFunction for Part2Problem {
-    type Output = FloatScalar;
-    type Param = Vec<FloatScalar>;
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rock {
+    pub position: [i128; 3],
+    pub velocity: [i128; 3],
+}
 
-    fn cost(&self, p: &Self::Param) -> Result<FloatScalar, argmin::core::Error> {
-        let stone = HailStone {
-            position: vector![p[0], p[1], p[2]],
-            velocity: vector![p[3], p[4], p[5]],
-        };
-        let dist = self
-            .stones
-            .iter()
-            .filter_map(|s| s.distance_path(&stone))
-            .map(|d| d.powi(2))
-            .sum();
-        Ok(dist)
-    }
+// the solver works on whole numbers only, rounding them would give the wrong rock
+fn to_exact(v: &Vec3D) -> Result<Exact3D, AocError> {
+    let exact = |c: FloatScalar| match c.fract() == 0.0 {
+        true => BigRational::from_float(c).ok_or(AocError::NonIntegerInput(c)),
+        false => Err(AocError::NonIntegerInput(c)),
+    };
+    Ok([exact(v.x)?, exact(v.y)?, exact(v.z)?])
 }
 
-impl Gradient for Part2Problem {
-    type Param = Vec<FloatScalar>;
-    type Gradient = Vec<FloatScalar>;
-
-    fn gradient(&self, t: &Vec<f64>) -> Result<Vec<f64>, argmin::core::Error> {
-        let mut grad = vec![0.0; t.len()];
-        for i in 0..t.len() {
-            let mut t1 = t.clone();
-            let mut t2 = t.clone();
-            t1[i] += 0.001;
-            t2[i] -= 0.001;
-            let c1 = self.cost(&t1)?;
-            let c2 = self.cost(&t2)?;
-            grad[i] = (c1 - c2) / 0.002;
-        }
+fn cross(a: &Exact3D, b: &Exact3D) -> Exact3D {
+    [
+        &a[1] * &b[2] - &a[2] * &b[1],
+        &a[2] * &b[0] - &a[0] * &b[2],
+        &a[0] * &b[1] - &a[1] * &b[0],
+    ]
+}
 
-        Ok(grad)
-    }
+fn sub(a: &Exact3D, b: &Exact3D) -> Exact3D {
+    [&a[0] - &b[0], &a[1] - &b[1], &a[2] - &b[2]]
 }
 
-fn optimize_stone(stones: &[HailStone], initial: &[f64]) -> Result<Vec<FloatScalar>, AocError> {
-    let mut problem = Part2Problem {
-        stones: stones.to_vec(),
-    };
+fn rock_equations(s1: &HailStone, s2: &HailStone) -> Result<Vec<(Vec<BigRational>, BigRational)>, AocError> {
+    // the rock (P, V) hits stone i when (P - Pi) x (V - Vi) = 0, the P x V term is shared
+    // by every stone so subtracting two of them leaves a linear system:
+    //
+    //    P x (V2 - V1) + (P2 - P1) x V = P2 x V2 - P1 x V1
+    let (p1, v1) = (to_exact(&s1.position)?, to_exact(&s1.velocity)?);
+    let (p2, v2) = (to_exact(&s2.position)?, to_exact(&s2.velocity)?);
+    let d = sub(&v2, &v1);
+    let e = sub(&p2, &p1);
+    let rhs = sub(&cross(&p2, &v2), &cross(&p1, &v1));
+    let zero = BigRational::from_integer(0.into());
+
+    let rows = [
+        [zero.clone(), d[2].clone(), -&d[1], zero.clone(), -&e[2], e[1].clone()],
+        [-&d[2], zero.clone(), d[0].clone(), e[2].clone(), zero.clone(), -&e[0]],
+        [d[1].clone(), -&d[0], zero.clone(), -&e[1], e[0].clone(), zero],
+    ];
+    Ok(rows.into_iter()
+        .map(|row| row.to_vec())
+        .zip(rhs)
+        .collect())
+}
 
-    let line_search = argmin::solver::linesearch::MoreThuenteLineSearch::new();
-    let solver = argmin::solver::gradientdescent::SteepestDescent::new(line_search);
-    let cost = Part2Problem {
-        stones: stones.to_vec(),
-    };
-    let res = Executor::new(cost, solver)
-        .configure(|state| {
-            state
-                .param(initial.to_vec())
-                .max_iters(1000)
-                .target_cost(0.0)
+fn to_rock(solution: &[BigRational]) -> Result<Rock, AocError> {
+    let ints = solution
+        .iter()
+        .map(|v| {
+            if v.is_integer() {
+                v.to_integer().to_i128().ok_or(AocError::NonIntegerSolution)
+            } else {
+                Err(AocError::NonIntegerSolution)
+            }
         })
-        .run()
-        .map_err(|e| AocError::NoSolution)?;
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Rock {
+        position: [ints[0], ints[1], ints[2]],
+        velocity: [ints[3], ints[4], ints[5]],
+    })
+}
 
-    Ok(res.state().get_prev_best_param().unwrap().clone())
+pub fn find_rock(stones: &[HailStone]) -> Result<Rock, AocError> {
+    // two pairs sharing a stone give six equations, unless parallel stones make them singular
+    for i in 1..stones.len() {
+        for j in i + 1..stones.len() {
+            let (a, b): (Vec<_>, Vec<_>) = rock_equations(&stones[0], &stones[i])?
+                .into_iter()
+                .chain(rock_equations(&stones[0], &stones[j])?)
+                .unzip();
+            match solve(&a, &b) {
+                Ok(solution) => return to_rock(&solution),
+                Err(LinearError::Singular) => continue,
+                Err(_) => return Err(AocError::NoSolution),
+            }
+        }
+    }
+    Err(AocError::NoSolution)
 }
 
 fn is_parallel(s1: &HailStone, s2: &HailStone) -> Option<(HailStone, HailStone)> {
@@ -243,57 +256,6 @@ pub fn find_parallel_stones(stones: &[HailStone]) -> Vec<(HailStone, HailStone)>
         .collect::<Vec<(HailStone, HailStone)>>()
 }
 
-fn start_stone(stones: &[HailStone]) -> HailStone {
-    let parallel = find_parallel_stones(stones);
-
-    stones[0].clone()
-}
-
-pub fn find_same_start(stones: &[HailStone]) -> Vec<(HailStone, HailStone)> {
-    with_pairs(stones, &|s1, s2| {
-        if s1.position.x == s2.position.x
-            || s1.position.y == s2.position.y
-            || s1.position.z == s2.position.z
-        {
-            Some((s1.clone(), s2.clone()))
-        } else {
-            None
-        }
-    })
-    .iter()
-    .filter_map(|i| *i)
-    .collect::<Vec<(HailStone, HailStone)>>()
-}
-
-pub fn find_invalid_v_x_ranges(stones: &[HailStone]) -> Vec<(FloatScalar, FloatScalar)> {
-    with_pairs(stones, &|s1, s2| {
-        if s1.position.x > s2.position.x && s1.velocity.x > s2.velocity.x {
-            return Some((s2.velocity.x, s1.velocity.x));
-        }
-        if s1.position.x < s2.position.x && s1.velocity.x < s2.velocity.x {
-            return Some((s1.velocity.x, s2.velocity.x));
-        }
-        None
-    })
-    .iter()
-    .filter_map(|i| *i)
-    .collect::<Vec<_>>()
-}
-
-pub fn find_invalid_v_z_ranges(stones: &[HailStone]) -> Vec<(FloatScalar, FloatScalar)> {
-    with_pairs(stones, &|s1, s2| {
-        if s1.position.z > s2.position.z && s1.velocity.z > s2.velocity.z {
-            return Some((s2.velocity.z, s1.velocity.z));
-        }
-        if s1.position.z < s2.position.z && s1.velocity.z < s2.velocity.z {
-            return Some((s1.velocity.z, s2.velocity.z));
-        }
-        None
-    })
-    .iter()
-    .filter_map(|i| *i)
-    .collect::<Vec<_>>()
-}
 #[cfg(test)]
 mod tests {
     use nalgebra::vector;
@@ -373,39 +335,20 @@ mod tests {
     }
 
     #[test]
-    fn test_distance_path() {
-        let stones = test_input().stones;
-        let p0 = vector![24.0, 13.0, 10.0];
-        let v0 = vector![-3.0, 1.0, 2.0];
-        let stone = HailStone {
-            position: p0,
-            velocity: v0,
-        };
-
-        let stones = test_input().stones;
-        let d1 = stone.distance_path(&stones[0]);
-
-        for hs in stones {
-            let d = stone.distance_path(&hs);
-            assert_eq!(d, Some(0.0))
-        }
-    }
-
-    #[test]
-    fn test_optimize_stone() {
+    fn test_find_rock() {
         let stones = test_input().stones;
 
-        let initial = [20.0, 13.0, 10.0, -3.0, 1.0, 1.0];
-
-        let params = optimize_stone(&stones[0..3], &initial);
-
+        let actual = find_rock(&stones).unwrap();
+        let expected = Rock {
+            position: [24, 13, 10],
+            velocity: [-3, 1, 2],
+        };
 
-        let params = params.unwrap();
+        assert_eq!(actual, expected);
 
-        let expected = [24.0, 13.0, 10.0, -3.0, 1.0, 2.0];
-        params.iter().zip(expected).for_each(|(a, e)| {
-            assert!((a - e).abs() < 0.5)
-        });
+        let mut stones = stones;
+        stones[1].position.x = 18.5;
+        assert!(matches!(find_rock(&stones), Err(AocError::NonIntegerInput(c)) if c == 18.5));
     }
 
     #[test]
@@ -416,13 +359,4 @@ mod tests {
 
         assert_eq!(parallel, vec![(stones[1], stones[2])]);
     }
-
-    #[test]
-    fn test_find_same_start() {
-        let stones = test_input().stones;
-
-        let same_start = find_same_start(&stones);
-
-        assert_eq!(same_start.len(), 17);
-    }
 }
@@ -0,0 +1,312 @@
+use std::{
+    fmt::{Display, Formatter},
+    ops::{BitAnd, BitOr, BitXor, Sub},
+};
+
+use crate::{bitset::BitSet, direction::Direction, position::Position};
+
+/// Fixed-size 2D grid of bits, one [`BitSet`] per row with bit `x` for column `x`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    rows: Vec<BitSet>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> BitGrid {
+        BitGrid {
+            width,
+            height,
+            rows: vec![BitSet::new(); height],
+        }
+    }
+
+    // parse a picture where the `on` character marks set cells
+    pub fn parse(s: &str, on: char) -> BitGrid {
+        let lines = s.lines().collect::<Vec<_>>();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut grid = BitGrid::new(width, lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            grid.rows[y] = line
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| *c == on)
+                .map(|(x, _)| x)
+                .collect();
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, index: Position) -> bool {
+        index.x >= 0
+            && index.y >= 0
+            && index.x < self.width as i32
+            && index.y < self.height as i32
+    }
+
+    pub fn get(&self, index: Position) -> bool {
+        self.in_bounds(index) && self.rows[index.y as usize].contains(index.x as usize)
+    }
+
+    pub fn set(&mut self, index: Position, value: bool) -> Option<()> {
+        if !self.in_bounds(index) {
+            return None;
+        }
+        let row = &mut self.rows[index.y as usize];
+        if value {
+            row.insert(index.x as usize);
+        } else {
+            row.remove(index.x as usize);
+        }
+        Some(())
+    }
+
+    pub fn row(&self, y: usize) -> &BitSet {
+        &self.rows[y]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &BitSet> {
+        self.rows.iter()
+    }
+
+    pub fn count(&self) -> usize {
+        self.rows.iter().map(|row| row.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|row| row.is_empty())
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.iter_mut().for_each(|row| row.clear());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        self.rows.iter().enumerate().flat_map(|(y, row)| {
+            row.iter().map(move |x| Position::new(x as i32, y as i32))
+        })
+    }
+
+    // move every cell n steps in the direction, dropping what leaves the grid
+    pub fn shift(&self, direction: Direction, n: usize) -> BitGrid {
+        let mut grid = BitGrid::new(self.width, self.height);
+        match direction {
+            Direction::East => {
+                for (dst, src) in grid.rows.iter_mut().zip(&self.rows) {
+                    *dst = src << n;
+                    dst.truncate(self.width);
+                }
+            }
+            Direction::West => {
+                for (dst, src) in grid.rows.iter_mut().zip(&self.rows) {
+                    *dst = src >> n;
+                }
+            }
+            Direction::South => {
+                for y in n..self.height {
+                    grid.rows[y] = self.rows[y - n].clone();
+                }
+            }
+            Direction::North => {
+                for y in n..self.height {
+                    grid.rows[y - n] = self.rows[y].clone();
+                }
+            }
+        }
+        grid
+    }
+
+    // move every cell n steps in the direction, wrapping around the edges
+    pub fn rotate(&self, direction: Direction, n: usize) -> BitGrid {
+        let mut grid = BitGrid::new(self.width, self.height);
+        match direction {
+            Direction::East | Direction::West => {
+                if self.width == 0 {
+                    return grid;
+                }
+                let n = n % self.width;
+                let n = if direction == Direction::East { n } else { self.width - n };
+                for (dst, src) in grid.rows.iter_mut().zip(&self.rows) {
+                    let mut row = src << n;
+                    row |= &(src >> (self.width - n));
+                    row.truncate(self.width);
+                    *dst = row;
+                }
+            }
+            Direction::North | Direction::South => {
+                if self.height == 0 {
+                    return grid;
+                }
+                let n = n % self.height;
+                let n = if direction == Direction::South { n } else { self.height - n };
+                for y in 0..self.height {
+                    grid.rows[(y + n) % self.height] = self.rows[y].clone();
+                }
+            }
+        }
+        grid
+    }
+
+    // move every cell by the offset, dropping what leaves the grid
+    pub fn translate(&self, offset: Position) -> BitGrid {
+        let horizontal = if offset.x >= 0 {
+            self.shift(Direction::East, offset.x as usize)
+        } else {
+            self.shift(Direction::West, offset.x.unsigned_abs() as usize)
+        };
+        if offset.y >= 0 {
+            horizontal.shift(Direction::South, offset.y as usize)
+        } else {
+            horizontal.shift(Direction::North, offset.y.unsigned_abs() as usize)
+        }
+    }
+
+    // a bigger grid with `margin` empty cells added on every side
+    pub fn pad(&self, margin: usize) -> BitGrid {
+        let mut rows = vec![BitSet::new(); margin];
+        rows.extend(self.rows.iter().map(|row| row << margin));
+        rows.extend(vec![BitSet::new(); margin]);
+        BitGrid {
+            width: self.width + 2 * margin,
+            height: self.height + 2 * margin,
+            rows,
+        }
+    }
+
+    /// The corners of the smallest box holding every set cell, found row by row.
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        let top = self.rows.iter().position(|row| !row.is_empty())?;
+        let bottom = self.rows.iter().rposition(|row| !row.is_empty())?;
+        let rows = &self.rows[top..=bottom];
+        let left = rows.iter().filter_map(|row| row.iter().next()).min()?;
+        let right = rows.iter().map(|row| row.bit_len()).max()? - 1;
+        Some((
+            Position::new(left as i32, top as i32),
+            Position::new(right as i32, bottom as i32),
+        ))
+    }
+
+    fn zip_with(&self, other: &BitGrid, op: impl Fn(&BitSet, &BitSet) -> BitSet) -> BitGrid {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "grid dimensions differ"
+        );
+        BitGrid {
+            width: self.width,
+            height: self.height,
+            rows: self
+                .rows
+                .iter()
+                .zip(&other.rows)
+                .map(|(a, b)| op(a, b))
+                .collect(),
+        }
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitor(self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a | b)
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitand(self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a & b)
+    }
+}
+
+impl BitXor for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitxor(self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+}
+
+impl Sub for &BitGrid {
+    type Output = BitGrid;
+
+    fn sub(self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a - b)
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in &self.rows {
+            for x in 0..self.width {
+                write!(f, "{}", if row.contains(x) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const PICTURE: &str = "\
+#...
+.#..
+...#
+";
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid = BitGrid::parse(PICTURE, '#');
+
+        assert_eq!(grid.count(), 3);
+        assert!(grid.get(Position::new(3, 2)));
+        assert!(!grid.get(Position::new(4, 2)));
+        assert_eq!(grid.to_string(), PICTURE);
+    }
+
+    #[test]
+    fn test_shift_drops_cells() {
+        let grid = BitGrid::parse(PICTURE, '#');
+
+        assert_eq!(grid.shift(Direction::East, 1).to_string(), ".#..\n..#.\n....\n");
+        assert_eq!(grid.shift(Direction::North, 1).to_string(), ".#..\n...#\n....\n");
+    }
+
+    #[test]
+    fn test_rotate_wraps_cells() {
+        let grid = BitGrid::parse(PICTURE, '#');
+
+        assert_eq!(grid.rotate(Direction::East, 1).to_string(), ".#..\n..#.\n#...\n");
+        assert_eq!(grid.rotate(Direction::West, 5).to_string(), "...#\n#...\n..#.\n");
+        assert_eq!(grid.rotate(Direction::South, 1).to_string(), "...#\n#...\n.#..\n");
+    }
+
+    #[test]
+    fn test_translate_pad_and_bounds() {
+        let grid = BitGrid::parse(PICTURE, '#');
+
+        assert_eq!(grid.translate(Position::new(-1, 1)).to_string(), "....\n....\n#...\n");
+        assert_eq!(grid.bounds(), Some((Position::new(0, 0), Position::new(3, 2))));
+
+        let padded = grid.pad(2);
+        assert_eq!((padded.width(), padded.height()), (8, 7));
+        assert_eq!(padded.bounds(), Some((Position::new(2, 2), Position::new(5, 4))));
+        assert_eq!(padded.count(), 3);
+        assert_eq!(BitGrid::new(3, 3).bounds(), None);
+    }
+}
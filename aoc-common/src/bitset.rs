@@ -0,0 +1,473 @@
+use std::{
+    fmt::{Debug, Formatter},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr, Sub, SubAssign},
+};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+// shift the bits of src towards higher indices, writing into dst and dropping what falls off
+fn shift_up(src: &[u64], dst: &mut [u64], n: usize) {
+    let (words, bits) = (n / WORD_BITS, n % WORD_BITS);
+    for i in (0..dst.len()).rev() {
+        let lo = i.checked_sub(words).and_then(|j| src.get(j)).copied().unwrap_or(0);
+        let carry = if bits == 0 {
+            0
+        } else {
+            i.checked_sub(words + 1)
+                .and_then(|j| src.get(j))
+                .map(|w| w >> (WORD_BITS - bits))
+                .unwrap_or(0)
+        };
+        dst[i] = (lo << bits) | carry;
+    }
+}
+
+// shift the bits of src towards lower indices, writing into dst and dropping what falls off
+fn shift_down(src: &[u64], dst: &mut [u64], n: usize) {
+    let (words, bits) = (n / WORD_BITS, n % WORD_BITS);
+    for (i, word) in dst.iter_mut().enumerate() {
+        let hi = src.get(i + words).copied().unwrap_or(0);
+        let carry = if bits == 0 {
+            0
+        } else {
+            src.get(i + words + 1)
+                .map(|w| w << (WORD_BITS - bits))
+                .unwrap_or(0)
+        };
+        *word = (hi >> bits) | carry;
+    }
+}
+
+fn iter_words(words: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    words.iter().enumerate().flat_map(|(i, &word)| {
+        let mut word = word;
+        std::iter::from_fn(move || {
+            if word == 0 {
+                None
+            } else {
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * WORD_BITS + bit)
+            }
+        })
+    })
+}
+
+/// Bitset with a capacity of `W * 64` bits, stored inline so it is `Copy` and cheap to hash.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct FixedBitSet<const W: usize> {
+    words: [u64; W],
+}
+
+pub type BitSet64 = FixedBitSet<1>;
+pub type BitSet128 = FixedBitSet<2>;
+
+impl<const W: usize> Default for FixedBitSet<W> {
+    fn default() -> Self {
+        FixedBitSet { words: [0; W] }
+    }
+}
+
+impl<const W: usize> FixedBitSet<W> {
+    pub const CAPACITY: usize = W * WORD_BITS;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // set with bits 0..n set
+    pub fn full(n: usize) -> Self {
+        assert!(n <= Self::CAPACITY, "{} bits exceed capacity {}", n, Self::CAPACITY);
+        let mut set = Self::new();
+        for (i, word) in set.words.iter_mut().enumerate() {
+            let lo = i * WORD_BITS;
+            *word = match n.saturating_sub(lo) {
+                0 => 0,
+                k if k >= WORD_BITS => u64::MAX,
+                k => (1 << k) - 1,
+            };
+        }
+        set
+    }
+
+    pub fn insert(&mut self, bit: usize) -> bool {
+        let was_set = self.contains(bit);
+        self.words[bit / WORD_BITS] |= 1 << (bit % WORD_BITS);
+        !was_set
+    }
+
+    pub fn remove(&mut self, bit: usize) -> bool {
+        let was_set = self.contains(bit);
+        self.words[bit / WORD_BITS] &= !(1 << (bit % WORD_BITS));
+        was_set
+    }
+
+    pub fn toggle(&mut self, bit: usize) {
+        self.words[bit / WORD_BITS] ^= 1 << (bit % WORD_BITS);
+    }
+
+    pub fn contains(&self, bit: usize) -> bool {
+        bit < Self::CAPACITY && self.words[bit / WORD_BITS] & (1 << (bit % WORD_BITS)) != 0
+    }
+
+    // return a copy with the bit set, handy for search states
+    pub fn with(&self, bit: usize) -> Self {
+        let mut set = *self;
+        set.insert(bit);
+        set
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words = [0; W];
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words.iter().zip(other.words.iter()).all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words.iter().zip(other.words.iter()).all(|(a, b)| a & b == 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        iter_words(&self.words)
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+impl<const W: usize> Debug for FixedBitSet<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<const W: usize> FromIterator<usize> for FixedBitSet<W> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for bit in iter {
+            set.insert(bit);
+        }
+        set
+    }
+}
+
+impl<const W: usize> Not for FixedBitSet<W> {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        self.words.iter_mut().for_each(|w| *w = !*w);
+        self
+    }
+}
+
+impl<const W: usize> Shl<usize> for FixedBitSet<W> {
+    type Output = Self;
+
+    fn shl(self, n: usize) -> Self {
+        let mut set = Self::new();
+        shift_up(&self.words, &mut set.words, n);
+        set
+    }
+}
+
+impl<const W: usize> Shr<usize> for FixedBitSet<W> {
+    type Output = Self;
+
+    fn shr(self, n: usize) -> Self {
+        let mut set = Self::new();
+        shift_down(&self.words, &mut set.words, n);
+        set
+    }
+}
+
+/// Growable bitset; trailing zero words are trimmed so equal sets compare and hash equal.
+#[derive(Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(bits: usize) -> Self {
+        BitSet {
+            words: Vec::with_capacity(bits.div_ceil(WORD_BITS)),
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    pub fn insert(&mut self, bit: usize) -> bool {
+        let word = bit / WORD_BITS;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let was_set = self.words[word] & (1 << (bit % WORD_BITS)) != 0;
+        self.words[word] |= 1 << (bit % WORD_BITS);
+        !was_set
+    }
+
+    pub fn remove(&mut self, bit: usize) -> bool {
+        let was_set = self.contains(bit);
+        if was_set {
+            self.words[bit / WORD_BITS] &= !(1 << (bit % WORD_BITS));
+            self.trim();
+        }
+        was_set
+    }
+
+    pub fn toggle(&mut self, bit: usize) {
+        if !self.insert(bit) {
+            self.remove(bit);
+        }
+    }
+
+    pub fn contains(&self, bit: usize) -> bool {
+        self.words
+            .get(bit / WORD_BITS)
+            .map(|w| w & (1 << (bit % WORD_BITS)) != 0)
+            .unwrap_or(false)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    // one past the highest set bit
+    pub fn bit_len(&self) -> usize {
+        match self.words.last() {
+            Some(w) => self.words.len() * WORD_BITS - w.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(i, a)| a & !other.words.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words.iter().zip(other.words.iter()).all(|(a, b)| a & b == 0)
+    }
+
+    // drop all bits at or above n
+    pub fn truncate(&mut self, n: usize) {
+        self.words.truncate(n.div_ceil(WORD_BITS));
+        // only the word holding bit n is cut, it is missing when the set is shorter
+        if !n.is_multiple_of(WORD_BITS) {
+            if let Some(w) = self.words.get_mut(n / WORD_BITS) {
+                *w &= (1 << (n % WORD_BITS)) - 1;
+            }
+        }
+        self.trim();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        iter_words(&self.words)
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    fn shifted_up(&self, n: usize) -> BitSet {
+        if self.is_empty() {
+            return BitSet::new();
+        }
+        let mut words = vec![0; (self.bit_len() + n).div_ceil(WORD_BITS)];
+        shift_up(&self.words, &mut words, n);
+        BitSet { words }
+    }
+
+    fn shifted_down(&self, n: usize) -> BitSet {
+        let mut set = BitSet {
+            words: vec![0; self.words.len().saturating_sub(n / WORD_BITS)],
+        };
+        shift_down(&self.words, &mut set.words, n);
+        set.trim();
+        set
+    }
+
+    fn zip_with(&mut self, other: &BitSet, op: impl Fn(u64, u64) -> u64) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (i, word) in self.words.iter_mut().enumerate() {
+            *word = op(*word, other.words.get(i).copied().unwrap_or(0));
+        }
+        self.trim();
+    }
+}
+
+impl Debug for BitSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for bit in iter {
+            set.insert(bit);
+        }
+        set
+    }
+}
+
+impl Shl<usize> for &BitSet {
+    type Output = BitSet;
+
+    fn shl(self, n: usize) -> BitSet {
+        self.shifted_up(n)
+    }
+}
+
+impl Shr<usize> for &BitSet {
+    type Output = BitSet;
+
+    fn shr(self, n: usize) -> BitSet {
+        self.shifted_down(n)
+    }
+}
+
+macro_rules! set_algebra {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident, $f:expr) => {
+        impl<const W: usize> $assign_op for FixedBitSet<W> {
+            fn $assign_method(&mut self, other: Self) {
+                for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
+                    *a = $f(*a, *b);
+                }
+            }
+        }
+
+        impl<const W: usize> $op for FixedBitSet<W> {
+            type Output = Self;
+
+            fn $method(mut self, other: Self) -> Self {
+                self.$assign_method(other);
+                self
+            }
+        }
+
+        impl $assign_op<&BitSet> for BitSet {
+            fn $assign_method(&mut self, other: &BitSet) {
+                self.zip_with(other, $f);
+            }
+        }
+
+        impl $op for &BitSet {
+            type Output = BitSet;
+
+            fn $method(self, other: &BitSet) -> BitSet {
+                let mut set = self.clone();
+                set.$assign_method(other);
+                set
+            }
+        }
+    };
+}
+
+set_algebra!(BitOr, bitor, BitOrAssign, bitor_assign, |a: u64, b: u64| a | b);
+set_algebra!(BitAnd, bitand, BitAndAssign, bitand_assign, |a: u64, b: u64| a & b);
+set_algebra!(BitXor, bitxor, BitXorAssign, bitxor_assign, |a: u64, b: u64| a ^ b);
+set_algebra!(Sub, sub, SubAssign, sub_assign, |a: u64, b: u64| a & !b);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_insert_remove() {
+        let mut set = BitSet128::new();
+        assert!(set.insert(3));
+        assert!(set.insert(100));
+        assert!(!set.insert(3));
+
+        assert_eq!(set.len(), 2);
+        assert!(set.remove(3));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![100]);
+    }
+
+    #[test]
+    fn test_fixed_algebra() {
+        let a: BitSet64 = [1, 2, 3].into_iter().collect();
+        let b: BitSet64 = [2, 3, 4].into_iter().collect();
+
+        assert_eq!((a | b).len(), 4);
+        assert_eq!((a & b).iter().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!((a - b).iter().collect::<Vec<_>>(), vec![1]);
+        assert_eq!((a ^ b).iter().collect::<Vec<_>>(), vec![1, 4]);
+        assert!((a & b).is_subset(&a));
+    }
+
+    #[test]
+    fn test_fixed_shift_across_words() {
+        let set: BitSet128 = [0, 63, 127].into_iter().collect();
+
+        assert_eq!((set << 1).iter().collect::<Vec<_>>(), vec![1, 64]);
+        assert_eq!((set >> 63).iter().collect::<Vec<_>>(), vec![0, 64]);
+        assert_eq!(BitSet128::full(70).len(), 70);
+    }
+
+    #[test]
+    fn test_growable_equality_ignores_capacity() {
+        let mut a: BitSet = [5, 200].into_iter().collect();
+        a.remove(200);
+        let b: BitSet = [5].into_iter().collect();
+
+        assert_eq!(a, b);
+        assert_eq!(a.bit_len(), 6);
+    }
+
+    #[test]
+    fn test_growable_shift_and_algebra() {
+        let a: BitSet = [0, 62].into_iter().collect();
+        let shifted = &a << 3;
+
+        assert_eq!(shifted.iter().collect::<Vec<_>>(), vec![3, 65]);
+        assert_eq!((&shifted >> 4).iter().collect::<Vec<_>>(), vec![61]);
+        assert_eq!((&a | &shifted).len(), 4);
+        assert!((&a & &shifted).is_empty());
+    }
+
+    #[test]
+    fn test_growable_truncate() {
+        let mut set: BitSet = [1, 24, 70, 130].into_iter().collect();
+        set.truncate(100);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 24, 70]);
+
+        // cutting beyond the last word keeps everything
+        let mut set: BitSet = [1, 24].into_iter().collect();
+        set.truncate(152);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 24]);
+    }
+}
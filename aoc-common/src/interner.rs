@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    ops::Index,
+};
+
+/// Dense id handed out by an [`Interner`], usable as a `Vec` index or bit position.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn id(&self) -> u32 {
+        self.0
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<Symbol> for usize {
+    fn from(symbol: Symbol) -> Self {
+        symbol.index()
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Maps names to dense [`Symbol`]s in order of first appearance.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Interner {
+    names: Vec<String>,
+    symbols: HashMap<String, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    // return the symbol for the name, allocating the next id if it is new
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(name) {
+            return *symbol;
+        }
+        let symbol = Symbol(self.names.len() as u32);
+        self.names.push(name.to_string());
+        self.symbols.insert(name.to_string(), symbol);
+        symbol
+    }
+
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.names.get(symbol.index()).map(|name| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = Symbol> {
+        (0..self.names.len() as u32).map(Symbol)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (Symbol(i as u32), name.as_str()))
+    }
+
+    // wrap a symbol so it displays as its name
+    pub fn display(&self, symbol: Symbol) -> Named<'_> {
+        Named {
+            interner: self,
+            symbol,
+        }
+    }
+}
+
+impl Index<Symbol> for Interner {
+    type Output = str;
+
+    fn index(&self, symbol: Symbol) -> &Self::Output {
+        match self.resolve(symbol) {
+            Some(name) => name,
+            None => panic!("Unknown symbol: {}", symbol),
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Interner {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut interner = Interner::new();
+        for name in iter {
+            interner.intern(name);
+        }
+        interner
+    }
+}
+
+pub struct Named<'a> {
+    interner: &'a Interner,
+    symbol: Symbol,
+}
+
+impl Display for Named<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.interner.resolve(self.symbol) {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{}", self.symbol),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_intern_is_dense_and_stable() {
+        let mut interner = Interner::new();
+        let aa = interner.intern("AA");
+        let bb = interner.intern("BB");
+
+        assert_eq!(aa.index(), 0);
+        assert_eq!(bb.index(), 1);
+        assert_eq!(interner.intern("AA"), aa);
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let interner: Interner = ["root", "humn"].into_iter().collect();
+        let humn = interner.get("humn").unwrap();
+
+        assert_eq!(&interner[humn], "humn");
+        assert_eq!(interner.get("pppw"), None);
+        assert_eq!(interner.resolve(Symbol(7)), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut interner = Interner::new();
+        let symbol = interner.intern("broadcaster");
+
+        assert_eq!(interner.display(symbol).to_string(), "broadcaster");
+        assert_eq!(symbol.to_string(), "#0");
+    }
+}
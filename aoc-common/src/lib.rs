@@ -1,3 +1,10 @@
+pub mod bitgrid;
+pub mod bitset;
 pub mod direction;
 pub mod grid;
+pub mod interner;
+pub mod linear;
+pub mod memo;
+pub mod ocr;
 pub mod position;
+pub mod vm;
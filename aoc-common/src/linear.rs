@@ -0,0 +1,117 @@
+use num::traits::Num;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum LinearError {
+    #[error("Matrix must be square and match the length of the right hand side")]
+    DimensionMismatch,
+    #[error("System has no unique solution")]
+    Singular,
+}
+
+/// Solve `a * x = b` exactly by Gauss-Jordan elimination.
+///
+/// Meant for small dense systems over an exact field such as `Ratio<i128>` or
+/// `BigRational`, so the first non-zero pivot is as good as any other.
+pub fn solve<T>(a: &[Vec<T>], b: &[T]) -> Result<Vec<T>, LinearError>
+where
+    T: Num + Clone,
+{
+    let n = b.len();
+    if a.len() != n || a.iter().any(|row| row.len() != n) {
+        return Err(LinearError::DimensionMismatch);
+    }
+
+    // augmented matrix [a | b]
+    let mut m = a
+        .iter()
+        .zip(b)
+        .map(|(row, rhs)| {
+            let mut row = row.clone();
+            row.push(rhs.clone());
+            row
+        })
+        .collect::<Vec<_>>();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .find(|&r| !m[r][col].is_zero())
+            .ok_or(LinearError::Singular)?;
+        m.swap(col, pivot);
+
+        let p = m[col][col].clone();
+        for v in m[col].iter_mut() {
+            *v = v.clone() / p.clone();
+        }
+
+        let pivot_row = m[col].clone();
+        for (r, row) in m.iter_mut().enumerate() {
+            if r == col || row[col].is_zero() {
+                continue;
+            }
+            let factor = row[col].clone();
+            for (v, p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                *v = v.clone() - factor.clone() * p.clone();
+            }
+        }
+    }
+
+    Ok(m.into_iter().map(|mut row| row.pop().unwrap()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use num::{rational::Ratio, BigInt, BigRational};
+
+    use super::*;
+
+    fn ratios(values: &[i128]) -> Vec<Ratio<i128>> {
+        values.iter().map(|&v| Ratio::from_integer(v)).collect()
+    }
+
+    #[test]
+    fn test_solve_fractions() {
+        // x + 2y = 1, 3x + 4y = 2
+        let a = vec![ratios(&[1, 2]), ratios(&[3, 4])];
+        let b = ratios(&[1, 2]);
+
+        let actual = solve(&a, &b).unwrap();
+        let expected = vec![Ratio::from_integer(0), Ratio::new(1, 2)];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_solve_needs_row_swap() {
+        let a = vec![ratios(&[0, 1, 0]), ratios(&[1, 0, 0]), ratios(&[0, 0, 2])];
+        let b = ratios(&[5, 7, 9]);
+
+        let actual = solve(&a, &b).unwrap();
+        let expected = vec![Ratio::from_integer(7), Ratio::from_integer(5), Ratio::new(9, 2)];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_singular() {
+        let a = vec![ratios(&[1, 2]), ratios(&[2, 4])];
+        let b = ratios(&[1, 2]);
+
+        assert_eq!(solve(&a, &b), Err(LinearError::Singular));
+        assert_eq!(solve(&a, &b[..1]), Err(LinearError::DimensionMismatch));
+    }
+
+    #[test]
+    fn test_big_rationals() {
+        let int = |v: BigInt| BigRational::from_integer(v);
+        let big = int(BigInt::from(10).pow(40));
+        let three = int(3.into());
+        // big * x + y = big^2 + 3, x = big
+        let a = vec![vec![big.clone(), int(1.into())], vec![int(1.into()), int(0.into())]];
+        let b = vec![big.clone() * big.clone() + three.clone(), big.clone()];
+
+        let actual = solve(&a, &b).unwrap();
+
+        assert_eq!(actual, vec![big, three]);
+    }
+}
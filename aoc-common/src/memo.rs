@@ -0,0 +1,165 @@
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    hash::Hash,
+};
+
+/// Keyed cache for recursive dynamic programming which counts its hits and misses.
+///
+/// Lookups take any borrowed form of the key, so callers only pay for building an
+/// owned key when a value is actually stored.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    stats: MemoStats,
+}
+
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl MemoStats {
+    pub fn lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups() == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.lookups() as f64
+        }
+    }
+}
+
+impl Display for MemoStats {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate)",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo {
+            cache: HashMap::new(),
+            stats: MemoStats::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Memo<K, V> {
+        Memo::default()
+    }
+
+    // look up a cached value, counting the lookup as a hit or a miss
+    pub fn get<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = self.cache.get(key).cloned();
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        value
+    }
+
+    // store a value and hand it back, so it can be the tail expression of the recursion
+    pub fn insert(&mut self, key: K, value: V) -> V {
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    // compute and store the value on a miss; the closure gets the memo back for recursion
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+    {
+        match self.get(&key) {
+            Some(value) => value,
+            None => {
+                let value = f(self);
+                self.insert(key, value)
+            }
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.cache.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.stats = MemoStats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn fibonacci(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_insert_with(n, |memo| fibonacci(n - 1, memo) + fibonacci(n - 2, memo))
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        let mut memo = Memo::new();
+
+        assert_eq!(fibonacci(90, &mut memo), 2880067194370816120);
+        assert_eq!(memo.len(), 89);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut memo = Memo::new();
+        fibonacci(10, &mut memo);
+        let stats = memo.stats();
+
+        assert_eq!(stats.misses, 9);
+        assert_eq!(stats.hits, 7);
+        assert_eq!(stats.lookups(), 16);
+    }
+
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut memo: Memo<String, usize> = Memo::new();
+        memo.insert("#.#".to_string(), 2);
+
+        assert_eq!(memo.get("#.#"), Some(2));
+        assert_eq!(memo.get("..."), None);
+        assert_eq!(memo.stats(), MemoStats { hits: 1, misses: 1 });
+    }
+}
@@ -0,0 +1,200 @@
+use std::ops::Range;
+
+use crate::bitgrid::BitGrid;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum OcrError {
+    #[error("No font has glyphs {0} pixels high")]
+    UnsupportedHeight(usize),
+    #[error("Unrecognised glyphs at columns {0:?}")]
+    UnknownGlyphs(Vec<Range<usize>>),
+}
+
+// the 4x6 font of 2016 day 8, 2019 days 8 and 11, 2021 day 13 and 2022 day 10
+const SMALL_FONT: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+// the 6x10 font of 2018 day 10
+const LARGE_FONT: &[(char, &str)] = &[
+    ('A', "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#"),
+    ('B', "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####."),
+    ('C', ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####."),
+    ('E', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('F', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('G', ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#"),
+    ('H', "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#"),
+    ('J', "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###.."),
+    ('K', "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#"),
+    ('L', "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('N', "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#"),
+    ('P', "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('R', "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#"),
+    ('X', "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#"),
+    ('Z', "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######"),
+];
+
+// rows of the glyph in the column range as bitmasks relative to its first column
+fn glyph(grid: &BitGrid, columns: &Range<usize>) -> Vec<u64> {
+    grid.rows()
+        .map(|row| {
+            columns
+                .clone()
+                .filter(|&x| row.contains(x))
+                .fold(0, |mask, x| mask | 1 << (x - columns.start))
+        })
+        .collect()
+}
+
+fn column_is_blank(grid: &BitGrid, x: usize) -> bool {
+    grid.rows().all(|row| !row.contains(x))
+}
+
+// split the picture into runs of non-blank columns, one per letter
+fn glyph_columns(grid: &BitGrid) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for x in 0..=grid.width() {
+        let blank = x == grid.width() || column_is_blank(grid, x);
+        match (start, blank) {
+            (None, false) => start = Some(x),
+            (Some(s), true) => {
+                runs.push(s..x);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+fn font(height: usize) -> Result<Vec<(char, Vec<u64>)>, OcrError> {
+    let font = match height {
+        6 => SMALL_FONT,
+        10 => LARGE_FONT,
+        _ => return Err(OcrError::UnsupportedHeight(height)),
+    };
+    Ok(font
+        .iter()
+        .map(|(letter, picture)| {
+            let grid = BitGrid::parse(picture, '#');
+            let columns = glyph_columns(&grid);
+            let columns = columns[0].start..columns[columns.len() - 1].end;
+            (*letter, glyph(&grid, &columns))
+        })
+        .collect())
+}
+
+/// Read the letters in a bit grid which is exactly one glyph high.
+pub fn read_grid(grid: &BitGrid) -> Result<String, OcrError> {
+    let font = font(grid.height())?;
+    // anything wider cannot be a letter, nor fit the masks
+    let widest = font
+        .iter()
+        .flat_map(|(_, glyph)| glyph)
+        .map(|row| (u64::BITS - row.leading_zeros()) as usize)
+        .max()
+        .unwrap_or(0);
+    let mut letters = String::new();
+    let mut unknown = Vec::new();
+    for columns in glyph_columns(grid) {
+        if columns.len() > widest {
+            unknown.push(columns);
+            continue;
+        }
+        let glyph = glyph(grid, &columns);
+        match font.iter().find(|(_, g)| *g == glyph) {
+            Some((letter, _)) => letters.push(*letter),
+            None => unknown.push(columns),
+        }
+    }
+    if unknown.is_empty() {
+        Ok(letters)
+    } else {
+        Err(OcrError::UnknownGlyphs(unknown))
+    }
+}
+
+/// Read the letters in a picture drawn with `#` for lit pixels.
+pub fn read_letters(picture: &str) -> Result<String, OcrError> {
+    read_grid(&BitGrid::parse(picture.trim_matches('\n'), '#'))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_small_font() {
+        let picture = "\
+####..##....##..##..###....##.###..####.
+#....#..#....#.#..#.#..#....#.#..#.#....
+###..#.......#.#..#.#..#....#.#..#.###..
+#....#.......#.####.###.....#.###..#....
+#....#..#.#..#.#..#.#....#..#.#.#..#....
+#.....##...##..#..#.#.....##..#..#.####.";
+
+        assert_eq!(read_letters(picture).unwrap(), "FCJAPJRE");
+    }
+
+    #[test]
+    fn test_large_font() {
+        let picture = "\
+#....#.....###
+#....#......#.
+#....#......#.
+#....#......#.
+######......#.
+#....#......#.
+#....#......#.
+#....#..#...#.
+#....#..#...#.
+#....#...###..";
+
+        assert_eq!(read_letters(picture).unwrap(), "HJ");
+    }
+
+    #[test]
+    fn test_unknown_glyphs() {
+        let picture = "\
+.##...##..
+#..#.#..#.
+#..#.#..#.
+####.#.##.
+#..#.#..#.
+#..#..##.#";
+
+        assert_eq!(
+            read_letters(picture),
+            Err(OcrError::UnknownGlyphs(vec![Range { start: 5, end: 10 }]))
+        );
+        // a bar wider than any letter, and than a mask can hold
+        let bar = format!("{}\n", "#".repeat(70)).repeat(6);
+        assert_eq!(
+            read_letters(&bar),
+            Err(OcrError::UnknownGlyphs(vec![Range { start: 0, end: 70 }]))
+        );
+        assert_eq!(
+            read_letters("#\n#"),
+            Err(OcrError::UnsupportedHeight(2))
+        );
+    }
+}
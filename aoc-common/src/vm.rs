@@ -0,0 +1,365 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+/// What the machine does after an instruction has executed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Flow {
+    Next,
+    Jump(isize),
+    Halt,
+}
+
+/// An instruction of a puzzle's instruction set.
+///
+/// The register file is chosen by the instruction set, a [`RegisterFile`] of named
+/// registers fits most puzzles.
+pub trait Instruction {
+    type Registers;
+
+    // number of cycles the instruction occupies the machine
+    fn cycles(&self) -> usize;
+
+    fn execute(&self, registers: &mut Self::Registers) -> Flow;
+}
+
+/// Named integer registers.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RegisterFile {
+    names: Vec<String>,
+    values: Vec<i64>,
+}
+
+impl RegisterFile {
+    pub fn new(names: &[&str]) -> RegisterFile {
+        RegisterFile {
+            names: names.iter().map(|n| n.to_string()).collect(),
+            values: vec![0; names.len()],
+        }
+    }
+
+    pub fn with(mut self, name: &str, value: i64) -> RegisterFile {
+        self[name] = value;
+        self
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<i64> {
+        self.position(name).map(|i| self.values[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.names
+            .iter()
+            .map(|n| n.as_str())
+            .zip(self.values.iter().copied())
+    }
+}
+
+impl Index<&str> for RegisterFile {
+    type Output = i64;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        match self.position(name) {
+            Some(i) => &self.values[i],
+            None => panic!("Unknown register: {}", name),
+        }
+    }
+}
+
+impl IndexMut<&str> for RegisterFile {
+    fn index_mut(&mut self, name: &str) -> &mut Self::Output {
+        match self.position(name) {
+            Some(i) => &mut self.values[i],
+            None => panic!("Unknown register: {}", name),
+        }
+    }
+}
+
+impl Display for RegisterFile {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let registers = self
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>();
+        write!(f, "{}", registers.join(" "))
+    }
+}
+
+/// Machine state between instructions; `cycle` is the cycle the next instruction starts in.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct State<R> {
+    pub cycle: usize,
+    pub pc: usize,
+    pub registers: R,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Stop {
+    Halted,
+    Breakpoint(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Machine<'a, I: Instruction> {
+    program: &'a [I],
+    state: State<I::Registers>,
+    breakpoints: BTreeSet<usize>,
+    halted: bool,
+}
+
+impl<'a, I> Machine<'a, I>
+where
+    I: Instruction,
+    I::Registers: Clone,
+{
+    // cycles are counted from 1 like the puzzles do
+    pub fn new(program: &'a [I], registers: I::Registers) -> Self {
+        Machine {
+            program,
+            state: State {
+                cycle: 1,
+                pc: 0,
+                registers,
+            },
+            breakpoints: BTreeSet::new(),
+            halted: false,
+        }
+    }
+
+    pub fn state(&self) -> &State<I::Registers> {
+        &self.state
+    }
+
+    pub fn registers(&self) -> &I::Registers {
+        &self.state.registers
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted || self.state.pc >= self.program.len()
+    }
+
+    fn next_instruction(&self) -> Option<&'a I> {
+        if self.is_halted() {
+            None
+        } else {
+            Some(&self.program[self.state.pc])
+        }
+    }
+
+    pub fn step(&mut self) -> Option<&State<I::Registers>> {
+        let instruction = self.next_instruction()?;
+        self.state.cycle += instruction.cycles();
+        match instruction.execute(&mut self.state.registers) {
+            Flow::Next => self.state.pc += 1,
+            Flow::Jump(offset) => match self.state.pc.checked_add_signed(offset) {
+                Some(pc) => self.state.pc = pc,
+                None => self.halted = true,
+            },
+            Flow::Halt => self.halted = true,
+        }
+        Some(&self.state)
+    }
+
+    // stop before the instruction which would be running during the cycle, breakpoints are one-shot
+    pub fn add_breakpoint(&mut self, cycle: usize) {
+        self.breakpoints.insert(cycle);
+    }
+
+    pub fn run(&mut self) -> Stop {
+        while let Some(instruction) = self.next_instruction() {
+            let end = self.state.cycle + instruction.cycles();
+            if let Some(&cycle) = self.breakpoints.range(..end).next() {
+                self.breakpoints.remove(&cycle);
+                return Stop::Breakpoint(cycle);
+            }
+            self.step();
+        }
+        Stop::Halted
+    }
+
+    // advance to the state whose registers are in effect during the cycle
+    pub fn run_to_cycle(&mut self, cycle: usize) -> Option<&State<I::Registers>> {
+        if cycle < self.state.cycle {
+            return None;
+        }
+        while let Some(instruction) = self.next_instruction() {
+            if self.state.cycle + instruction.cycles() > cycle {
+                break;
+            }
+            self.step();
+        }
+        Some(&self.state)
+    }
+
+    /// The initial state followed by the state after every instruction.
+    pub fn trace(&mut self) -> impl Iterator<Item = State<I::Registers>> + use<'_, 'a, I> {
+        let initial = self.state.clone();
+        std::iter::once(initial).chain(std::iter::from_fn(move || self.step().cloned()))
+    }
+
+    /// Every cycle with the registers in effect during it, until the program ends.
+    pub fn cycles(&mut self) -> impl Iterator<Item = (usize, I::Registers)> + use<'_, 'a, I> {
+        let mut cycle = self.state.cycle;
+        std::iter::from_fn(move || {
+            let end = self.next_instruction()
+                .map(|i| self.state.cycle + i.cycles())?;
+            let registers = self.state.registers.clone();
+            let current = cycle;
+            cycle += 1;
+            if cycle >= end {
+                self.step();
+            }
+            Some((current, registers))
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("Unknown instruction on line {line}: {text}")]
+pub struct AsmError {
+    pub line: usize,
+    pub text: String,
+}
+
+// parse one instruction per line, blank lines are skipped
+pub fn assemble<I: FromStr>(source: &str) -> Result<Vec<I>, AsmError> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            l.trim().parse::<I>().map_err(|_| AsmError {
+                line: i + 1,
+                text: l.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub fn disassemble<I: Display>(program: &[I]) -> String {
+    program
+        .iter()
+        .map(|i| format!("{}\n", i))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    enum Op {
+        Inc(&'static str),
+        Jnz(&'static str, isize),
+        Hlt,
+    }
+
+    impl Instruction for Op {
+        type Registers = RegisterFile;
+
+        fn cycles(&self) -> usize {
+            match self {
+                Op::Inc(_) => 1,
+                Op::Jnz(_, _) => 2,
+                Op::Hlt => 1,
+            }
+        }
+
+        fn execute(&self, registers: &mut RegisterFile) -> Flow {
+            match self {
+                Op::Inc(r) => {
+                    registers[*r] += 1;
+                    Flow::Next
+                }
+                Op::Jnz(r, offset) if registers[*r] % 3 != 0 => Flow::Jump(*offset),
+                Op::Jnz(_, _) => Flow::Next,
+                Op::Hlt => Flow::Halt,
+            }
+        }
+    }
+
+    impl FromStr for Op {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.split_whitespace().collect::<Vec<_>>()[..] {
+                ["inc", "a"] => Ok(Op::Inc("a")),
+                ["jnz", "a", offset] => Ok(Op::Jnz("a", offset.parse().map_err(|_| ())?)),
+                ["hlt"] => Ok(Op::Hlt),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl Display for Op {
+        fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+            match self {
+                Op::Inc(r) => write!(f, "inc {}", r),
+                Op::Jnz(r, offset) => write!(f, "jnz {} {}", r, offset),
+                Op::Hlt => write!(f, "hlt"),
+            }
+        }
+    }
+
+    const PROGRAM: &str = "inc a\njnz a -1\nhlt\ninc a\n";
+
+    #[test]
+    fn test_run_to_halt() {
+        let program = assemble::<Op>(PROGRAM).unwrap();
+        let mut machine = Machine::new(&program, RegisterFile::new(&["a"]));
+
+        assert_eq!(machine.run(), Stop::Halted);
+        assert_eq!(machine.registers()["a"], 3);
+        assert_eq!(machine.state().cycle, 11);
+    }
+
+    #[test]
+    fn test_breakpoint() {
+        let program = assemble::<Op>(PROGRAM).unwrap();
+        let mut machine = Machine::new(&program, RegisterFile::new(&["a"]));
+        machine.add_breakpoint(5);
+
+        assert_eq!(machine.run(), Stop::Breakpoint(5));
+        assert_eq!(machine.state().cycle, 5);
+        assert_eq!(machine.registers()["a"], 2);
+        assert_eq!(machine.run(), Stop::Halted);
+    }
+
+    #[test]
+    fn test_trace_and_cycles() {
+        let program = assemble::<Op>("inc a\njnz a 1\ninc a").unwrap();
+        let trace = Machine::new(&program, RegisterFile::new(&["a"]))
+            .trace()
+            .map(|s| (s.cycle, s.pc, s.registers["a"]))
+            .collect::<Vec<_>>();
+        let cycles = Machine::new(&program, RegisterFile::new(&["a"]))
+            .cycles()
+            .map(|(c, r)| (c, r["a"]))
+            .collect::<Vec<_>>();
+
+        assert_eq!(trace, vec![(1, 0, 0), (2, 1, 1), (4, 2, 1), (5, 3, 2)]);
+        assert_eq!(cycles, vec![(1, 0), (2, 1), (3, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn test_assembler_round_trip() {
+        let program = assemble::<Op>(PROGRAM).unwrap();
+
+        assert_eq!(disassemble(&program), PROGRAM);
+        assert_eq!(
+            assemble::<Op>("inc a\n\ndec a"),
+            Err(AsmError {
+                line: 3,
+                text: "dec a".to_string()
+            })
+        );
+    }
+}
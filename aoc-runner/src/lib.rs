@@ -0,0 +1,396 @@
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum AocError {
+    #[error("Error parsing the arguments: {0}")]
+    ParseError(String),
+    #[error("Error accessing the workspace: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("No days match the selection")]
+    NothingSelected,
+    #[error("{0} day(s) did not finish successfully")]
+    DaysFailed(usize),
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Day {
+    pub year: u32,
+    pub day: u32,
+}
+
+impl Day {
+    pub fn package(&self) -> String {
+        format!("aoc-{}-{}", self.year, self.day)
+    }
+
+    // a selector is either a year like `2022` or a single day like `2022-10`
+    pub fn matches(&self, selectors: &[String]) -> bool {
+        selectors.is_empty()
+            || selectors.iter().any(|s| match s.split_once('-') {
+                Some((year, day)) => {
+                    year.parse() == Ok(self.year) && day.parse() == Ok(self.day)
+                }
+                None => s.parse() == Ok(self.year),
+            })
+    }
+}
+
+impl FromStr for Day {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || AocError::ParseError(s.to_string());
+        let rest = s.strip_prefix("aoc-").ok_or_else(error)?;
+        let (year, day) = rest.split_once('-').ok_or_else(error)?;
+        Ok(Day {
+            year: year.parse().map_err(|_| error())?,
+            day: day.parse().map_err(|_| error())?,
+        })
+    }
+}
+
+impl Display for Day {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}-{:02}", self.year, self.day)
+    }
+}
+
+// all `aoc-<year>-<day>` crates in the workspace, in calendar order
+pub fn discover_days(root: &Path) -> Result<Vec<Day>, AocError> {
+    let mut days = std::fs::read_dir(root)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("Cargo.toml").exists())
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<Day>().ok())
+        .collect::<Vec<_>>();
+    days.sort();
+    Ok(days)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Status {
+    Queued,
+    Running,
+    Done {
+        part1: Option<String>,
+        part2: Option<String>,
+    },
+    Failed(String),
+    TimedOut,
+}
+
+impl Status {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, Status::Queued | Status::Running)
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Status::Done { .. })
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Status::Queued => "queued",
+            Status::Running => "running",
+            Status::Done { .. } => "ok",
+            Status::Failed(_) => "FAILED",
+            Status::TimedOut => "TIMEOUT",
+        }
+    }
+}
+
+// pick the answers out of the `Part1: ...` / `Part2: ...` lines every day prints
+pub fn parse_answers(stdout: &str) -> (Option<String>, Option<String>) {
+    let answer = |prefix: &str| {
+        stdout
+            .lines()
+            .rev()
+            .find_map(|l| l.strip_prefix(prefix))
+            .map(|a| a.trim().to_string())
+    };
+    (answer("Part1:"), answer("Part2:"))
+}
+
+// the panic message if the day panicked, otherwise the last thing it complained about
+pub fn failure_reason(stderr: &str, code: Option<i32>) -> String {
+    let lines = stderr.lines().collect::<Vec<_>>();
+    if let Some(i) = lines.iter().position(|l| l.contains("panicked at")) {
+        let message = lines.get(i + 1).map(|l| l.trim()).unwrap_or("");
+        return format!("panicked: {}", message);
+    }
+    match lines.iter().rev().find(|l| !l.trim().is_empty()) {
+        Some(line) => line.trim().to_string(),
+        None => match code {
+            Some(code) => format!("exit code {}", code),
+            None => "killed by signal".to_string(),
+        },
+    }
+}
+
+// the first error cargo reported for a day that did not build
+pub fn build_failure(stderr: &str) -> String {
+    match stderr.lines().find(|l| l.starts_with("error")) {
+        Some(line) => format!("build: {}", line.trim()),
+        None => "build failed".to_string(),
+    }
+}
+
+fn drain<R: Read + Send + 'static>(mut reader: R) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buffer = String::new();
+        let _ = reader.read_to_string(&mut buffer);
+        buffer
+    })
+}
+
+// run one day's binary in its own process so a panic or hang cannot take the runner down
+pub fn run_binary(binary: &Path, timeout: Duration) -> Status {
+    let mut child = match Command::new(binary)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return Status::Failed(format!("{}: {}", binary.display(), e)),
+    };
+    let stdout = drain(child.stdout.take().unwrap());
+    let stderr = drain(child.stderr.take().unwrap());
+
+    let deadline = Instant::now() + timeout;
+    let exit = loop {
+        match child.try_wait() {
+            Ok(Some(exit)) => break Some(exit),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Status::Failed(e.to_string()),
+        }
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    match exit {
+        None => Status::TimedOut,
+        Some(exit) if exit.success() => {
+            let (part1, part2) = parse_answers(&stdout);
+            Status::Done { part1, part2 }
+        }
+        Some(exit) => Status::Failed(failure_reason(&stderr, exit.code())),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Event {
+    pub index: usize,
+    pub status: Status,
+    pub elapsed: Duration,
+}
+
+/// Run the binaries on `jobs` worker threads, reporting every status change on the channel
+/// under the index given with the binary.
+pub fn run_all(
+    binaries: Vec<(usize, PathBuf)>,
+    jobs: usize,
+    timeout: Duration,
+) -> mpsc::Receiver<Event> {
+    let (tx, rx) = mpsc::channel();
+    let binaries = Arc::new(binaries);
+    let queue = Arc::new(Mutex::new((0..binaries.len()).collect::<VecDeque<_>>()));
+
+    for _ in 0..jobs.max(1) {
+        let (tx, binaries, queue) = (tx.clone(), binaries.clone(), queue.clone());
+        thread::spawn(move || loop {
+            let Some(next) = queue.lock().unwrap().pop_front() else {
+                break;
+            };
+            let (index, binary) = &binaries[next];
+            let index = *index;
+            let started = Instant::now();
+            let _ = tx.send(Event {
+                index,
+                status: Status::Running,
+                elapsed: Duration::ZERO,
+            });
+            let status = run_binary(binary, timeout);
+            let _ = tx.send(Event {
+                index,
+                status,
+                elapsed: started.elapsed(),
+            });
+        });
+    }
+    rx
+}
+
+/// Live table with one row per day, redrawn in place on a terminal.
+pub struct Dashboard {
+    days: Vec<Day>,
+    rows: Vec<(Status, Option<Instant>, Duration)>,
+    drawn: usize,
+}
+
+impl Dashboard {
+    pub fn new(days: Vec<Day>) -> Dashboard {
+        let rows = vec![(Status::Queued, None, Duration::ZERO); days.len()];
+        Dashboard {
+            days,
+            rows,
+            drawn: 0,
+        }
+    }
+
+    pub fn update(&mut self, event: Event) {
+        let row = &mut self.rows[event.index];
+        if event.status == Status::Running {
+            row.1 = Some(Instant::now());
+        }
+        row.0 = event.status;
+        row.2 = event.elapsed;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.rows.iter().all(|(status, _, _)| status.is_finished())
+    }
+
+    pub fn failures(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|(status, _, _)| !status.is_success())
+            .count()
+    }
+
+    pub fn render(&self) -> String {
+        let mut table = format!(
+            "{:<8} {:<8} {:>10}  {:<20} {:<20}\n",
+            "day", "status", "time", "part 1", "part 2"
+        );
+        for (day, (status, started, elapsed)) in self.days.iter().zip(&self.rows) {
+            let elapsed = match (status, started) {
+                (Status::Running, Some(started)) => started.elapsed(),
+                _ => *elapsed,
+            };
+            let time = match status {
+                Status::Queued => String::new(),
+                _ => format!("{:.3}s", elapsed.as_secs_f64()),
+            };
+            let (part1, part2) = match status {
+                Status::Done { part1, part2 } => (
+                    part1.clone().unwrap_or_default(),
+                    part2.clone().unwrap_or_default(),
+                ),
+                Status::Failed(reason) => (reason.clone(), String::new()),
+                _ => (String::new(), String::new()),
+            };
+            table.push_str(&format!(
+                "{:<8} {:<8} {:>10}  {:<20} {:<20}\n",
+                day.to_string(),
+                status.label(),
+                time,
+                part1,
+                part2
+            ));
+        }
+        table
+    }
+
+    // redraw over the previous table
+    pub fn draw(&mut self) -> String {
+        let table = self.render();
+        let mut frame = String::new();
+        if self.drawn > 0 {
+            frame.push_str(&format!("\x1b[{}A", self.drawn));
+        }
+        for line in table.lines() {
+            frame.push_str("\x1b[2K");
+            frame.push_str(line);
+            frame.push('\n');
+        }
+        self.drawn = table.lines().count();
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_day() {
+        let actual = "aoc-2022-10".parse::<Day>().unwrap();
+        let expected = Day {
+            year: 2022,
+            day: 10,
+        };
+
+        assert_eq!(actual, expected);
+        assert_eq!(actual.package(), "aoc-2022-10");
+        assert_eq!(actual.to_string(), "2022-10");
+        assert!("aoc-common".parse::<Day>().is_err());
+    }
+
+    #[test]
+    fn test_selectors() {
+        let day = Day {
+            year: 2023,
+            day: 5,
+        };
+
+        assert!(day.matches(&[]));
+        assert!(day.matches(&["2023".to_string()]));
+        assert!(day.matches(&["2022-1".to_string(), "2023-05".to_string()]));
+        assert!(!day.matches(&["2022".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        let stdout = "debug\nPart1: 24000\n--------------\nPart2: 45000\n";
+
+        let actual = parse_answers(stdout);
+        let expected = (Some("24000".to_string()), Some("45000".to_string()));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_failure_reason() {
+        let stderr = "\nthread 'main' panicked at src/main.rs:3:5:\nno input\nnote: run with `RUST_BACKTRACE=1`\n";
+
+        assert_eq!(failure_reason(stderr, Some(101)), "panicked: no input");
+        assert_eq!(failure_reason("", Some(1)), "exit code 1");
+    }
+
+    #[test]
+    fn test_build_failure() {
+        let stderr = "   Compiling time v0.1.0\nerror[E0282]: type annotations needed\n  --> src/lib.rs:1:1\nerror: could not compile `time`\n";
+
+        assert_eq!(build_failure(stderr), "build: error[E0282]: type annotations needed");
+        assert_eq!(build_failure(""), "build failed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_binary() {
+        // `yes` never stops on its own, `false` fails without saying why
+        assert_eq!(run_binary(Path::new("yes"), Duration::from_millis(200)), Status::TimedOut);
+        assert_eq!(
+            run_binary(Path::new("false"), Duration::from_secs(10)),
+            Status::Failed("exit code 1".to_string())
+        );
+        let missing = run_binary(Path::new("./no-such-day"), Duration::from_secs(10));
+        assert!(matches!(missing, Status::Failed(reason) if reason.starts_with("./no-such-day")));
+    }
+}
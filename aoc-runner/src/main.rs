@@ -0,0 +1,132 @@
+use std::{
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::RecvTimeoutError,
+    thread,
+    time::Duration,
+};
+
+use aoc_runner::{build_failure, discover_days, run_all, AocError, Dashboard, Day, Event, Status};
+
+const USAGE: &str = "usage: aoc-runner [--jobs N] [--timeout SECONDS] [YEAR | YEAR-DAY]...";
+
+struct Options {
+    jobs: usize,
+    timeout: Duration,
+    selectors: Vec<String>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, AocError> {
+    let mut options = Options {
+        jobs: thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+        timeout: Duration::from_secs(60),
+        selectors: Vec::new(),
+    };
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| AocError::ParseError(USAGE.to_string()));
+        match arg.as_str() {
+            "--jobs" | "-j" => {
+                options.jobs = value()?
+                    .parse()
+                    .map_err(|_| AocError::ParseError(USAGE.to_string()))?
+            }
+            "--timeout" | "-t" => {
+                options.timeout = Duration::from_secs(
+                    value()?
+                        .parse()
+                        .map_err(|_| AocError::ParseError(USAGE.to_string()))?,
+                )
+            }
+            "--help" | "-h" => return Err(AocError::ParseError(USAGE.to_string())),
+            _ => options.selectors.push(arg),
+        }
+    }
+    Ok(options)
+}
+
+fn cargo_build(root: &Path, days: &[Day]) -> Command {
+    let mut cargo = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+    cargo.current_dir(root).args(["build", "--release"]);
+    for day in days {
+        cargo.args(["-p", &day.package()]);
+    }
+    cargo
+}
+
+// build the days in one go, and when that fails one by one to find the days which do not build
+fn build(root: &Path, days: &[Day]) -> Result<Vec<Option<String>>, AocError> {
+    if cargo_build(root, days).status()?.success() {
+        return Ok(vec![None; days.len()]);
+    }
+    days.iter()
+        .map(|day| {
+            let output = cargo_build(root, &[*day]).output()?;
+            Ok((!output.status.success())
+                .then(|| build_failure(&String::from_utf8_lossy(&output.stderr))))
+        })
+        .collect()
+}
+
+fn main() -> Result<(), AocError> {
+    let options = parse_args(std::env::args().skip(1))?;
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let target = std::env::var("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| root.join("target"));
+
+    let days = discover_days(&root)?
+        .into_iter()
+        .filter(|day| day.matches(&options.selectors))
+        .collect::<Vec<_>>();
+    if days.is_empty() {
+        return Err(AocError::NothingSelected);
+    }
+    let failures = build(&root, &days)?;
+
+    let binaries = days
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| failures[*i].is_none())
+        .map(|(i, day)| {
+            let binary = format!("{}{}", day.package(), std::env::consts::EXE_SUFFIX);
+            (i, target.join("release").join(binary))
+        })
+        .collect::<Vec<_>>();
+    let events = run_all(binaries, options.jobs, options.timeout);
+    let mut dashboard = Dashboard::new(days);
+    for (index, failure) in failures.into_iter().enumerate() {
+        if let Some(reason) = failure {
+            dashboard.update(Event {
+                index,
+                status: Status::Failed(reason),
+                elapsed: Duration::ZERO,
+            });
+        }
+    }
+    let live = std::io::stdout().is_terminal();
+    let mut stdout = std::io::stdout();
+
+    while !dashboard.is_finished() {
+        match events.recv_timeout(Duration::from_millis(100)) {
+            Ok(event) => dashboard.update(event),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if live {
+            write!(stdout, "{}", dashboard.draw())?;
+            stdout.flush()?;
+        }
+    }
+    if live {
+        write!(stdout, "{}", dashboard.draw())?;
+    } else {
+        write!(stdout, "{}", dashboard.render())?;
+    }
+
+    match dashboard.failures() {
+        0 => Ok(()),
+        n => Err(AocError::DaysFailed(n)),
+    }
+}